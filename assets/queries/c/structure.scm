@@ -0,0 +1,22 @@
+; 函数定义
+(function_definition declarator: (function_declarator declarator: (identifier) @function.name)) @function.declaration
+(function_definition declarator: (pointer_declarator declarator: (function_declarator declarator: (identifier) @function.name))) @function.declaration
+
+; 函数原型声明
+(declaration declarator: (function_declarator declarator: (identifier) @function.name)) @function.declaration
+(declaration declarator: (pointer_declarator declarator: (function_declarator declarator: (identifier) @function.name))) @function.declaration
+
+; 结构体、联合体和枚举定义
+(struct_specifier name: (type_identifier) @struct.name body: (field_declaration_list)) @struct.declaration
+(union_specifier name: (type_identifier) @struct.name body: (field_declaration_list)) @struct.declaration
+(enum_specifier name: (type_identifier) @enum.name body: (enumerator_list)) @enum.declaration
+
+; 类型定义
+(type_definition declarator: (type_identifier) @type.name) @type.declaration
+
+; 宏定义
+(preproc_def name: (identifier) @macro.name) @macro.declaration
+(preproc_function_def name: (identifier) @macro.name) @macro.declaration
+
+; 包含指令
+(preproc_include) @include.declaration
@@ -0,0 +1,43 @@
+; 函数定义
+(function_definition declarator: (function_declarator declarator: (identifier) @function.name)) @function.declaration
+(function_definition declarator: (pointer_declarator declarator: (function_declarator declarator: (identifier) @function.name))) @function.declaration
+
+; 函数原型声明
+(declaration declarator: (function_declarator declarator: (identifier) @function.name)) @function.declaration
+(declaration declarator: (pointer_declarator declarator: (function_declarator declarator: (identifier) @function.name))) @function.declaration
+
+; 结构体、联合体和枚举定义
+(struct_specifier name: (type_identifier) @struct.name body: (field_declaration_list)) @struct.declaration
+(union_specifier name: (type_identifier) @struct.name body: (field_declaration_list)) @struct.declaration
+(enum_specifier name: (type_identifier) @enum.name body: (enumerator_list)) @enum.declaration
+
+; 类型定义
+(type_definition declarator: (type_identifier) @type.name) @type.declaration
+
+; 宏定义
+(preproc_def name: (identifier) @macro.name) @macro.declaration
+(preproc_function_def name: (identifier) @macro.name) @macro.declaration
+
+; 包含指令
+(preproc_include) @include.declaration
+
+; 类外定义的方法、析构函数和运算符
+(function_definition declarator: (function_declarator declarator: [(qualified_identifier) (field_identifier) (destructor_name) (operator_name)] @method.name)) @method.declaration
+
+; 类内声明的方法
+(field_declaration declarator: (function_declarator declarator: [(field_identifier) (destructor_name) (operator_name)] @method.name)) @method.declaration
+(declaration declarator: (function_declarator declarator: (destructor_name) @method.name)) @method.declaration
+
+; 类定义
+(class_specifier name: (type_identifier) @class.name body: (field_declaration_list)) @class.declaration
+
+; 命名空间
+(namespace_definition name: (namespace_identifier) @namespace.name) @namespace.declaration
+
+; 模板
+(template_declaration (function_definition declarator: (function_declarator declarator: (identifier) @template.name))) @template.declaration
+(template_declaration (class_specifier name: (type_identifier) @template.name)) @template.declaration
+(template_declaration (struct_specifier name: (type_identifier) @template.name)) @template.declaration
+
+; 类型别名
+(alias_declaration name: (type_identifier) @type.name) @type.declaration
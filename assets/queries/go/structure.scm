@@ -0,0 +1,16 @@
+; 函数声明
+(function_declaration name: (identifier) @function.name) @function.declaration
+
+; 方法声明（带接收者）
+(method_declaration name: (field_identifier) @method.name) @method.declaration
+
+; 类型声明和类型别名（分析器根据具体类型区分结构体和接口）
+(type_spec name: (type_identifier) @type.name) @type.declaration
+(type_alias name: (type_identifier) @type.name) @type.declaration
+
+; 常量和包级变量
+(const_spec name: (identifier) @const.name) @const.declaration
+(source_file (var_declaration (var_spec name: (identifier) @var.name) @var.declaration))
+
+; 导入声明
+(import_declaration) @import.declaration
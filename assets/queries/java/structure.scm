@@ -0,0 +1,40 @@
+; Class declarations
+(class_declaration name: (identifier) @class.name) @class.declaration
+
+; Interface declarations
+(interface_declaration name: (identifier) @interface.name) @interface.declaration
+
+; Annotation type declarations
+(annotation_type_declaration name: (identifier) @annotation_type.name) @annotation_type.declaration
+
+; Enum declarations
+(enum_declaration name: (identifier) @enum.name) @enum.declaration
+
+; Method declarations
+(method_declaration name: (identifier) @method.name) @method.declaration
+
+; Constructor declarations
+(constructor_declaration name: (identifier) @constructor.name) @constructor.declaration
+
+; Field declarations (instance variables)
+(field_declaration declarator: (variable_declarator name: (identifier) @field.name)) @field.declaration
+
+; Static field declarations
+(field_declaration (modifiers (marker_annotation name: (identifier) @annotation.name))? (type_identifier) (variable_declarator name: (identifier) @static.field.name)) @static.field.declaration
+
+; Package declaration
+(package_declaration (scoped_identifier) @package.name) @package.declaration
+
+; Import statements
+(import_declaration (scoped_identifier) @import.name) @import.declaration
+(import_declaration (asterisk) @import.wildcard) @import.declaration
+
+; Annotations
+(marker_annotation name: (identifier) @annotation.name) @annotation.declaration
+(annotation name: (identifier) @annotation.name) @annotation.declaration
+
+; Method invocations (useful for call graphs, but can be noisy)
+; (method_invocation name: (identifier) @method.call)
+
+; Object creation (constructors)
+; (object_creation_expression type: (type_identifier) @constructor.call)
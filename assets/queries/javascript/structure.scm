@@ -0,0 +1,29 @@
+; 函数声明
+(function_declaration name: (identifier) @function.name) @function.declaration
+(generator_function_declaration name: (identifier) @function.name) @function.declaration
+
+; 赋值给变量的箭头函数和函数表达式
+(variable_declarator name: (identifier) @function.name value: (arrow_function)) @function.declaration
+(variable_declarator name: (identifier) @function.name value: (function_expression)) @function.declaration
+
+; 类定义
+(class_declaration name: (identifier) @class.name) @class.declaration
+
+; 方法定义
+(method_definition name: (property_identifier) @method.name) @method.declaration
+
+; 导出语句
+(export_statement) @export.declaration
+
+; 导入语句
+(import_statement) @import.declaration
+
+; React 组件：首字母大写并返回 JSX 的函数
+(function_declaration
+  name: (identifier) @component.name
+  body: (statement_block (return_statement [(jsx_element) (jsx_self_closing_element) (parenthesized_expression [(jsx_element) (jsx_self_closing_element)])]))
+  (#match? @component.name "^[A-Z]")) @component.declaration
+(variable_declarator
+  name: (identifier) @component.name
+  value: (arrow_function body: [(jsx_element) (jsx_self_closing_element) (parenthesized_expression [(jsx_element) (jsx_self_closing_element)]) (statement_block (return_statement [(jsx_element) (jsx_self_closing_element) (parenthesized_expression [(jsx_element) (jsx_self_closing_element)])]))])
+  (#match? @component.name "^[A-Z]")) @component.declaration
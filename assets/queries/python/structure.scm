@@ -0,0 +1,16 @@
+; 函数定义（包括 async def）
+(function_definition name: (identifier) @function.name) @function.declaration
+
+; 类定义
+(class_definition name: (identifier) @class.name) @class.declaration
+
+; 带装饰器的函数和类（范围包含装饰器行）
+(decorated_definition definition: (function_definition name: (identifier) @decorated.name)) @decorated.declaration
+(decorated_definition definition: (class_definition name: (identifier) @decorated.name)) @decorated.declaration
+
+; 模块的公开 API 列表
+(expression_statement (assignment left: (identifier) @public_api.name (#eq? @public_api.name "__all__"))) @public_api.declaration
+
+; 导入语句
+(import_statement) @import.declaration
+(import_from_statement) @import.declaration
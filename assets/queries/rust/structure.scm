@@ -0,0 +1,49 @@
+; 函数定义
+(function_item) @function.declaration
+(function_item name: (identifier) @function.name)
+
+; trait 中没有默认实现的方法签名
+(function_signature_item) @function.declaration
+(function_signature_item name: (identifier) @function.name)
+
+; 结构体定义
+(struct_item) @struct.declaration
+(struct_item name: (type_identifier) @struct.name)
+
+; 枚举定义
+(enum_item) @enum.declaration
+(enum_item name: (type_identifier) @enum.name)
+
+; 特性定义
+(trait_item) @trait.declaration
+(trait_item name: (type_identifier) @trait.name)
+
+; 实现块
+(impl_item) @impl.declaration
+; TODO: Capture impl target type and trait if present
+
+; 模块定义
+(mod_item) @module.declaration
+(mod_item name: (identifier) @module.name)
+
+; 常量定义
+(const_item) @const.declaration
+(const_item name: (identifier) @const.name)
+
+; 静态变量定义
+(static_item) @static.declaration
+(static_item name: (identifier) @static.name)
+
+; 类型别名
+(type_item) @type_alias.declaration
+(type_item name: (type_identifier) @type_alias.name)
+
+; 宏定义
+(macro_definition) @macro.declaration
+(macro_definition name: (identifier) @macro.name)
+
+; 使用声明
+(use_declaration) @use.declaration
+
+; 属性标记 (捕获整个属性)
+(attribute_item) @attribute
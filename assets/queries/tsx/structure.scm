@@ -0,0 +1,41 @@
+; 函数声明
+(function_declaration name: (identifier) @function.name) @function.declaration
+(generator_function_declaration name: (identifier) @function.name) @function.declaration
+
+; 赋值给变量的箭头函数和函数表达式
+(variable_declarator name: (identifier) @function.name value: (arrow_function)) @function.declaration
+(variable_declarator name: (identifier) @function.name value: (function_expression)) @function.declaration
+
+; 类定义
+(class_declaration name: (type_identifier) @class.name) @class.declaration
+
+; 方法定义
+(method_definition name: (property_identifier) @method.name) @method.declaration
+
+; 导出语句
+(export_statement) @export.declaration
+
+; 导入语句
+(import_statement) @import.declaration
+
+; 接口定义
+(interface_declaration name: (type_identifier) @interface.name) @interface.declaration
+
+; 类型别名
+(type_alias_declaration name: (type_identifier) @type.name) @type.declaration
+
+; 枚举定义
+(enum_declaration name: (identifier) @enum.name) @enum.declaration
+
+; 抽象类
+(abstract_class_declaration name: (type_identifier) @class.name) @class.declaration
+
+; React 组件：首字母大写并返回 JSX 的函数
+(function_declaration
+  name: (identifier) @component.name
+  body: (statement_block (return_statement [(jsx_element) (jsx_self_closing_element) (parenthesized_expression [(jsx_element) (jsx_self_closing_element)])]))
+  (#match? @component.name "^[A-Z]")) @component.declaration
+(variable_declarator
+  name: (identifier) @component.name
+  value: (arrow_function body: [(jsx_element) (jsx_self_closing_element) (parenthesized_expression [(jsx_element) (jsx_self_closing_element)]) (statement_block (return_statement [(jsx_element) (jsx_self_closing_element) (parenthesized_expression [(jsx_element) (jsx_self_closing_element)])]))])
+  (#match? @component.name "^[A-Z]")) @component.declaration
@@ -0,0 +1,31 @@
+; 函数声明
+(function_declaration name: (identifier) @function.name) @function.declaration
+(generator_function_declaration name: (identifier) @function.name) @function.declaration
+
+; 赋值给变量的箭头函数和函数表达式
+(variable_declarator name: (identifier) @function.name value: (arrow_function)) @function.declaration
+(variable_declarator name: (identifier) @function.name value: (function_expression)) @function.declaration
+
+; 类定义
+(class_declaration name: (type_identifier) @class.name) @class.declaration
+
+; 方法定义
+(method_definition name: (property_identifier) @method.name) @method.declaration
+
+; 导出语句
+(export_statement) @export.declaration
+
+; 导入语句
+(import_statement) @import.declaration
+
+; 接口定义
+(interface_declaration name: (type_identifier) @interface.name) @interface.declaration
+
+; 类型别名
+(type_alias_declaration name: (type_identifier) @type.name) @type.declaration
+
+; 枚举定义
+(enum_declaration name: (identifier) @enum.name) @enum.declaration
+
+; 抽象类
+(abstract_class_declaration name: (type_identifier) @class.name) @class.declaration
@@ -215,6 +215,60 @@ pub async fn explain_git_command(
     execute_ai_request(config, messages).await
 }
 
+/// Explains a failed local check command (e.g. a linter or formatter) using AI
+///
+/// # Arguments
+///
+/// * `config` - Application configuration
+/// * `command` - The check command that failed
+/// * `output` - Combined stdout and stderr of the command
+///
+/// # Returns
+///
+/// * `Result<String, AIError>` - AI explanation or error
+pub async fn explain_check_failure(
+    config: &AppConfig,
+    command: &str,
+    output: &str,
+) -> Result<String, AIError> {
+    if output.trim().is_empty() {
+        return Ok("检查命令未产生输出，但执行失败。请手动运行该命令查看详细信息。".to_string());
+    }
+
+    tracing::debug!(
+        "请求 AI 分析检查失败 (命令: {}): {:?}",
+        command,
+        output.chars().take(200).collect::<String>()
+    );
+
+    let system_prompt_content = config
+        .prompts
+        .get("git-master")
+        .cloned()
+        .unwrap_or_else(|| {
+            tracing::warn!("Expert prompt 提示词未在配置中找到，使用空字符串");
+            "".to_string()
+        });
+
+    let user_message = format!(
+        "在提交前运行以下本地检查命令时失败：\n\n命令: {}\n\n输出:\n{}\n\n请简要说明失败原因，并给出修复这些问题的具体步骤。",
+        command, output
+    );
+
+    let messages = vec![
+        ChatMessage {
+            role: "system".to_string(),
+            content: system_prompt_content,
+        },
+        ChatMessage {
+            role: "user".to_string(),
+            content: user_message,
+        },
+    ];
+
+    execute_ai_request(config, messages).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -228,6 +282,7 @@ mod tests {
         let mut config = AppConfig {
             ai: AIConfig::default(),
             tree_sitter: crate::config_management::settings::TreeSitterConfig::default(),
+            commit: crate::config_management::settings::CommitConfig::default(),
             prompts: HashMap::new(),
         };
         config.prompts.insert("git-master".to_string(), "测试提示词".to_string());
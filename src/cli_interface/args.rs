@@ -18,6 +18,13 @@ pub enum GitieSubCommand {
     /// Perform code review with AI assistance.
     #[clap(alias = "rv")]
     Review(ReviewArgs),
+    /// Rewrite the message of an existing commit with AI assistance.
+    Reword(RewordArgs),
+    /// Analyze how far a commit deviates from its task description.
+    Deviation(DeviationArgs),
+    /// Compare the public API between two revisions and suggest a semver bump.
+    #[clap(name = "api-diff")]
+    ApiDiff(ApiDiffArgs),
     // Future: Add(AddArgs)
     // Future: Config(ConfigArgs)
 }
@@ -51,6 +58,31 @@ pub struct CommitArgs {
     #[clap(long = "review")]
     pub review: bool,
 
+    /// How the pre-commit review decides whether to continue:
+    /// 'ask' (default on a terminal), 'block-on-error' or 'never-block' (default otherwise)
+    #[clap(long = "review-policy", value_name = "POLICY", value_parser = ["ask", "block-on-error", "never-block"])]
+    pub review_policy: Option<String>,
+
+    /// Split staged changes into several logical commits proposed by AI
+    #[clap(long = "split")]
+    pub split: bool,
+
+    /// Amend the previous commit, regenerating its message from HEAD^ plus the staged changes
+    #[clap(long = "amend")]
+    pub amend: bool,
+
+    /// Add a Signed-off-by trailer (like git commit --signoff)
+    #[clap(short = 's', long = "signoff")]
+    pub signoff: bool,
+
+    /// GPG-sign the commit, optionally with a specific key (like git commit -S)
+    #[clap(short = 'S', long = "gpg-sign", value_name = "KEYID", num_args = 0..=1, default_missing_value = "")]
+    pub gpg_sign: Option<String>,
+
+    /// Add a trailer to the commit message (like git commit --trailer)
+    #[clap(long = "trailer", value_name = "TRAILER")]
+    pub trailers: Vec<String>,
+
     /// Allow all other flags and arguments to be passed through to the udnerlying `git commit`.
     #[clap(allow_hyphen_values = true, last = true)]
     pub passthrough_args: Vec<String>,
@@ -59,11 +91,20 @@ pub struct CommitArgs {
 /// Arguments for the `review` subcommand
 #[derive(Args, Debug, Clone)]
 pub struct ReviewArgs {
+    /// Revision to review: a single commit (reviewed as `REV^!`), or an `A..B` / `A...B` range
+    #[clap(value_name = "REV")]
+    pub revision: Option<String>,
+
+    /// Review the current branch against its merge-base with BASE (default: the main branch)
+    #[clap(long, value_name = "BASE", num_args = 0..=1, default_missing_value = "")]
+    pub branch: Option<String>,
+
     /// Analysis depth level
     #[clap(long, value_name = "LEVEL", default_value = "normal")]
     pub depth: String,
 
-    /// Focus areas for the review
+    /// Focus areas for the review: comma-separated reviewers
+    /// (security, performance, api, tests, all) or free text
     #[clap(long, value_name = "AREA")]
     pub focus: Option<String>,
 
@@ -91,6 +132,10 @@ pub struct ReviewArgs {
     #[clap(long = "review-ts")]
     pub review_ts: bool,
 
+    /// Interactively apply automatic fixes for mechanical findings
+    #[clap(long)]
+    pub fix: bool,
+
     /// First commit reference
     #[clap(long, value_name = "COMMIT")]
     pub commit1: Option<String>,
@@ -104,6 +149,58 @@ pub struct ReviewArgs {
     pub passthrough_args: Vec<String>,
 }
 
+/// Arguments for the `reword` subcommand
+#[derive(Args, Debug, Clone)]
+pub struct RewordArgs {
+    /// The commit whose message should be rewritten
+    #[clap(value_name = "REV", default_value = "HEAD")]
+    pub rev: String,
+
+    /// Skip the confirmation prompt
+    #[clap(short = 'y', long = "yes")]
+    pub yes: bool,
+}
+
+/// Arguments for the `deviation` subcommand
+#[derive(Args, Debug, Clone)]
+pub struct DeviationArgs {
+    /// Task description: a path to a file, or the description text itself
+    #[clap(long, value_name = "FILE|TEXT")]
+    pub task: String,
+
+    /// The commit (or commit range A..B) to analyze
+    #[clap(value_name = "REV", default_value = "HEAD")]
+    pub rev: String,
+
+    /// Disable Tree-sitter analysis
+    #[clap(long = "no-ts")]
+    pub no_tree_sitter: bool,
+
+    /// Output format: text or json
+    #[clap(long, value_name = "FORMAT", default_value = "text")]
+    pub format: String,
+}
+
+/// Arguments for the `api-diff` subcommand
+#[derive(Args, Debug, Clone)]
+pub struct ApiDiffArgs {
+    /// The old revision, e.g. the last release tag
+    #[clap(value_name = "REV1")]
+    pub rev1: String,
+
+    /// The new revision
+    #[clap(value_name = "REV2", default_value = "HEAD")]
+    pub rev2: String,
+
+    /// Only compare these paths
+    #[clap(last = true, value_name = "PATHS")]
+    pub paths: Vec<String>,
+
+    /// Output format: text or json
+    #[clap(long, value_name = "FORMAT", default_value = "text")]
+    pub format: String,
+}
+
 /// Checks if a slice of string arguments contains "-h" or "--help".
 #[inline]
 pub fn args_contain_help(args: &[String]) -> bool {
@@ -204,18 +301,48 @@ pub fn generate_gitie_help() -> String {
     help.push_str("                      启用 Tree-sitter 语法分析以改进提交信息\n");
     help.push_str("      -a, --all       自动暂存所有已跟踪的修改文件（类似 git commit -a）\n");
     help.push_str("      -m, --message   直接传递消息给提交\n");
-    help.push_str("      --review        在提交前执行代码评审\n\n");
+    help.push_str("      --review        在提交前执行代码评审\n");
+    help.push_str("      --review-policy=POLICY\n");
+    help.push_str("                      评审后的处理策略: ask（终端下默认）, block-on-error, never-block（非终端默认）\n");
+    help.push_str("      --split         由 AI 将暂存的变更拆分为多个逻辑提交\n");
+    help.push_str("      --amend         修补上一次提交，并根据 HEAD^ 与暂存区的差异重新生成提交信息\n");
+    help.push_str("      -s, --signoff   添加 Signed-off-by 签名\n");
+    help.push_str("      -S, --gpg-sign[=KEYID]\n");
+    help.push_str("                      使用 GPG 签名提交\n");
+    help.push_str("      --trailer=TRAILER\n");
+    help.push_str("                      为提交消息添加 trailer（可重复）\n");
+    help.push_str("      -n, --no-verify 跳过配置的提交前检查（[commit] pre_commit_checks）及 git 钩子\n\n");
+
+    help.push_str("  reword <REV>        使用 AI 重写指定提交的提交信息（默认: HEAD）\n");
+    help.push_str("    选项:\n");
+    help.push_str("      -y, --yes       跳过确认，直接改写\n\n");
+
+    help.push_str("  deviation [REV]     分析提交（或提交范围 A..B）与开发任务的偏差度（默认: HEAD）\n");
+    help.push_str("    选项:\n");
+    help.push_str("      --task=FILE|TEXT 任务描述文件路径或任务描述文本（必需）\n");
+    help.push_str("      --no-ts         禁用 Tree-sitter 结构分析\n");
+    help.push_str("      --format=FORMAT 输出格式: text (默认), json\n\n");
+
+    help.push_str("  api-diff REV1 [REV2] [-- <paths>]\n");
+    help.push_str("                      比较两个版本（REV2 默认: HEAD）的公共 API，区分破坏性、新增和内部变更，\n");
+    help.push_str("                      并给出建议的语义化版本升级\n");
+    help.push_str("    选项:\n");
+    help.push_str("      --format=FORMAT 输出格式: text (默认), json\n\n");
 
-    help.push_str("  review (rv)         执行 AI 辅助的代码评审\n");
+    help.push_str("  review (rv) [REV|A..B|A...B] [-- <paths>]\n");
+    help.push_str("                      执行 AI 辅助的代码评审（默认: 暂存区或工作区的变更）\n");
     help.push_str("    选项:\n");
     help.push_str("      --depth=LEVEL   分析深度级别 (默认: normal)\n");
-    help.push_str("      --focus=AREA    评审重点区域\n");
+    help.push_str("      --focus=AREA    评审重点区域；security,performance,api,tests 或 all\n");
+    help.push_str("                      选择专项评审角色，各角色单独评审后合并为一份报告\n");
     help.push_str("      --lang=LANGUAGE 限制分析到特定语言\n");
     help.push_str("      --format=FORMAT 输出格式 (默认: text)\n");
     help.push_str("      --output=FILE   输出文件\n");
     help.push_str("      --ts            使用 Tree-sitter 进行增强代码分析（默认）\n");
     help.push_str("      --no-ts         禁用 Tree-sitter 分析\n");
     help.push_str("      --review-ts     结合评审与 tree-sitter 分析\n");
+    help.push_str("      --fix           逐个确认并应用可自动修复问题的补丁\n");
+    help.push_str("      --branch[=BASE] 评审当前分支相对于其与 BASE（默认: 主分支）合并基础的全部变更\n");
     help.push_str("      --commit1=COMMIT 第一个提交引用\n");
     help.push_str("      --commit2=COMMIT 第二个提交引用（如果比较两个提交）\n\n");
 
@@ -226,9 +353,16 @@ pub fn generate_gitie_help() -> String {
     help.push_str("示例:\n");
     help.push_str("  gitie commit        使用 AI 辅助生成提交信息\n");
     help.push_str("  gitie commit --noai 禁用 AI，使用标准 git commit\n");
+    help.push_str("  gitie reword HEAD~2 使用 AI 重写倒数第三个提交的提交信息\n");
+    help.push_str("  gitie deviation --task task.md HEAD\n");
+    help.push_str("                      评估最新提交与 task.md 中任务描述的偏差度\n");
+    help.push_str("  gitie api-diff v1.2.0 HEAD\n");
+    help.push_str("                      检查自 v1.2.0 发布以来的公共 API 变更及建议的版本号\n");
     help.push_str("  gitie review        对当前更改执行 AI 辅助代码评审\n");
     help.push_str("  gitie review --depth=deep --focus=\"性能问题\"\n");
     help.push_str("                      执行深度代码评审，重点关注性能问题\n");
+    help.push_str("  gitie review --focus=security,performance\n");
+    help.push_str("                      分别进行安全和性能评审，去重排序后合并结果\n");
 
     help
 }
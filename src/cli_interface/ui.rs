@@ -2,3 +2,46 @@
 // This file is now a re-export of the app_context::args module.
 // All original content has been moved to src/app_context/args.rs
 
+use crate::core::errors::AppError;
+use colored::Colorize;
+use std::io::Write;
+
+/// 向用户提问并读取回答
+///
+/// # Arguments
+///
+/// * `question` - 显示给用户的问题
+/// * `choices` - 显示在问题后的可选回答，例如 `y/n`
+///
+/// # Returns
+///
+/// * `Result<String, AppError>` - 去掉首尾空白并转为小写的回答，或读取输入时的错误
+pub fn ask(question: &str, choices: &str) -> Result<String, AppError> {
+    print!("\n{} ({}): ", question.bold().yellow(), choices);
+    std::io::stdout()
+        .flush()
+        .map_err(|e| AppError::IO("刷新标准输出失败".to_string(), e))?;
+
+    let mut input = String::new();
+    std::io::stdin()
+        .read_line(&mut input)
+        .map_err(|e| AppError::IO("读取用户输入失败".to_string(), e))?;
+
+    Ok(input.trim().to_lowercase())
+}
+
+/// 向用户提出是/否问题并读取回答
+///
+/// 仅当用户输入 `y` 或 `yes`（不区分大小写）时返回 true
+///
+/// # Arguments
+///
+/// * `question` - 显示给用户的问题
+///
+/// # Returns
+///
+/// * `Result<bool, AppError>` - 用户是否确认，或读取输入时的错误
+pub fn confirm(question: &str) -> Result<bool, AppError> {
+    let answer = ask(question, "y/n")?;
+    Ok(answer == "y" || answer == "yes")
+}
@@ -0,0 +1,254 @@
+//! 公共 API 变更检测命令
+//!
+//! `gitie api-diff <rev1> <rev2>` 读取两个版本中变更文件的内容，比较其公共 API，
+//! 列出破坏性、新增和内部变更并给出建议的语义化版本升级。提交流程也使用这里的函数，
+//! 在暂存的变更包含破坏性 API 变更时为提交消息加上 `BREAKING CHANGE` 标记。
+
+use crate::{
+    cli_interface::args::ApiDiffArgs,
+    command_processing::review::{FileVersionSource, read_file_version, revision_exists},
+    config_management::settings::AppConfig,
+    core::errors::AppError,
+    git_module::{execute_git_command_and_capture_output, get_repository_root},
+    tree_sitter_analyzer::{ApiReport, FileVersions, SemverBump, TreeSitterAnalyzer},
+};
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+
+/// 提交消息中表示破坏性变更的页脚前缀
+const BREAKING_CHANGE_FOOTER: &str = "BREAKING CHANGE";
+
+/// `BREAKING CHANGE` 页脚中最多列出的破坏性变更数量，其余的只给出数量
+const MAX_FOOTER_CHANGES: usize = 5;
+
+/// 解析 `git diff --name-status -M` 的输出，返回每个文件的 (变更前路径, 变更后路径)
+pub fn parse_name_status(output: &str) -> Vec<(Option<PathBuf>, Option<PathBuf>)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let status = fields.next()?.chars().next()?;
+            let first = PathBuf::from(fields.next()?);
+            let second = fields.next().map(PathBuf::from);
+            Some(match (status, second) {
+                ('A', _) => (None, Some(first)),
+                ('D', _) => (Some(first), None),
+                // 复制的源文件仍然存在，只比较新文件
+                ('C', Some(second)) => (None, Some(second)),
+                ('R', Some(second)) => (Some(first), Some(second)),
+                _ => (Some(first.clone()), Some(first)),
+            })
+        })
+        .collect()
+}
+
+/// 按 `git diff --name-status` 的参数列出变更文件，读取其变更前后的内容
+///
+/// 只保留分析器支持的语言；`old_source` 为 None 时所有文件都视为新增。
+fn collect_file_versions(
+    analyzer: &TreeSitterAnalyzer,
+    diff_args: Vec<String>,
+    old_source: Option<&FileVersionSource>,
+    new_source: &FileVersionSource,
+) -> Result<Vec<FileVersions>, AppError> {
+    let root = get_repository_root()?;
+    let output = execute_git_command_and_capture_output(&diff_args)?;
+    if !output.is_success() {
+        return Err(AppError::Generic(format!("无法列出变更文件: {}", output.stderr.trim())));
+    }
+
+    let supported = |path: &Option<PathBuf>| {
+        path.as_deref().is_some_and(|p| analyzer.detect_language(p).ok().flatten().is_some())
+    };
+    let read = |path: &Option<PathBuf>, source: Option<&FileVersionSource>| -> Option<String> {
+        read_file_version(&root, path.as_deref()?, source?)
+    };
+    Ok(parse_name_status(&output.stdout)
+        .into_iter()
+        .filter(|(old_path, new_path)| supported(old_path) || supported(new_path))
+        .map(|(old_path, new_path)| {
+            let old_path = old_path.filter(|_| old_source.is_some());
+            FileVersions {
+                old_source: read(&old_path, old_source),
+                new_source: read(&new_path, Some(new_source)),
+                old_path,
+                new_path,
+            }
+        })
+        .collect())
+}
+
+fn new_analyzer(config: &AppConfig, root: &Path) -> Result<TreeSitterAnalyzer, AppError> {
+    let mut analyzer = TreeSitterAnalyzer::new(config.tree_sitter.clone())?;
+    analyzer.set_project_root(root.to_path_buf());
+    Ok(analyzer)
+}
+
+/// 比较暂存区与 HEAD（修补提交时为 HEAD^）之间的公共 API
+///
+/// 没有父提交或比较失败时返回 None，不影响提交流程。
+pub(crate) fn staged_api_report(config: &AppConfig, amend: bool) -> Option<ApiReport> {
+    let base = if amend { "HEAD^" } else { "HEAD" };
+    if !revision_exists(base) {
+        return None;
+    }
+    let root = get_repository_root().ok()?;
+    let mut analyzer = new_analyzer(config, &root).ok()?;
+    let diff_args = ["diff", "--cached", "--name-status", "-M", base].map(String::from).to_vec();
+    let old_source = FileVersionSource::Revision(base.to_string());
+    match collect_file_versions(&analyzer, diff_args, Some(&old_source), &FileVersionSource::Index) {
+        Ok(files) if !files.is_empty() => Some(analyzer.diff_public_api(&files)),
+        Ok(_) => None,
+        Err(e) => {
+            tracing::warn!("无法比较暂存区的公共 API: {}", e);
+            None
+        }
+    }
+}
+
+/// 为约定式提交的标题加上 `!`，如 `feat(api): x` → `feat(api)!: x`；非约定式标题保持不变
+fn mark_breaking_header(header: &str) -> String {
+    let Some((prefix, description)) = header.split_once(": ") else {
+        return header.to_string();
+    };
+    let commit_type = prefix.split('(').next().unwrap_or_default();
+    let conventional = !commit_type.is_empty()
+        && commit_type.chars().all(|c| c.is_ascii_alphanumeric())
+        && (prefix == commit_type || prefix.ends_with(')'));
+    if conventional {
+        format!("{}!: {}", prefix, description)
+    } else {
+        header.to_string()
+    }
+}
+
+/// 存在破坏性 API 变更时为提交消息加上 `BREAKING CHANGE` 标记
+///
+/// 约定式标题加上 `!`，并追加列出破坏性变更的 `BREAKING CHANGE:` 页脚（最多列出
+/// [`MAX_FOOTER_CHANGES`] 项）；消息中已有该页脚时保持不变。
+pub fn mark_breaking_change(message: &str, report: &ApiReport) -> String {
+    let breaking = report.breaking_summary();
+    if breaking.is_empty() || message.contains(BREAKING_CHANGE_FOOTER) || message.contains("BREAKING-CHANGE") {
+        return message.to_string();
+    }
+    let message = message.trim_end();
+    let (header, body) = message.split_once('\n').unwrap_or((message, ""));
+    let mut marked = mark_breaking_header(header);
+    if !body.trim().is_empty() {
+        marked.push('\n');
+        marked.push_str(body);
+    }
+    let mut footer = breaking[..breaking.len().min(MAX_FOOTER_CHANGES)].join("；");
+    if breaking.len() > MAX_FOOTER_CHANGES {
+        footer.push_str(&format!("；等共 {} 项", breaking.len()));
+    }
+    format!("{}\n\n{}: {}", marked, BREAKING_CHANGE_FOOTER, footer)
+}
+
+fn colored_bump(bump: SemverBump) -> colored::ColoredString {
+    match bump {
+        SemverBump::Major => bump.as_str().red().bold(),
+        SemverBump::Minor => bump.as_str().yellow().bold(),
+        SemverBump::Patch => bump.as_str().green().bold(),
+        SemverBump::None => bump.as_str().normal(),
+    }
+}
+
+/// Handles the `api-diff` subcommand
+///
+/// # Arguments
+///
+/// * `args` - api-diff arguments from CLI
+/// * `config` - Application configuration
+///
+/// # Returns
+///
+/// * `Result<(), AppError>` - Success or an error
+pub async fn handle_api_diff(args: ApiDiffArgs, config: &AppConfig) -> Result<(), AppError> {
+    tracing::info!("比较 {} 与 {} 之间的公共 API", args.rev1, args.rev2);
+    for rev in [&args.rev1, &args.rev2] {
+        if !revision_exists(rev) {
+            return Err(AppError::Generic(format!("无法解析版本 {}", rev)));
+        }
+    }
+
+    let root = get_repository_root()?;
+    let mut analyzer = new_analyzer(config, &root)?;
+    let mut diff_args = ["diff", "--name-status", "-M"].map(String::from).to_vec();
+    diff_args.extend([args.rev1.clone(), args.rev2.clone()]);
+    if !args.paths.is_empty() {
+        diff_args.push("--".to_string());
+        diff_args.extend(args.paths.iter().cloned());
+    }
+    let old_source = FileVersionSource::Revision(args.rev1.clone());
+    let new_source = FileVersionSource::Revision(args.rev2.clone());
+    let files = collect_file_versions(&analyzer, diff_args, Some(&old_source), &new_source)?;
+    let report = analyzer.diff_public_api(&files);
+
+    if args.format.eq_ignore_ascii_case("json") {
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| AppError::Generic(format!("无法序列化 API 变更报告: {}", e)))?;
+        println!("{}", json);
+    } else {
+        println!("{}", format!("公共 API 变更: {} → {}", args.rev1, args.rev2).bold().green());
+        println!("建议版本升级: {}\n", colored_bump(report.bump));
+        println!("{}", report.to_markdown());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter_analyzer::api_surface::{ApiChange, ApiImpact};
+
+    #[test]
+    fn test_parse_name_status() {
+        let output = "M\tsrc/lib.rs\nA\tsrc/new.rs\nD\tsrc/old.rs\nR087\tsrc/a.rs\tsrc/b.rs\nC100\tsrc/c.rs\tsrc/d.rs\n";
+        let files = parse_name_status(output);
+        assert_eq!(files, vec![
+            (Some(PathBuf::from("src/lib.rs")), Some(PathBuf::from("src/lib.rs"))),
+            (None, Some(PathBuf::from("src/new.rs"))),
+            (Some(PathBuf::from("src/old.rs")), None),
+            (Some(PathBuf::from("src/a.rs")), Some(PathBuf::from("src/b.rs"))),
+            (None, Some(PathBuf::from("src/d.rs"))),
+        ]);
+    }
+
+    #[test]
+    fn test_mark_breaking_change() {
+        let report = ApiReport {
+            changes: vec![ApiChange {
+                impact: ApiImpact::Breaking,
+                kind: "function".to_string(),
+                name: "parse".to_string(),
+                path: PathBuf::from("src/lib.rs"),
+                line: 1,
+                description: "签名变更".to_string(),
+                old_signature: None,
+                new_signature: None,
+            }],
+            bump: SemverBump::Major,
+            skipped: Vec::new(),
+        };
+        assert_eq!(
+            mark_breaking_change("feat(parser): 支持严格模式\n\n新增 strict 参数", &report),
+            "feat(parser)!: 支持严格模式\n\n新增 strict 参数\n\nBREAKING CHANGE: 签名变更 function `parse`"
+        );
+        assert_eq!(
+            mark_breaking_change("更新解析器", &report),
+            "更新解析器\n\nBREAKING CHANGE: 签名变更 function `parse`"
+        );
+        let marked = "feat!: x\n\nBREAKING CHANGE: 已说明";
+        assert_eq!(mark_breaking_change(marked, &report), marked);
+        assert_eq!(mark_breaking_change("fix: y", &ApiReport::default()), "fix: y");
+
+        let mut many = report.clone();
+        many.changes = (0..8)
+            .map(|i| ApiChange { name: format!("f{}", i), ..report.changes[0].clone() })
+            .collect();
+        let marked = mark_breaking_change("fix: z", &many);
+        assert!(marked.ends_with("签名变更 function `f4`；等共 8 项"), "{}", marked);
+        assert!(!marked.contains("`f5`"));
+    }
+}
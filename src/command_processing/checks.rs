@@ -0,0 +1,272 @@
+//! 提交前检查模块
+//!
+//! 在生成 AI 提交消息之前，对暂存的文件运行配置中的本地检查命令（如 `cargo fmt --check`、
+//! `cargo clippy`、`mvn -q spotless:check`）。任一命令失败时汇总输出并中止提交，
+//! 可选地由 AI 解释失败原因并给出修复建议。
+//!
+//! 注意：检查命令运行在工作区上，看到的是文件的工作区内容而非暂存内容。
+//! 暂存文件同时存在未暂存的修改时，会在输出中提示这些文件。
+
+use crate::{
+    ai_module::explainer::explain_check_failure,
+    config_management::settings::AppConfig,
+    core::errors::AppError,
+    git_module::{execute_git_command_and_capture_output, get_repository_root},
+};
+use colored::Colorize;
+use std::path::Path;
+use std::process::Command;
+
+/// 命令中替换为暂存文件列表的占位符
+const FILES_PLACEHOLDER: &str = "{files}";
+
+/// 传递给检查命令的暂存文件环境变量（以换行分隔）
+const STAGED_FILES_ENV: &str = "GITIE_STAGED_FILES";
+
+/// 汇总中显示的失败输出的最大行数
+const MAX_SUMMARY_LINES: usize = 20;
+
+/// 发送给 AI 解释的失败输出的最大行数
+const MAX_EXPLAIN_LINES: usize = 80;
+
+/// 单个检查命令的执行结果
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    /// 实际执行的命令
+    pub command: String,
+    /// 是否成功
+    pub success: bool,
+    /// 合并后的标准输出和标准错误
+    pub output: String,
+}
+
+/// 获取暂存区中新增、复制、修改或重命名的文件
+fn get_staged_files() -> Result<Vec<String>, AppError> {
+    let args: Vec<String> = ["diff", "--staged", "--name-only", "--diff-filter=ACMR"]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+    let output = execute_git_command_and_capture_output(&args)?;
+    if !output.is_success() {
+        tracing::warn!("无法获取暂存文件列表: {}", output.stderr.trim());
+        return Ok(Vec::new());
+    }
+    Ok(output
+        .stdout
+        .lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect())
+}
+
+/// 从给定的暂存文件中找出在工作区还有未暂存修改的文件
+fn get_files_with_unstaged_changes(files: &[String]) -> Vec<String> {
+    let args: Vec<String> = ["diff", "--name-only"].iter().map(|a| a.to_string()).collect();
+    let output = match execute_git_command_and_capture_output(&args) {
+        Ok(output) if output.is_success() => output,
+        Ok(output) => {
+            tracing::warn!("无法获取未暂存文件列表: {}", output.stderr.trim());
+            return Vec::new();
+        }
+        Err(e) => {
+            tracing::warn!("无法获取未暂存文件列表: {}", e);
+            return Vec::new();
+        }
+    };
+    let unstaged: Vec<&str> = output.stdout.lines().map(str::trim).collect();
+    files.iter().filter(|f| unstaged.contains(&f.as_str())).cloned().collect()
+}
+
+/// 为 shell 转义单个参数
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:@+=".contains(c))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// 将命令中的 `{files}` 占位符替换为转义后的暂存文件列表
+///
+/// # Arguments
+///
+/// * `command` - 配置中的检查命令
+/// * `files` - 暂存的文件
+///
+/// # Returns
+///
+/// * `String` - 可直接交给 shell 执行的命令
+pub fn expand_check_command(command: &str, files: &[String]) -> String {
+    if !command.contains(FILES_PLACEHOLDER) {
+        return command.to_string();
+    }
+    let quoted = files
+        .iter()
+        .map(|f| shell_quote(f))
+        .collect::<Vec<_>>()
+        .join(" ");
+    command.replace(FILES_PLACEHOLDER, &quoted)
+}
+
+/// 通过 shell 在仓库根目录执行单个检查命令
+///
+/// 暂存文件列表中的路径相对于仓库根目录，因此在子目录中运行 gitie 时也要切换到根目录。
+fn run_check_command(command: &str, files: &[String], root: &Path) -> Result<CheckResult, AppError> {
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    cmd.current_dir(root).env(STAGED_FILES_ENV, files.join("\n"));
+
+    tracing::debug!("执行提交前检查: {}", command);
+    let output = cmd
+        .output()
+        .map_err(|e| AppError::IO(format!("无法执行检查命令: {}", command), e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let combined = match (stdout.trim().is_empty(), stderr.trim().is_empty()) {
+        (false, false) => format!("{}\n{}", stdout.trim_end(), stderr.trim_end()),
+        (false, true) => stdout.trim_end().to_string(),
+        _ => stderr.trim_end().to_string(),
+    };
+
+    Ok(CheckResult {
+        command: command.to_string(),
+        success: output.status.success(),
+        output: combined,
+    })
+}
+
+/// 返回输出的最后若干行
+fn tail_lines(output: &str, max_lines: usize) -> String {
+    let lines: Vec<&str> = output.lines().collect();
+    let start = lines.len().saturating_sub(max_lines);
+    let mut tail = lines[start..].join("\n");
+    if start > 0 {
+        tail = format!("... (省略 {} 行)\n{}", start, tail);
+    }
+    tail
+}
+
+/// 运行配置中的提交前检查
+///
+/// 没有配置检查命令或没有暂存文件时直接返回。任一检查失败时打印汇总，
+/// 按配置请求 AI 解释失败原因，并返回错误以中止提交。
+///
+/// # Arguments
+///
+/// * `config` - 应用配置
+///
+/// # Returns
+///
+/// * `Result<(), AppError>` - 全部检查通过时返回 Ok
+pub async fn run_pre_commit_checks(config: &AppConfig) -> Result<(), AppError> {
+    let checks = &config.commit.pre_commit_checks;
+    if checks.is_empty() {
+        return Ok(());
+    }
+
+    let files = get_staged_files()?;
+    if files.is_empty() {
+        tracing::debug!("没有需要检查的暂存文件，跳过提交前检查");
+        return Ok(());
+    }
+
+    let root = get_repository_root()?;
+    println!("{}", format!("正在对 {} 个暂存文件运行提交前检查...", files.len()).bold());
+
+    let partially_staged = get_files_with_unstaged_changes(&files);
+    if !partially_staged.is_empty() {
+        println!(
+            "{}",
+            "注意：以下文件还有未暂存的修改，检查针对的是工作区内容而非暂存内容:".yellow()
+        );
+        for file in &partially_staged {
+            println!("  {}", file.yellow());
+        }
+    }
+
+    let mut results = Vec::new();
+    for check in checks.iter().filter(|c| !c.trim().is_empty()) {
+        let command = expand_check_command(check, &files);
+        let result = run_check_command(&command, &files, &root)?;
+        if result.success {
+            println!("  {} {}", "✔".green(), check);
+        } else {
+            println!("  {} {}", "✘".red(), check);
+        }
+        results.push(result);
+    }
+
+    let failures: Vec<&CheckResult> = results.iter().filter(|r| !r.success).collect();
+    if failures.is_empty() {
+        return Ok(());
+    }
+
+    for failure in &failures {
+        println!("\n{} {}", "检查失败:".red().bold(), failure.command);
+        if !failure.output.is_empty() {
+            println!("{}", tail_lines(&failure.output, MAX_SUMMARY_LINES));
+        }
+
+        if config.commit.explain_check_failures {
+            match explain_check_failure(
+                config,
+                &failure.command,
+                &tail_lines(&failure.output, MAX_EXPLAIN_LINES),
+            )
+            .await {
+                Ok(explanation) => {
+                    println!("\n{}\n{}", "【Gitie AI 帮助】".bold().yellow(), explanation);
+                }
+                Err(e) => tracing::warn!("无法获取 AI 解释: {}", e),
+            }
+        }
+    }
+
+    Err(AppError::Generic(format!(
+        "提交前检查失败（{}/{} 项未通过），已中止提交。修复问题后重试，或使用 --no-verify 跳过检查",
+        failures.len(),
+        results.len()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_check_command_quotes_files() {
+        let files = vec!["src/main.rs".to_string(), "docs/my file.md".to_string(), "it's.txt".to_string()];
+        assert_eq!(
+            expand_check_command("rustfmt --check {files}", &files),
+            "rustfmt --check src/main.rs 'docs/my file.md' 'it'\\''s.txt'"
+        );
+        assert_eq!(expand_check_command("cargo fmt --check", &files), "cargo fmt --check");
+        assert_eq!(tail_lines("a\nb\nc", 2), "... (省略 1 行)\nb\nc");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_command_runs_in_repository_root() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("src")).unwrap();
+        std::fs::write(root.path().join("src/lib.rs"), "").unwrap();
+        let files = vec!["src/lib.rs".to_string()];
+
+        let command = expand_check_command("test -f {files}", &files);
+        assert!(run_check_command(&command, &files, root.path()).unwrap().success);
+        let result = run_check_command("pwd", &files, root.path()).unwrap();
+        assert_eq!(Path::new(&result.output).canonicalize().unwrap(), root.path().canonicalize().unwrap());
+    }
+}
@@ -2,10 +2,16 @@ use crate::{
     ai_module::utils::{ChatMessage, OpenAIChatCompletionResponse, OpenAIChatRequest, clean_ai_output},
     config_management::settings::AppConfig,
     cli_interface::args::CommitArgs,
+    command_processing::api_diff::{mark_breaking_change, staged_api_report},
+    command_processing::commit_options::{contains_commit_option, filter_commit_args, gpg_sign_option},
+    command_processing::issues::{append_issue_context, current_issue_keys, enforce_issue_footer},
+    command_processing::review::{FileVersionSource, read_file_version},
+    command_processing::style::append_style_context,
     core::errors::{AIError, AppError, GitError},
     git_module::map_output_to_git_command_error,
     tree_sitter_analyzer::TreeSitterAnalyzer,
 };
+use colored::Colorize;
 use std::{
     process::Command,
     path::PathBuf,
@@ -22,7 +28,7 @@ use std::{
 /// # 返回值
 /// 
 /// * `Result<PathBuf, AppError>` - 临时文件路径或错误
-fn create_commit_message_file(message: &str) -> Result<PathBuf, AppError> {
+pub(crate) fn create_commit_message_file(message: &str) -> Result<PathBuf, AppError> {
     let temp_dir = std::env::temp_dir();
     let temp_file_path = temp_dir.join(format!("gitie_commit_msg_{}.txt", std::process::id()));
     std::fs::write(&temp_file_path, message)
@@ -35,62 +41,279 @@ fn create_commit_message_file(message: &str) -> Result<PathBuf, AppError> {
 /// # 参数
 /// 
 /// * `file_path` - 临时文件路径
-fn cleanup_commit_message_file(file_path: &PathBuf) {
+pub(crate) fn cleanup_commit_message_file(file_path: &PathBuf) {
     if let Err(e) = std::fs::remove_file(file_path) {
         tracing::warn!("无法删除临时提交消息文件: {}", e);
     }
 }
 
-/// 判断是否是Tree-sitter相关标志
-fn is_tree_sitter_flag(arg: &str) -> bool {
-    arg == "--tree-sitter" || 
-    arg == "-t" || 
-    arg.starts_with("--tree-sitter=")
+/// 过滤透传给 git commit 的参数
+///
+/// 当启用了 auto_stage 时移除 -a/--all，并始终移除 gitie 自己的选项（`--tree-sitter`、`--ai`、
+/// `--noai`、`--review`、`--review-policy`、`--split`）及其值。
+/// 参数按照 git commit 的选项语法解析，不会误伤带值选项（如 `-S<keyid>`、`-m<msg>`）中的字符
+pub(crate) fn filter_passthrough_args(args: &CommitArgs) -> Vec<String> {
+    filter_commit_args(&args.passthrough_args, |option| {
+        option.is_gitie_option() || (args.auto_stage && option.is("all"))
+    })
 }
 
-/// 判断标志是否包含tree-sitter选项
-fn contains_tree_sitter_option(arg: &str) -> bool {
-    // 检查是否是包含't'的短标志组合（如 -at）
-    arg.starts_with('-') && !arg.starts_with("--") && arg.contains('t')
+/// 使用 AI 根据用户提示生成提交消息
+///
+/// 系统提示词取自配置中的 `commit` 提示，返回清理后的提交消息
+pub(crate) async fn generate_ai_commit_message(
+    config: &AppConfig,
+    user_prompt: String,
+) -> Result<String, AppError> {
+    let messages = vec![
+        ChatMessage {
+            role: "system".to_string(),
+            content: config.prompts.get("commit").cloned().unwrap_or_else(|| {
+                tracing::warn!("在配置中未找到 Commit Message Generator 提示词，使用空字符串");
+                "".to_string()
+            }),
+        },
+        ChatMessage {
+            role: "user".to_string(),
+            content: user_prompt,
+        },
+    ];
+    let req_payload = OpenAIChatRequest {
+        model: config.ai.model_name.clone(),
+        messages,
+        temperature: Some(config.ai.temperature),
+        stream: false,
+    };
+    if let Ok(json_str) = serde_json::to_string_pretty(&req_payload) {
+        tracing::debug!("AI req:\n{}", json_str);
+    }
+
+    let client = reqwest::Client::new();
+    let mut builder = client.post(&config.ai.api_url);
+    if let Some(key) = &config.ai.api_key {
+        builder = builder.bearer_auth(key);
+    }
+    let ai_resp = builder
+        .json(&req_payload)
+        .send()
+        .await
+        .map_err(AIError::RequestFailed)?;
+
+    if !ai_resp.status().is_success() {
+        let code = ai_resp.status();
+        let body = ai_resp.text().await.unwrap_or_else(|_| "<no body>".into());
+        tracing::error!("AI API 请求失败，状态码 {}: {}", code, body);
+        return Err(AppError::AI(AIError::ApiResponseError(code, body)));
+    }
+
+    let resp_data = ai_resp
+        .json::<OpenAIChatCompletionResponse>()
+        .await
+        .map_err(AIError::ResponseParseFailed)?;
+    let ai_msg = resp_data.choices.get(0).map_or("", |c| &c.message.content);
+    let final_msg = clean_ai_output(ai_msg).trim().to_string();
+
+    if final_msg.is_empty() {
+        tracing::error!("AI 返回了空消息。");
+        return Err(AppError::AI(AIError::EmptyMessage));
+    }
+
+    Ok(final_msg)
 }
 
-/// 判断标志是否包含自动暂存选项
-fn contains_auto_stage_option(arg: &str) -> bool {
-    // 检查是否是包含'a'的短标志组合（如 -at）
-    arg.starts_with('-') && !arg.starts_with("--") && arg.contains('a')
+/// 空树对象的哈希，用于与根提交进行比较
+pub(crate) const EMPTY_TREE_HASH: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// 判断是否为修补提交（--amend 标志或透传参数中的 --amend）
+fn is_amend_commit(args: &CommitArgs) -> bool {
+    args.amend || contains_commit_option(&args.passthrough_args, "amend")
 }
 
-/// 判断参数是否是Tree-sitter标志的值
-fn is_tree_sitter_value(args: &[String], index: usize) -> bool {
-    if index == 0 {
-        return false;
+/// 读取指定提交的完整提交消息
+///
+/// # 参数
+///
+/// * `rev` - 提交引用
+///
+/// # 返回值
+///
+/// * `Result<String, AppError>` - 提交消息或错误
+pub(crate) fn get_commit_message(rev: &str) -> Result<String, AppError> {
+    let output = Command::new("git")
+        .args(["log", "-1", "--format=%B", rev])
+        .output()
+        .map_err(|e| AppError::IO(format!("无法读取提交 {} 的消息", rev), e))?;
+    if !output.status.success() {
+        return Err(map_output_to_git_command_error(&format!("git log -1 --format=%B {}", rev), output).into());
     }
-    
-    // 检查前一个参数是否是tree-sitter标志
-    let prev_arg = &args[index - 1];
-    if (prev_arg == "--tree-sitter" || prev_arg == "-t") && !args[index].starts_with('-') {
-        return true;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// 获取修补提交所对应的完整差异
+///
+/// 即 HEAD^ 与暂存区之间的差异（HEAD 的改动加上新暂存的改动），
+/// 当 HEAD 为根提交时与空树比较
+fn get_amend_diff() -> Result<String, AppError> {
+    let parent = Command::new("git")
+        .args(["rev-parse", "--verify", "-q", "HEAD^"])
+        .output()
+        .map_err(|e| AppError::Git(GitError::DiffError(e)))?;
+    let base = if parent.status.success() {
+        "HEAD^".to_string()
+    } else {
+        let head = Command::new("git")
+            .args(["rev-parse", "--verify", "-q", "HEAD"])
+            .output()
+            .map_err(|e| AppError::Git(GitError::DiffError(e)))?;
+        if !head.status.success() {
+            return Err(AppError::Generic("当前仓库还没有任何提交，无法使用 --amend".to_string()));
+        }
+        EMPTY_TREE_HASH.to_string()
+    };
+
+    let diff_out = Command::new("git")
+        .args(["diff", "--staged", &base])
+        .output()
+        .map_err(|e| AppError::Git(GitError::DiffError(e)))?;
+    if !diff_out.status.success() {
+        return Err(map_output_to_git_command_error(&format!("git diff --staged {}", base), diff_out).into());
     }
-    
-    false
+    Ok(String::from_utf8_lossy(&diff_out.stdout).into_owned())
 }
 
-/// 从短标志组合中创建新的参数，移除特定选项
-fn create_filtered_short_option(arg: &str, remove_options: &[char]) -> Option<String> {
-    if !arg.starts_with('-') || arg.starts_with("--") {
+/// 在提示中附加原提交消息，供 AI 在重新生成时参考
+pub(crate) fn append_previous_message_context(prompt: String, previous_message: &str) -> String {
+    if previous_message.trim().is_empty() {
+        return prompt;
+    }
+    format!(
+        "{}\n\n原提交消息（仅供参考，请保留其中仍然成立的意图和关联信息，并根据上面的完整变更更新内容）:\n{}",
+        prompt,
+        previous_message.trim()
+    )
+}
+
+/// 构建签名、签署和 trailer 相关的 git commit 参数
+///
+/// 合并命令行标志与配置中的 `signoff` 和 `trailers`，由 git 自身完成 trailer 的格式化与去重
+pub(crate) fn commit_trailer_args(args: &CommitArgs, config: &AppConfig) -> Vec<String> {
+    let mut git_args = Vec::new();
+
+    if args.signoff || config.commit.signoff {
+        git_args.push("--signoff".to_string());
+    }
+
+    match args.gpg_sign.as_deref() {
+        Some("") => git_args.push("--gpg-sign".to_string()),
+        Some(key) => git_args.push(format!("--gpg-sign={}", key)),
+        None => {}
+    }
+
+    for trailer in config.commit.trailers.iter().chain(args.trailers.iter()) {
+        if !trailer.trim().is_empty() {
+            git_args.push("--trailer".to_string());
+            git_args.push(trailer.trim().to_string());
+        }
+    }
+
+    git_args
+}
+
+/// 读取 git 配置的 commit.template 内容
+///
+/// 注释行会被移除，未配置或模板为空时返回 None
+pub(crate) fn load_commit_template() -> Option<String> {
+    let output = Command::new("git")
+        .args(["config", "--path", "--get", "commit.template"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
         return None;
     }
-    
-    // 过滤掉不需要的选项
-    let filtered: String = arg.chars()
-        .filter(|c| *c == '-' || !remove_options.contains(c))
-        .collect();
-    
-    // 如果只剩下'-'，返回None
-    if filtered == "-" {
+
+    let template_path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if template_path.is_empty() {
+        return None;
+    }
+
+    let content = match std::fs::read_to_string(&template_path) {
+        Ok(content) => content,
+        Err(e) => {
+            tracing::warn!("无法读取提交模板 {}: {}", template_path, e);
+            return None;
+        }
+    };
+
+    let template = content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if template.trim().is_empty() {
         None
     } else {
-        Some(filtered)
+        tracing::info!("使用提交模板: {}", template_path);
+        Some(template.trim().to_string())
+    }
+}
+
+/// 在提示中附加 commit.template，要求 AI 按模板结构生成提交消息
+fn append_template_context(prompt: String, template: Option<&str>) -> String {
+    match template {
+        Some(template) => format!(
+            "{}\n\n本仓库配置了提交模板（commit.template），请严格按照以下模板的结构填写提交消息：\n```\n{}\n```",
+            prompt, template
+        ),
+        None => prompt,
+    }
+}
+
+/// 读取 git 配置 `commit.gpgsign`
+fn git_config_gpgsign() -> bool {
+    Command::new("git")
+        .args(["config", "--bool", "commit.gpgsign"])
+        .output()
+        .is_ok_and(|out| out.status.success() && String::from_utf8_lossy(&out.stdout).trim() == "true")
+}
+
+/// 判断提交是否应当被签名
+///
+/// gitie 的 `--gpg-sign` 标志优先，其次是透传参数中最后一个 `-S`/`--no-gpg-sign`，
+/// 最后是 git 配置 `commit.gpgsign`
+fn expects_signature(args: &CommitArgs) -> bool {
+    args.gpg_sign.is_some() || gpg_sign_option(&args.passthrough_args).unwrap_or_else(git_config_gpgsign)
+}
+
+/// 提交完成后验证签名和 Signed-off-by 是否生效
+///
+/// 提交已经完成，因此只输出警告而不返回错误
+fn verify_commit_trailers(args: &CommitArgs, config: &AppConfig) {
+    if args.signoff || config.commit.signoff || contains_commit_option(&args.passthrough_args, "signoff") {
+        let output = Command::new("git")
+            .args(["log", "-1", "--format=%(trailers:key=Signed-off-by,valueonly)"])
+            .output();
+        match output {
+            Ok(out) if out.status.success() && !String::from_utf8_lossy(&out.stdout).trim().is_empty() => {
+                tracing::debug!("已验证 Signed-off-by 签名");
+            }
+            _ => {
+                tracing::warn!("提交中未找到 Signed-off-by 签名");
+                eprintln!("{}", "警告: 提交中未找到 Signed-off-by 签名，请检查提交消息。".yellow());
+            }
+        }
+    }
+
+    if expects_signature(args) {
+        let output = Command::new("git").args(["log", "-1", "--format=%G?"]).output();
+        match output {
+            Ok(out) if out.status.success() && String::from_utf8_lossy(&out.stdout).trim() != "N" => {
+                tracing::debug!("已验证提交签名");
+            }
+            _ => {
+                tracing::warn!("提交未被 GPG 签名");
+                eprintln!("{}", "警告: 提交未被 GPG 签名，请检查 GPG 配置。".yellow());
+            }
+        }
     }
 }
 
@@ -99,6 +322,7 @@ fn create_filtered_short_option(arg: &str, remove_options: &[char]) -> Option<St
 /// # Arguments
 ///
 /// * `args` - Commit arguments from CLI
+/// * `config` - Application configuration
 /// * `context_msg` - Context message for logging
 ///
 /// # Returns
@@ -106,6 +330,7 @@ fn create_filtered_short_option(arg: &str, remove_options: &[char]) -> Option<St
 /// * `Result<(), AppError>` - Success or an error
 pub async fn handle_commit_passthrough(
     args: CommitArgs,
+    config: &AppConfig,
     context_msg: String,
 ) -> Result<(), AppError> {
     tracing::info!(
@@ -123,6 +348,10 @@ pub async fn handle_commit_passthrough(
         git_args.push("-a".to_string());
     }
 
+    if args.amend {
+        git_args.push("--amend".to_string());
+    }
+
     let temp_file_path_opt = if let Some(message) = &args.message {
         // 创建提交消息临时文件
         let temp_file_path = create_commit_message_file(message)?;
@@ -134,52 +363,11 @@ pub async fn handle_commit_passthrough(
         None
     };
 
+    // 签名、签署和 trailer
+    git_args.extend(commit_trailer_args(&args, config));
+
     // Add remaining args, but exclude -a, -all if auto_stage is true, and tree-sitter flags with their values
-    for (i, arg) in args.passthrough_args.iter().enumerate() {
-        // 判断是否是特定标志
-        let is_auto_stage_flag = arg == "-a" || arg == "--all";
-        let is_ts_flag = is_tree_sitter_flag(arg);
-        let is_ts_value = is_tree_sitter_value(&args.passthrough_args, i);
-        
-        // 处理组合的短标志
-        let contains_auto_stage = contains_auto_stage_option(arg);
-        let contains_ts = contains_tree_sitter_option(arg);
-        
-        if (args.auto_stage && (is_auto_stage_flag || contains_auto_stage)) || 
-           is_ts_flag || 
-           is_ts_value {
-            // 跳过这些标志
-            continue;
-        }
-        
-        // 处理包含多个选项的组合短标志
-        if contains_auto_stage && contains_ts {
-            // 如果同时包含auto-stage和tree-sitter选项，则需要特殊处理
-            // 移除 'a' 和 't'
-            if let Some(filtered_arg) = create_filtered_short_option(arg, &['a', 't']) {
-                if filtered_arg != "-" {
-                    git_args.push(filtered_arg.to_string());
-                }
-            }
-        } else if args.auto_stage && contains_auto_stage {
-            // 如果启用了auto-stage，移除 'a'
-            if let Some(filtered_arg) = create_filtered_short_option(arg, &['a']) {
-                if filtered_arg != "-" {
-                    git_args.push(filtered_arg.to_string());
-                }
-            }
-        } else if contains_ts {
-            // 如果包含tree-sitter选项，移除 't'
-            if let Some(filtered_arg) = create_filtered_short_option(arg, &['t']) {
-                if filtered_arg != "-" {
-                    git_args.push(filtered_arg.to_string());
-                }
-            }
-        } else {
-            // 没有特殊处理的情况，直接添加
-            git_args.push(arg.clone());
-        }
-    }
+    git_args.extend(filter_passthrough_args(&args));
 
     let cmd_desc = format!(
         "commit (passthrough {}) args: {:?}",
@@ -195,11 +383,17 @@ pub async fn handle_commit_passthrough(
         cleanup_commit_message_file(&temp_file_path);
     }
     
-    if let Err(e) = result {
-        tracing::error!("传递 git {} 失败", cmd_desc);
-        return Err(e);
-    }
+    let output = match result {
+        Ok(output) => output,
+        Err(e) => {
+            tracing::error!("传递 git {} 失败", cmd_desc);
+            return Err(e);
+        }
+    };
     
+    if output.is_success() {
+        verify_commit_trailers(&args, config);
+    }
     tracing::info!(
         "传递 git {} 已成功启动/完成。",
         cmd_desc
@@ -286,12 +480,15 @@ async fn generate_enhanced_prompt_with_tree_sitter(
         }
     }
 
-    // 执行分析
-    match analyzer.analyze_diff(diff_text) {
-        Ok(_) => {
+    // 执行分析：diff 来自暂存区（修补提交时为 HEAD^ 与暂存区之间），变更后的内容读取暂存区，
+    // 部分暂存的文件不会按工作区内容错位
+    let root = analyzer.project_root.clone();
+    let analysis = analyzer
+        .analyze_diff_with_sources(diff_text, &|path| read_file_version(&root, path, &FileVersionSource::Index));
+    match analysis {
+        Ok(analysis) => {
             // 生成增强提示
-            // Call generate_commit_prompt with both required parameters
-            let commit_prompt = analyzer.generate_commit_prompt(diff_text, config).await?;
+            let commit_prompt = analyzer.generate_commit_prompt(&analysis, config).await?;
             let enhanced_prompt = format!(
                 "Git diff:\n{}\n\n{}\nGenerate commit message.",
                 diff_text.trim(),
@@ -341,30 +538,37 @@ pub async fn handle_commit(args: CommitArgs, config: &AppConfig) -> Result<(), A
             }
         }
 
-        let diff_out = Command::new("git")
-            .arg("diff")
-            .arg("--staged")
-            .output()
-            .map_err(|e| AppError::Git(GitError::DiffError(e)))?;
-        if !diff_out.status.success() {
-            tracing::error!("获取 git diff 时出错。是否有任何更改已暂存以供提交？");
-            return Err(map_output_to_git_command_error("git diff --staged", diff_out).into());
-        }
-        let diff = String::from_utf8_lossy(&diff_out.stdout);
+        let amend = is_amend_commit(&args);
+        let diff = if amend {
+            tracing::info!("修补提交: 使用 HEAD^ 与暂存区之间的差异重新生成消息");
+            get_amend_diff()?
+        } else {
+            let diff_out = Command::new("git")
+                .arg("diff")
+                .arg("--staged")
+                .output()
+                .map_err(|e| AppError::Git(GitError::DiffError(e)))?;
+            if !diff_out.status.success() {
+                tracing::error!("获取 git diff 时出错。是否有任何更改已暂存以供提交？");
+                return Err(map_output_to_git_command_error("git diff --staged", diff_out).into());
+            }
+            String::from_utf8_lossy(&diff_out.stdout).into_owned()
+        };
         if diff.trim().is_empty() {
             tracing::info!("AI 提交: 没有暂存的更改。检查是否使用了 --allow-empty。");
-            if args.passthrough_args.contains(&"--allow-empty".to_string()) {
+            if contains_commit_option(&args.passthrough_args, "allow-empty") {
                 let passthrough_commit_args = CommitArgs {
                     ai: false,
                     noai: true,
-                    auto_stage: args.auto_stage,
                     tree_sitter: None,
                     message: None,
                     review: false,
-                    passthrough_args: args.passthrough_args.clone(),
+                    split: false,
+                    ..args.clone()
                 };
                 return handle_commit_passthrough(
                     passthrough_commit_args,
+                    config,
                     "(AI commit with --allow-empty and no diff)".to_string(),
                 )
                 .await;
@@ -374,6 +578,16 @@ pub async fn handle_commit(args: CommitArgs, config: &AppConfig) -> Result<(), A
         }
         tracing::debug!("Staged changes for AI: \n{}", diff);
 
+        // 生成消息前运行配置的提交前检查，--no-verify 时跳过
+        if !contains_commit_option(&args.passthrough_args, "no-verify") {
+            super::checks::run_pre_commit_checks(config).await?;
+        }
+
+        // 拆分模式：由 AI 将暂存的变更划分为多个提交
+        if args.split && super::split::handle_commit_split(&args, config).await? {
+            return Ok(());
+        }
+
         // 检查是否应该使用Tree-sitter分析
         let use_tree_sitter = should_use_tree_sitter(&args, config);
         
@@ -393,58 +607,40 @@ pub async fn handle_commit(args: CommitArgs, config: &AppConfig) -> Result<(), A
             format!("Git diff:\n{}\nGenerate commit message.", diff.trim())
         };
 
-        let messages = vec![
-            ChatMessage {
-                role: "system".to_string(),
-                content: config.prompts.get("commit").cloned().unwrap_or_else(|| {
-                    tracing::warn!("在配置中未找到 Commit Message Generator 提示词，使用空字符串");
-                    "".to_string()
-                }),
-            },
-            ChatMessage {
-                role: "user".to_string(),
-                content: user_prompt,
-            },
-        ];
-        let req_payload = OpenAIChatRequest {
-            model: config.ai.model_name.clone(),
-            messages,
-            temperature: Some(config.ai.temperature),
-            stream: false,
+        // 修补提交时附带原提交消息作为上下文
+        let user_prompt = if amend {
+            let previous_message = get_commit_message("HEAD")?;
+            append_previous_message_context(user_prompt, &previous_message)
+        } else {
+            user_prompt
         };
-        if let Ok(json_str) = serde_json::to_string_pretty(&req_payload) {
-            tracing::debug!("AI req:\n{}", json_str);
-        }
 
-        let client = reqwest::Client::new();
-        let mut builder = client.post(&config.ai.api_url);
-        if let Some(key) = &config.ai.api_key {
-            builder = builder.bearer_auth(key);
-        }
-        let ai_resp = builder
-            .json(&req_payload)
-            .send()
-            .await
-            .map_err(AIError::RequestFailed)?;
-
-        if !ai_resp.status().is_success() {
-            let code = ai_resp.status();
-            let body = ai_resp.text().await.unwrap_or_else(|_| "<no body>".into());
-            tracing::error!("AI API 请求失败，状态码 {}: {}", code, body);
-            return Err(AppError::AI(AIError::ApiResponseError(code, body)));
-        }
+        // 附加从分支名中提取的工单号
+        let issue_keys = current_issue_keys(&config.commit);
+        let user_prompt = append_issue_context(user_prompt, &issue_keys, &config.commit);
 
-        let resp_data = ai_resp
-            .json::<OpenAIChatCompletionResponse>()
-            .await
-            .map_err(AIError::ResponseParseFailed)?;
-        let ai_msg = resp_data.choices.get(0).map_or("", |c| &c.message.content);
-        let final_msg = clean_ai_output(ai_msg).trim().to_string();
+        // 附加从仓库历史中学习到的提交风格
+        let user_prompt = append_style_context(user_prompt, &config.commit);
 
-        if final_msg.is_empty() {
-            tracing::error!("AI 返回了空消息。");
-            return Err(AppError::AI(AIError::EmptyMessage));
-        }
+        // 遵循 commit.template（使用 -F 时 git 不会再应用模板）
+        let user_prompt = append_template_context(user_prompt, load_commit_template().as_deref());
+
+        let final_msg = generate_ai_commit_message(config, user_prompt).await?;
+
+        // 配置开启时，暂存的变更包含破坏性公共 API 变更则标记 BREAKING CHANGE
+        let api_report = if use_tree_sitter && config.commit.mark_breaking_changes {
+            staged_api_report(config, amend)
+        } else {
+            None
+        };
+        let final_msg = match api_report {
+            Some(report) if report.has_breaking_changes() => {
+                tracing::info!("检测到破坏性公共 API 变更，标记 BREAKING CHANGE");
+                mark_breaking_change(&final_msg, &report)
+            }
+            _ => final_msg,
+        };
+        let final_msg = enforce_issue_footer(&final_msg, &issue_keys, &config.commit);
         tracing::info!("AI 消息:\n---\n{}\n---", final_msg);
 
         // 创建提交消息临时文件
@@ -452,67 +648,36 @@ pub async fn handle_commit(args: CommitArgs, config: &AppConfig) -> Result<(), A
         
         // 使用 -F 从文件读取提交消息
         let mut git_args = vec!["commit".to_string(), "-F".to_string(), temp_file_path.to_string_lossy().to_string()];
+        if args.amend {
+            git_args.push("--amend".to_string());
+        }
+        git_args.extend(commit_trailer_args(&args, config));
         
         // Filter out -a, --all from passthrough_args if auto_stage=true, and tree-sitter flags with their values
-        for (i, p_arg) in args.passthrough_args.iter().enumerate() {
-            // 判断是否是特定标志
-            let is_auto_stage_flag = p_arg == "-a" || p_arg == "--all";
-            let is_ts_flag = is_tree_sitter_flag(p_arg);
-            let is_ts_value = is_tree_sitter_value(&args.passthrough_args, i);
-            
-            // 处理组合的短标志
-            let contains_auto_stage = contains_auto_stage_option(p_arg);
-            let contains_ts = contains_tree_sitter_option(p_arg);
-            
-            if (args.auto_stage && (is_auto_stage_flag || contains_auto_stage)) || 
-               is_ts_flag || 
-               is_ts_value {
-                // 跳过这些标志
-                continue;
-            }
-            
-            // 处理包含多个选项的组合短标志
-            if contains_auto_stage && contains_ts {
-                // 如果同时包含auto-stage和tree-sitter选项，则需要特殊处理
-                // 移除 'a' 和 't'
-                if let Some(filtered_arg) = create_filtered_short_option(p_arg, &['a', 't']) {
-                    if filtered_arg != "-" {
-                        git_args.push(filtered_arg.to_string());
-                    }
-                }
-            } else if args.auto_stage && contains_auto_stage {
-                // 如果启用了auto-stage，移除 'a'
-                if let Some(filtered_arg) = create_filtered_short_option(p_arg, &['a']) {
-                    if filtered_arg != "-" {
-                        git_args.push(filtered_arg.to_string());
-                    }
-                }
-            } else if contains_ts {
-                // 如果包含tree-sitter选项，移除 't'
-                if let Some(filtered_arg) = create_filtered_short_option(p_arg, &['t']) {
-                    if filtered_arg != "-" {
-                        git_args.push(filtered_arg.to_string());
-                    }
-                }
-            } else {
-                // 没有特殊处理的情况，直接添加
-                git_args.push(p_arg.clone());
-            }
-        }
+        git_args.extend(filter_passthrough_args(&args));
 
         // 使用 git_module 中的函数执行 git commit
         tracing::debug!("执行 Git 命令: git {}", git_args.join(" "));
         
         let result = crate::git_module::passthrough_to_git_with_error_handling(&git_args, true);
-        if let Err(e) = result {
-            tracing::error!("带有 AI 消息的 Git commit 命令失败。");
-            return Err(e);
-        }
+        let output = match result {
+            Ok(output) => output,
+            Err(e) => {
+                tracing::error!("带有 AI 消息的 Git commit 命令失败。");
+                return Err(e);
+            }
+        };
         // 清理临时文件
         cleanup_commit_message_file(&temp_file_path);
+        if output.is_success() {
+            verify_commit_trailers(&args, config);
+        }
         tracing::info!("使用 AI 消息成功提交。");
     } else {
-        return handle_commit_passthrough(args, "(standard commit with --noai)".to_string()).await;
+        if args.split {
+            return Err(AppError::Generic("--split 需要 AI 生成拆分计划，不能与 --noai 同时使用".to_string()));
+        }
+        return handle_commit_passthrough(args, config, "(standard commit with --noai)".to_string()).await;
     }
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,325 @@
+//! git commit 选项语法模型
+//!
+//! 按照 git 的 parse-options 规则解析透传给 `git commit` 的参数：区分布尔选项与带值选项，
+//! 正确处理短选项组合（`-am msg`）、紧贴的值（`-Skeyid`、`-mmsg`）、`--long=value`、
+//! 长选项的唯一前缀缩写以及 `--` 之后的路径参数，使参数过滤只移除 gitie 自己的选项。
+
+/// 选项的取值方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    /// 布尔选项，不带值
+    None,
+    /// 必须带值：`-m msg`、`-mmsg`、`--message msg`、`--message=msg`
+    Required,
+    /// 值可选，只能紧贴：`-S`、`-Skeyid`、`--gpg-sign=keyid`
+    Optional,
+    /// 值可选，可以紧贴或作为下一个不以 `-` 开头的参数（gitie 的 `-t LEVEL`）
+    OptionalSeparate,
+}
+
+/// 单个选项的规格
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OptionSpec {
+    /// 短选项字符
+    pub short: Option<char>,
+    /// 长选项名（不含 `--`）
+    pub long: &'static str,
+    /// 取值方式
+    pub value: ValueKind,
+}
+
+const fn opt(short: Option<char>, long: &'static str, value: ValueKind) -> OptionSpec {
+    OptionSpec { short, long, value }
+}
+
+/// gitie 自己的 commit 选项，优先于同名的 git 选项（例如 `-t` 在 gitie 中表示 Tree-sitter）
+pub const GITIE_COMMIT_OPTIONS: &[OptionSpec] = &[
+    opt(Some('t'), "tree-sitter", ValueKind::OptionalSeparate),
+    opt(None, "ai", ValueKind::None),
+    opt(None, "noai", ValueKind::None),
+    opt(None, "review", ValueKind::None),
+    opt(None, "review-policy", ValueKind::Required),
+    opt(None, "split", ValueKind::None),
+];
+
+/// `git commit` 的选项表
+pub const GIT_COMMIT_OPTIONS: &[OptionSpec] = &[
+    opt(Some('a'), "all", ValueKind::None),
+    opt(Some('p'), "patch", ValueKind::None),
+    opt(Some('C'), "reuse-message", ValueKind::Required),
+    opt(Some('c'), "reedit-message", ValueKind::Required),
+    opt(None, "fixup", ValueKind::Required),
+    opt(None, "squash", ValueKind::Required),
+    opt(None, "reset-author", ValueKind::None),
+    opt(None, "short", ValueKind::None),
+    opt(None, "branch", ValueKind::None),
+    opt(None, "porcelain", ValueKind::None),
+    opt(None, "long", ValueKind::None),
+    opt(Some('z'), "null", ValueKind::None),
+    opt(Some('F'), "file", ValueKind::Required),
+    opt(None, "author", ValueKind::Required),
+    opt(None, "date", ValueKind::Required),
+    opt(Some('m'), "message", ValueKind::Required),
+    opt(Some('t'), "template", ValueKind::Required),
+    opt(Some('s'), "signoff", ValueKind::None),
+    opt(None, "trailer", ValueKind::Required),
+    opt(Some('n'), "no-verify", ValueKind::None),
+    opt(None, "verify", ValueKind::None),
+    opt(None, "allow-empty", ValueKind::None),
+    opt(None, "allow-empty-message", ValueKind::None),
+    opt(None, "cleanup", ValueKind::Required),
+    opt(Some('e'), "edit", ValueKind::None),
+    opt(None, "amend", ValueKind::None),
+    opt(None, "no-post-rewrite", ValueKind::None),
+    opt(Some('i'), "include", ValueKind::None),
+    opt(Some('o'), "only", ValueKind::None),
+    opt(None, "pathspec-from-file", ValueKind::Required),
+    opt(None, "pathspec-file-nul", ValueKind::None),
+    opt(Some('u'), "untracked-files", ValueKind::Optional),
+    opt(Some('v'), "verbose", ValueKind::None),
+    opt(Some('q'), "quiet", ValueKind::None),
+    opt(None, "dry-run", ValueKind::None),
+    opt(None, "status", ValueKind::None),
+    opt(Some('S'), "gpg-sign", ValueKind::Optional),
+];
+
+/// 解析得到的单个选项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedOption {
+    /// 选项规格，未知选项为 None
+    pub spec: Option<&'static OptionSpec>,
+    /// 是否为 `--no-xxx` 形式的否定选项
+    pub negated: bool,
+    /// 选项值
+    pub value: Option<String>,
+    /// 单独渲染该选项时使用的参数
+    pub rendered: Vec<String>,
+}
+
+impl ParsedOption {
+    /// 判断是否为指定长选项名的（非否定）选项
+    pub fn is(&self, long: &str) -> bool {
+        !self.negated && self.spec.is_some_and(|spec| spec.long == long)
+    }
+
+    /// 判断是否为 gitie 自己的选项（见 [`GITIE_COMMIT_OPTIONS`]），这些选项不能透传给 git
+    pub fn is_gitie_option(&self) -> bool {
+        self.spec.is_some_and(|spec| GITIE_COMMIT_OPTIONS.iter().any(|gitie| gitie.long == spec.long))
+    }
+}
+
+/// 一组连续的原始参数及其中解析出的选项
+///
+/// 短选项组合（如 `-av`）对应一个参数组中的多个选项；路径参数和 `--` 的 options 为空
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgGroup {
+    /// 原始参数
+    pub tokens: Vec<String>,
+    /// 解析出的选项
+    pub options: Vec<ParsedOption>,
+}
+
+/// 查找短选项，gitie 选项优先
+fn find_short(c: char) -> Option<&'static OptionSpec> {
+    GITIE_COMMIT_OPTIONS
+        .iter()
+        .chain(GIT_COMMIT_OPTIONS.iter())
+        .find(|spec| spec.short == Some(c))
+}
+
+/// 查找长选项：先精确匹配，再按唯一前缀匹配（与 git 的缩写规则一致）
+fn find_long(name: &str) -> Option<&'static OptionSpec> {
+    let all = || GITIE_COMMIT_OPTIONS.iter().chain(GIT_COMMIT_OPTIONS.iter());
+    if let Some(spec) = all().find(|spec| spec.long == name) {
+        return Some(spec);
+    }
+    let mut candidates = all().filter(|spec| spec.long.starts_with(name));
+    match (candidates.next(), candidates.next()) {
+        (Some(spec), None) if !name.is_empty() => Some(spec),
+        _ => None,
+    }
+}
+
+/// 判断下一个参数能否作为 `OptionalSeparate` 选项的值
+fn is_separate_value(next: Option<&String>) -> bool {
+    next.is_some_and(|n| !n.starts_with('-'))
+}
+
+/// 解析长选项参数（不含开头的 `--`），返回选项及额外消耗的参数数量
+fn parse_long(body: &str, next: Option<&String>) -> (ParsedOption, usize) {
+    let (name, attached) = match body.split_once('=') {
+        Some((name, value)) => (name, Some(value.to_string())),
+        None => (body, None),
+    };
+
+    let (spec, negated) = match find_long(name) {
+        Some(spec) => (Some(spec), false),
+        None => match name.strip_prefix("no-").and_then(find_long) {
+            Some(spec) => (Some(spec), true),
+            None => (None, false),
+        },
+    };
+
+    let raw = format!("--{}", body);
+    let value_kind = if negated { ValueKind::None } else { spec.map_or(ValueKind::None, |s| s.value) };
+
+    match value_kind {
+        ValueKind::Required if attached.is_none() => {
+            let value = next.cloned();
+            let consumed = usize::from(value.is_some());
+            let mut rendered = vec![raw];
+            rendered.extend(value.clone());
+            (ParsedOption { spec, negated, value, rendered }, consumed)
+        }
+        ValueKind::OptionalSeparate if attached.is_none() && is_separate_value(next) => {
+            let value = next.cloned();
+            let mut rendered = vec![raw];
+            rendered.extend(value.clone());
+            (ParsedOption { spec, negated, value, rendered }, 1)
+        }
+        _ => (ParsedOption { spec, negated, value: attached, rendered: vec![raw] }, 0),
+    }
+}
+
+/// 解析短选项组合（不含开头的 `-`），返回选项列表及额外消耗的参数数量
+fn parse_short_cluster(body: &str, next: Option<&String>) -> (Vec<ParsedOption>, usize) {
+    let mut options = Vec::new();
+    let mut consumed = 0;
+
+    for (index, c) in body.char_indices() {
+        let spec = find_short(c);
+        let rest = &body[index + c.len_utf8()..];
+        let flag = format!("-{}", c);
+
+        match spec.map_or(ValueKind::None, |s| s.value) {
+            ValueKind::None => {
+                options.push(ParsedOption { spec, negated: false, value: None, rendered: vec![flag] });
+                continue;
+            }
+            ValueKind::Required => {
+                let value = if !rest.is_empty() {
+                    Some(rest.to_string())
+                } else {
+                    consumed = usize::from(next.is_some());
+                    next.cloned()
+                };
+                let mut rendered = vec![flag];
+                rendered.extend(value.clone());
+                options.push(ParsedOption { spec, negated: false, value, rendered });
+            }
+            ValueKind::Optional => {
+                let value = (!rest.is_empty()).then(|| rest.to_string());
+                let rendered = vec![format!("{}{}", flag, rest)];
+                options.push(ParsedOption { spec, negated: false, value, rendered });
+            }
+            ValueKind::OptionalSeparate => {
+                let value = if !rest.is_empty() {
+                    Some(rest.to_string())
+                } else if is_separate_value(next) {
+                    consumed = 1;
+                    next.cloned()
+                } else {
+                    None
+                };
+                let mut rendered = vec![flag];
+                rendered.extend(value.clone());
+                options.push(ParsedOption { spec, negated: false, value, rendered });
+            }
+        }
+        // 带值的选项会消耗组合中剩余的字符
+        break;
+    }
+
+    (options, consumed)
+}
+
+/// 按照 git commit 的选项语法解析参数
+///
+/// # Arguments
+///
+/// * `args` - 透传给 git commit 的参数
+///
+/// # Returns
+///
+/// * `Vec<ArgGroup>` - 按原始顺序排列的参数组
+pub fn parse_commit_args(args: &[String]) -> Vec<ArgGroup> {
+    let mut groups = Vec::new();
+    let mut i = 0;
+
+    while i < args.len() {
+        let arg = &args[i];
+        let next = args.get(i + 1);
+
+        if arg == "--" {
+            // `--` 之后全部是路径参数
+            groups.push(ArgGroup { tokens: args[i..].to_vec(), options: Vec::new() });
+            break;
+        }
+
+        let (options, consumed) = if let Some(body) = arg.strip_prefix("--") {
+            let (option, consumed) = parse_long(body, next);
+            (vec![option], consumed)
+        } else if arg.len() > 1 && arg.starts_with('-') {
+            parse_short_cluster(&arg[1..], next)
+        } else {
+            (Vec::new(), 0)
+        };
+
+        groups.push(ArgGroup { tokens: args[i..=i + consumed].to_vec(), options });
+        i += consumed + 1;
+    }
+
+    groups
+}
+
+/// 过滤参数中的指定选项，其余参数保持原样
+///
+/// 未受影响的参数组按原始形式输出；短选项组合中只有部分选项被移除时，
+/// 剩余选项逐个单独输出，以保证值仍与正确的选项对应
+///
+/// # Arguments
+///
+/// * `args` - 原始参数
+/// * `remove` - 判断选项是否需要移除
+///
+/// # Returns
+///
+/// * `Vec<String>` - 过滤后的参数
+pub fn filter_commit_args<F>(args: &[String], remove: F) -> Vec<String>
+where
+    F: Fn(&ParsedOption) -> bool,
+{
+    let mut result = Vec::new();
+
+    for group in parse_commit_args(args) {
+        if !group.options.iter().any(&remove) {
+            result.extend(group.tokens);
+            continue;
+        }
+        for option in group.options.into_iter().filter(|o| !remove(o)) {
+            result.extend(option.rendered);
+        }
+    }
+
+    result
+}
+
+/// 参数中最后一个 `-S`/`--gpg-sign`/`--no-gpg-sign` 选项的取值
+///
+/// 与 git 一致，后出现的选项覆盖先出现的；没有相关选项时返回 None
+pub fn gpg_sign_option(args: &[String]) -> Option<bool> {
+    parse_commit_args(args)
+        .iter()
+        .flat_map(|group| group.options.iter())
+        .rev()
+        .find(|option| option.spec.is_some_and(|spec| spec.long == "gpg-sign"))
+        .map(|option| !option.negated)
+}
+
+/// 判断参数中是否包含指定长选项名的选项（包括其短选项和缩写形式）
+pub fn contains_commit_option(args: &[String], long: &str) -> bool {
+    parse_commit_args(args)
+        .iter()
+        .flat_map(|group| group.options.iter())
+        .any(|option| option.is(long))
+}
@@ -0,0 +1,388 @@
+//! 提交偏差度分析模块
+//!
+//! 使用 `commit-deviation-analyzer.md` 提示，结合提交的 diff、Tree-sitter 结构分析和任务描述，
+//! 评估提交与开发任务之间的偏差程度，并以表格形式输出各维度的偏差评分。
+
+use crate::{
+    ai_module::prompt_handler::{load_prompt_file, send_prompt_and_get_response},
+    cli_interface::args::DeviationArgs,
+    command_processing::review::{FileVersionSource, ReviewRange, post_change_revision, read_file_version},
+    config_management::settings::AppConfig,
+    core::errors::{AppError, GitError},
+    git_module::execute_git_command_and_capture_output,
+    tree_sitter_analyzer::{TreeSitterAnalyzer, core::DiffAnalysis},
+};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 偏差度分析提示文件名
+const DEVIATION_PROMPT_FILE_NAME: &str = "commit-deviation-analyzer.md";
+
+/// 内置的偏差度分析提示，当用户目录和 assets 目录中都找不到提示文件时使用
+const DEFAULT_DEVIATION_PROMPT: &str = include_str!("../../assets/commit-deviation-analyzer.md");
+
+/// 单个维度的偏差评分
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviationDimension {
+    /// 维度名称，例如 "范围偏离"
+    pub name: String,
+    /// 偏差百分比，None 表示该维度不可用（N/A）
+    #[serde(default)]
+    pub score: Option<f32>,
+    /// 简要说明
+    #[serde(default)]
+    pub note: String,
+}
+
+/// AI 返回的偏差度分析报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviationReport {
+    /// 总体偏差度百分比
+    pub overall: f32,
+    /// 各维度评分
+    #[serde(default)]
+    pub dimensions: Vec<DeviationDimension>,
+    /// 分析发现
+    #[serde(default)]
+    pub findings: Vec<String>,
+    /// 改进建议
+    #[serde(default)]
+    pub suggestions: Vec<String>,
+    /// 建议的提交消息
+    #[serde(default)]
+    pub suggested_commit_message: Option<String>,
+}
+
+/// Tree-sitter 分析状态，对应提示中的三种分析模式
+enum StructureAnalysis {
+    Enabled(DiffAnalysis),
+    Failed(String),
+    Disabled,
+}
+
+/// 读取任务描述：若参数是已存在的文件路径则读取文件内容，否则将其视为任务文本
+fn load_task_description(task: &str) -> Result<String, AppError> {
+    let path = Path::new(task);
+    let text = if path.is_file() {
+        std::fs::read_to_string(path)
+            .map_err(|e| AppError::IO(format!("无法读取任务描述文件: {}", task), e))?
+    } else {
+        task.to_string()
+    };
+
+    if text.trim().is_empty() {
+        return Err(AppError::Generic("任务描述为空，无法进行偏差度分析。".to_string()));
+    }
+    Ok(text)
+}
+
+/// 执行 git 命令并返回标准输出，失败时返回 CommandFailed 错误
+fn run_git(args: Vec<String>) -> Result<String, AppError> {
+    let output = execute_git_command_and_capture_output(&args)?;
+    if !output.is_success() {
+        return Err(AppError::Git(GitError::CommandFailed {
+            command: format!("git {}", args.join(" ")),
+            status_code: output.exit_code(),
+            stdout: output.stdout,
+            stderr: output.stderr,
+        }));
+    }
+    Ok(output.stdout)
+}
+
+/// 获取提交（或提交范围）的 diff 与提交消息
+fn extract_commit_changes(rev: &str) -> Result<(String, String), AppError> {
+    if rev.contains("..") {
+        let diff = run_git(vec!["diff".to_string(), "--no-color".to_string(), rev.to_string()])?;
+        let messages = run_git(vec!["log".to_string(), "--format=- %s".to_string(), rev.to_string()])?;
+        Ok((diff, messages))
+    } else {
+        let diff = run_git(vec![
+            "show".to_string(),
+            "--format=".to_string(),
+            "--no-color".to_string(),
+            "--diff-merges=first-parent".to_string(),
+            rev.to_string(),
+        ])?;
+        let message = run_git(vec!["log".to_string(), "-1".to_string(), "--format=%B".to_string(), rev.to_string()])?;
+        Ok((diff, message))
+    }
+}
+
+/// 对 diff 进行 Tree-sitter 结构分析
+///
+/// 变更后的文件内容取自被分析的提交（范围 `A..B` 取 `B`），而不是可能已经变化的工作区。
+fn analyze_structure(config: &AppConfig, root: &Path, rev: &str, diff_text: &str, disabled: bool) -> StructureAnalysis {
+    if disabled {
+        return StructureAnalysis::Disabled;
+    }
+
+    let mut analyzer = match TreeSitterAnalyzer::new(config.tree_sitter.clone()) {
+        Ok(analyzer) => analyzer,
+        Err(e) => return StructureAnalysis::Failed(e.to_string()),
+    };
+    analyzer.set_project_root(root.to_path_buf());
+
+    let range = if rev.contains("..") { ReviewRange::Range(rev.to_string()) } else { ReviewRange::Commit(rev.to_string()) };
+    let source = post_change_revision(&range).map_or(FileVersionSource::WorkingTree, FileVersionSource::Revision);
+    match analyzer.analyze_diff_with_sources(diff_text, &|path| read_file_version(root, path, &source)) {
+        Ok(analysis) => StructureAnalysis::Enabled(analysis),
+        Err(e) => StructureAnalysis::Failed(e.to_string()),
+    }
+}
+
+/// 构建发送给 AI 的用户提示
+fn build_deviation_prompt(
+    task: &str,
+    commit_message: &str,
+    diff_text: &str,
+    structure: &StructureAnalysis,
+) -> String {
+    let mut prompt = String::new();
+    prompt.push_str("## 开发任务描述\n\n");
+    prompt.push_str(task.trim());
+    prompt.push_str("\n\n## 提交消息\n\n");
+    prompt.push_str(commit_message.trim());
+    prompt.push_str("\n\n## Tree-sitter 状态\n\n");
+
+    match structure {
+        StructureAnalysis::Enabled(analysis) => {
+            prompt.push_str("成功启用\n\n### 结构分析\n\n");
+            prompt.push_str(&format!("- {}\n", analysis.overall_summary));
+            prompt.push_str(&format!(
+                "- 变更模式: {:?}，变更范围: {:?}\n",
+                analysis.change_analysis.change_pattern, analysis.change_analysis.change_scope
+            ));
+            for file in &analysis.file_analyses {
+                prompt.push_str(&format!(
+                    "- {} ({}): {}\n",
+                    file.path.display(),
+                    file.language,
+                    file.summary.as_deref().unwrap_or("无摘要")
+                ));
+                for node in &file.affected_nodes {
+                    prompt.push_str(&format!(
+                        "  - {} `{}`{}\n",
+                        node.node_type,
+                        node.name,
+                        if node.is_public { " (公开)" } else { "" }
+                    ));
+                }
+            }
+        }
+        StructureAnalysis::Failed(error) => {
+            prompt.push_str(&format!("解析失败: {}\n", error));
+        }
+        StructureAnalysis::Disabled => {
+            prompt.push_str("未启用\n");
+        }
+    }
+
+    prompt.push_str("\n## 代码变更\n\n```diff\n");
+    prompt.push_str(diff_text.trim());
+    prompt.push_str("\n```\n\n");
+    prompt.push_str(
+        "请按照系统提示进行偏差度分析，并只输出如下格式的 JSON（不可用的维度 score 为 null）：\n\
+        {\"overall\": 32, \"dimensions\": [{\"name\": \"范围偏离\", \"score\": 25, \"note\": \"说明\"}], \
+        \"findings\": [\"发现\"], \"suggestions\": [\"建议\"], \"suggested_commit_message\": \"优化后的提交消息\"}\n\
+        维度依次为：范围偏离、结构偏离、组件偏离、风格偏离、测试偏离、复杂度偏离。",
+    );
+    prompt
+}
+
+/// 从 AI 响应中解析偏差度报告
+pub fn parse_deviation_report(response: &str) -> Option<DeviationReport> {
+    let start = response.find('{')?;
+    let end = response.rfind('}')?;
+    if start >= end {
+        return None;
+    }
+    serde_json::from_str(&response[start..=end]).ok()
+}
+
+/// 偏差度对应的提示标记
+fn score_marker(score: f32) -> &'static str {
+    if score < 20.0 {
+        "✅"
+    } else if score < 50.0 {
+        "⚠️"
+    } else {
+        "🚨"
+    }
+}
+
+/// 将偏差度报告渲染为表格文本
+pub fn render_deviation_report(report: &DeviationReport) -> String {
+    let mut output = String::new();
+    output.push_str(&format!(
+        "### 偏差度评分：{:.0}% {}\n\n",
+        report.overall,
+        score_marker(report.overall)
+    ));
+
+    output.push_str("| 维度 | 偏差度 | 说明 |\n");
+    output.push_str("|------|--------|------|\n");
+    for dimension in &report.dimensions {
+        let score = match dimension.score {
+            Some(score) => format!("{:.0}% {}", score, score_marker(score)),
+            None => "N/A".to_string(),
+        };
+        output.push_str(&format!(
+            "| {} | {} | {} |\n",
+            dimension.name,
+            score,
+            dimension.note.replace('|', "\\|").replace('\n', " ")
+        ));
+    }
+
+    if !report.findings.is_empty() {
+        output.push_str("\n### 偏差分析 🔍\n\n");
+        for finding in &report.findings {
+            output.push_str(&format!("- {}\n", finding));
+        }
+    }
+
+    if !report.suggestions.is_empty() {
+        output.push_str("\n### 改进建议 💡\n\n");
+        for suggestion in &report.suggestions {
+            output.push_str(&format!("- {}\n", suggestion));
+        }
+    }
+
+    if let Some(message) = report.suggested_commit_message.as_ref().filter(|m| !m.trim().is_empty()) {
+        output.push_str(&format!("\n### 建议的提交消息 📝\n\n```\n{}\n```\n", message.trim()));
+    }
+
+    output
+}
+
+/// 加载偏差度分析的系统提示，优先使用用户目录中的覆盖版本
+fn load_deviation_prompt() -> String {
+    let dirs: Vec<String> = AppConfig::get_user_config_dir()
+        .map(|dir| vec![dir.to_string_lossy().to_string()])
+        .unwrap_or_default();
+    load_prompt_file(DEVIATION_PROMPT_FILE_NAME, &dirs).unwrap_or_else(|e| {
+        tracing::debug!("{}，使用内置的偏差度分析提示", e);
+        DEFAULT_DEVIATION_PROMPT.to_string()
+    })
+}
+
+/// Handles the `deviation` subcommand
+///
+/// # Arguments
+///
+/// * `args` - Deviation arguments from CLI
+/// * `config` - Application configuration
+///
+/// # Returns
+///
+/// * `Result<(), AppError>` - Success or an error
+pub async fn handle_deviation(args: DeviationArgs, config: &AppConfig) -> Result<(), AppError> {
+    tracing::info!("分析提交 {} 与任务的偏差度", args.rev);
+
+    let task = load_task_description(&args.task)?;
+    let (diff_text, commit_message) = extract_commit_changes(&args.rev)?;
+    if diff_text.trim().is_empty() {
+        return Err(AppError::Generic(format!("提交 {} 没有代码变更，无法进行偏差度分析。", args.rev)));
+    }
+
+    let root = PathBuf::from(run_git(vec!["rev-parse".to_string(), "--show-toplevel".to_string()])?.trim());
+    let structure = analyze_structure(config, &root, &args.rev, &diff_text, args.no_tree_sitter);
+    if let StructureAnalysis::Failed(error) = &structure {
+        tracing::warn!("Tree-sitter 分析失败，使用纯文本分析模式: {}", error);
+    }
+
+    let prompt = build_deviation_prompt(&task, &commit_message, &diff_text, &structure);
+    let system_prompt = load_deviation_prompt();
+
+    tracing::info!("发送至 AI 进行偏差度分析");
+    let response = send_prompt_and_get_response(config, &prompt, &system_prompt).await?;
+
+    match parse_deviation_report(&response) {
+        Some(report) => {
+            if args.format.eq_ignore_ascii_case("json") {
+                let json = serde_json::to_string_pretty(&report)
+                    .map_err(|e| AppError::Generic(format!("无法序列化偏差度报告: {}", e)))?;
+                println!("{}", json);
+            } else {
+                println!("{}", "提交偏差度分析".bold().green());
+                println!("{}", "==============".green());
+                println!("\n{}", render_deviation_report(&report));
+            }
+        }
+        None => {
+            tracing::warn!("AI 未返回结构化的偏差度报告，输出原始结果");
+            println!("{}", response);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_render_deviation_report() {
+        let response = "分析如下：\n```json\n{\"overall\": 32, \"dimensions\": [\
+            {\"name\": \"范围偏离\", \"score\": 25, \"note\": \"新增了 payment.rs\"},\
+            {\"name\": \"结构偏离\", \"score\": null, \"note\": \"需要 Tree-sitter\"}],\
+            \"findings\": [\"未修改 user.rs\"], \"suggestions\": [], \
+            \"suggested_commit_message\": \"feat(auth): 添加登录\"}\n```";
+        let report = parse_deviation_report(response).unwrap();
+        assert_eq!(report.overall, 32.0);
+        assert_eq!(report.dimensions.len(), 2);
+        assert!(report.dimensions[1].score.is_none());
+
+        let table = render_deviation_report(&report);
+        assert!(table.contains("### 偏差度评分：32% ⚠️"));
+        assert!(table.contains("| 范围偏离 | 25% ⚠️ | 新增了 payment.rs |"));
+        assert!(table.contains("| 结构偏离 | N/A | 需要 Tree-sitter |"));
+        assert!(table.contains("- 未修改 user.rs"));
+        assert!(!table.contains("改进建议"));
+        assert!(table.contains("feat(auth): 添加登录"));
+
+        assert!(parse_deviation_report("没有 JSON").is_none());
+    }
+
+    #[test]
+    fn test_analyze_structure_reads_analyzed_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git").arg("-C").arg(root).args(args).output().unwrap();
+            assert!(output.status.success(), "git {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.name", "Gitie"]);
+        git(&["config", "user.email", "gitie@example.com"]);
+        git(&["config", "commit.gpgsign", "false"]);
+        std::fs::write(root.join("lib.rs"), "pub fn first() -> u32 {\n    1\n}\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "first"]);
+        std::fs::write(root.join("lib.rs"), "pub fn first() -> u32 {\n    1\n}\n\npub fn second() -> u32 {\n    2\n}\n").unwrap();
+        git(&["commit", "-q", "-am", "second"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "later"]);
+        // 工作区中新增函数所在的行已经变成了另一个函数
+        std::fs::write(root.join("lib.rs"), "pub fn first() -> u32 {\n    1\n}\n\npub fn rewritten() -> u32 {\n    3\n}\n").unwrap();
+
+        let config = AppConfig {
+            ai: Default::default(),
+            tree_sitter: Default::default(),
+            commit: Default::default(),
+            prompts: Default::default(),
+        };
+        for rev in ["HEAD~1", "HEAD~2..HEAD~1"] {
+            let diff = git(&["diff", "HEAD~2", "HEAD~1"]);
+            let StructureAnalysis::Enabled(analysis) = analyze_structure(&config, root, rev, &diff, false) else {
+                panic!("{} 的结构分析失败", rev);
+            };
+            let names: Vec<&str> = analysis.file_analyses[0].affected_nodes.iter().map(|node| node.name.as_str()).collect();
+            assert!(names.contains(&"second"), "{}: {:?}", rev, names);
+            assert!(!names.contains(&"rewritten"), "{}: {:?}", rev, names);
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! 工单引用模块
+//!
+//! 从当前分支名中提取工单号（如 `PROJ-123`、`#456`），
+//! 将其提供给提交提示，并在生成的提交消息中强制添加工单引用页脚。
+
+use crate::{
+    config_management::settings::CommitConfig, git_module::get_current_branch,
+};
+use regex::Regex;
+
+/// 页脚模板中工单号的占位符
+const ISSUES_PLACEHOLDER: &str = "{issues}";
+
+/// 使用配置的正则表达式从分支名中提取工单号
+///
+/// 若正则包含捕获组，则使用第一个捕获组；结果按出现顺序去重
+///
+/// # Arguments
+///
+/// * `branch` - 分支名
+/// * `pattern` - 工单号正则表达式
+///
+/// # Returns
+///
+/// * `Vec<String>` - 提取到的工单号，正则无效时返回空列表
+pub fn extract_issue_keys(branch: &str, pattern: &str) -> Vec<String> {
+    if pattern.trim().is_empty() {
+        return Vec::new();
+    }
+
+    let regex = match Regex::new(pattern) {
+        Ok(regex) => regex,
+        Err(e) => {
+            tracing::warn!("工单号正则表达式无效 '{}': {}", pattern, e);
+            return Vec::new();
+        }
+    };
+
+    let mut keys: Vec<String> = Vec::new();
+    for captures in regex.captures_iter(branch) {
+        let matched = captures.get(1).or_else(|| captures.get(0));
+        if let Some(key) = matched.map(|m| m.as_str().to_string())
+            && !key.is_empty()
+            && !keys.contains(&key)
+        {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+/// 获取当前分支关联的工单号
+///
+/// 分离 HEAD 或读取分支失败时返回空列表
+pub fn current_issue_keys(config: &CommitConfig) -> Vec<String> {
+    match get_current_branch() {
+        Ok(Some(branch)) => {
+            let keys = extract_issue_keys(&branch, &config.issue_pattern);
+            if !keys.is_empty() {
+                tracing::info!("从分支 '{}' 中提取到工单号: {}", branch, keys.join(", "));
+            }
+            keys
+        }
+        Ok(None) => {
+            tracing::debug!("当前处于分离 HEAD 状态，不提取工单号");
+            Vec::new()
+        }
+        Err(e) => {
+            tracing::warn!("无法获取当前分支: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// 在提交提示中附加工单号信息
+pub fn append_issue_context(prompt: String, keys: &[String], config: &CommitConfig) -> String {
+    if keys.is_empty() {
+        return prompt;
+    }
+
+    let mut prompt = format!("{}\n\n关联工单: {}", prompt, keys.join(", "));
+    if let Some(footer) = format_issue_footer(keys, &config.issue_footer) {
+        prompt.push_str(&format!("\n请在提交消息末尾添加页脚: {}", footer));
+    }
+    prompt
+}
+
+/// 根据模板生成工单引用页脚，模板为空时返回 None
+fn format_issue_footer(keys: &[String], template: &str) -> Option<String> {
+    if keys.is_empty() || template.trim().is_empty() {
+        return None;
+    }
+    if template.contains(ISSUES_PLACEHOLDER) {
+        Some(template.replace(ISSUES_PLACEHOLDER, &keys.join(", ")))
+    } else {
+        Some(format!("{} {}", template.trim_end(), keys.join(", ")))
+    }
+}
+
+/// 判断一行是否为 trailer，如 `Refs: PROJ-1`、`Signed-off-by: A <a@b>`、`BREAKING CHANGE: x`
+fn is_trailer_line(line: &str, prefix: &str) -> bool {
+    let line = line.trim_start();
+    if (!prefix.is_empty() && line.starts_with(prefix)) || line.starts_with("BREAKING CHANGE: ") {
+        return true;
+    }
+    line.split_once(": ").is_some_and(|(token, _)| {
+        !token.is_empty() && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+/// 确保提交消息以配置的工单引用页脚结尾
+///
+/// 只处理消息末尾的 trailer 段落：其中已有的同类页脚（与模板前缀相同的行）会被替换为
+/// 规范的页脚，其他 trailer 保留在同一段落中；正文中的同名行保持不变。
+///
+/// # Arguments
+///
+/// * `message` - 提交消息
+/// * `keys` - 工单号
+/// * `config` - 提交配置
+///
+/// # Returns
+///
+/// * `String` - 带有工单引用页脚的提交消息
+pub fn enforce_issue_footer(message: &str, keys: &[String], config: &CommitConfig) -> String {
+    let Some(footer) = format_issue_footer(keys, &config.issue_footer) else {
+        return message.to_string();
+    };
+
+    let prefix = config
+        .issue_footer
+        .split(ISSUES_PLACEHOLDER)
+        .next()
+        .unwrap_or_default()
+        .trim();
+
+    let lines: Vec<&str> = message.trim_end().lines().collect();
+    // 末尾段落全部由 trailer 组成时才视为 trailer 段落；标题所在的第一段不是 trailer
+    let (body, mut trailers) = match lines.iter().rposition(|line| line.trim().is_empty()) {
+        Some(blank) if lines[blank + 1..].iter().all(|line| is_trailer_line(line, prefix)) => {
+            let trailers: Vec<&str> = lines[blank + 1..]
+                .iter()
+                .copied()
+                .filter(|line| prefix.is_empty() || !line.trim_start().starts_with(prefix))
+                .collect();
+            (&lines[..blank], trailers)
+        }
+        _ => (&lines[..], Vec::new()),
+    };
+    trailers.push(&footer);
+
+    let body = body.join("\n");
+    let body = body.trim_end();
+    if body.is_empty() {
+        trailers.join("\n")
+    } else {
+        format!("{}\n\n{}", body, trailers.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_issue_keys() {
+        let pattern = r"[A-Z][A-Z0-9]+-\d+|#\d+";
+        assert_eq!(extract_issue_keys("feature/PROJ-123-login", pattern), vec!["PROJ-123"]);
+        assert_eq!(
+            extract_issue_keys("fix/#456-and-PROJ-7-PROJ-7", pattern),
+            vec!["#456", "PROJ-7"]
+        );
+        assert!(extract_issue_keys("main", pattern).is_empty());
+        assert!(extract_issue_keys("feature/PROJ-1", "").is_empty());
+        assert!(extract_issue_keys("feature/PROJ-1", "([").is_empty());
+
+        // 使用第一个捕获组
+        assert_eq!(extract_issue_keys("issue-42-crash", r"issue-(\d+)"), vec!["42"]);
+    }
+
+    #[test]
+    fn test_enforce_issue_footer() {
+        let config = CommitConfig::default();
+        let keys = vec!["PROJ-123".to_string()];
+
+        assert_eq!(
+            enforce_issue_footer("feat: 添加登录\n", &keys, &config),
+            "feat: 添加登录\n\nRefs: PROJ-123"
+        );
+
+        // 替换 AI 生成的不规范页脚
+        assert_eq!(
+            enforce_issue_footer("fix: 修复崩溃\n\n详细说明\n\nRefs: proj-123\n", &keys, &config),
+            "fix: 修复崩溃\n\n详细说明\n\nRefs: PROJ-123"
+        );
+
+        // 没有工单号或没有页脚模板时保持原样
+        assert_eq!(enforce_issue_footer("chore: x", &[], &config), "chore: x");
+        let no_footer = CommitConfig {
+            issue_footer: String::new(),
+            ..CommitConfig::default()
+        };
+        assert_eq!(enforce_issue_footer("chore: x", &keys, &no_footer), "chore: x");
+
+        // 只替换末尾 trailer 段落中的页脚，正文中的同名行保留
+        assert_eq!(
+            enforce_issue_footer("docs: 更新说明\n\nRefs: 见设计文档第 3 节\n继续说明", &keys, &config),
+            "docs: 更新说明\n\nRefs: 见设计文档第 3 节\n继续说明\n\nRefs: PROJ-123"
+        );
+        // 页脚加入已有的 trailer 段落
+        assert_eq!(
+            enforce_issue_footer("feat!: x\n\nBREAKING CHANGE: 删除 f\nRefs: OLD-1", &keys, &config),
+            "feat!: x\n\nBREAKING CHANGE: 删除 f\nRefs: PROJ-123"
+        );
+        // 只有标题时标题不视为 trailer
+        assert_eq!(enforce_issue_footer("Refs: x", &keys, &config), "Refs: x\n\nRefs: PROJ-123");
+    }
+
+    #[test]
+    fn test_issue_pattern_is_opt_in() {
+        let config = CommitConfig::default();
+        assert!(config.issue_pattern.is_empty());
+        assert!(extract_issue_keys("fix/UTF-8-decoding", &config.issue_pattern).is_empty());
+    }
+}
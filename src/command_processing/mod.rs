@@ -1,2 +1,12 @@
+pub mod api_diff;
+pub mod checks;
 pub mod commit;
+pub mod commit_options;
+pub mod deviation;
+pub mod issues;
 pub mod review;
+pub mod review_personas;
+pub mod review_prompts;
+pub mod reword;
+pub mod split;
+pub mod style;
\ No newline at end of file
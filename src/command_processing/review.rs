@@ -1,47 +1,169 @@
 use crate::ai_module::prompt_handler::send_prompt_and_get_response;
 use crate::cli_interface::args::ReviewArgs;
 use crate::config_management::settings::{AppConfig, TreeSitterConfig};
-use crate::core::errors::AppError;
-use crate::git_module::execute_git_command_and_capture_output;
+use crate::command_processing::commit::EMPTY_TREE_HASH;
+use crate::core::errors::{AppError, GitError};
+use crate::git_module::{execute_git_command_and_capture_output, get_repository_root};
 use crate::tree_sitter_analyzer::simple_diff::{parse_simple_diff, detect_language_from_path};
-use crate::tree_sitter_analyzer::core::{GitDiff, ChangePattern, ChangeScope, DiffAnalysis};
+use crate::tree_sitter_analyzer::core::{GitDiff, ChangePattern, ChangeScope, ChangeType, DiffAnalysis, FileAst};
 use crate::tree_sitter_analyzer::analyzer::TreeSitterAnalyzer;
-use crate::review_engine::AnalysisDepth;
+use crate::tree_sitter_analyzer::ast_diff::{AstDiff, DefinitionChangeKind, FileVersions};
+use crate::tree_sitter_analyzer::context::{ChangedFileAst, ReviewContext, build_review_context, estimate_tokens};
+use crate::command_processing::review_personas::{build_merge_prompt, concat_persona_reports, parse_focus_personas};
+use crate::command_processing::review_prompts::{
+    BASE_REVIEW_SYSTEM_PROMPT, OTHER_LANGUAGE, group_diff_by_language, merge_language_reviews, review_language_for_path, review_system_prompt,
+};
+use crate::review_engine::{AnalysisDepth, Issue, Severity, SimpleReviewResult, SimpleReviewer};
+use crate::review_engine::findings::{
+    FINDINGS_JSON_INSTRUCTION, merge_issues, parse_ai_findings, render_issues_markdown, strip_findings_blocks,
+};
+use crate::cli_interface::ui::{ask, confirm};
+use crate::review_engine::fixes::{
+    FixSuggestion, apply_fixes_to_content, collect_added_lines, render_fix_diff, suggest_fixes,
+};
 use std::fs;
-use std::io::Write;
+use std::io::{IsTerminal, Write};
 use colored::Colorize;
 use std::env;
 
+/// 评审的变更范围
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReviewRange {
+    /// 暂存区的变更，没有暂存时为工作区的变更
+    WorkingTree,
+    /// 单个提交相对于其父提交的变更（`REV^!`）
+    Commit(String),
+    /// 提交范围 `A..B` 或 `A...B`
+    Range(String),
+    /// 当前分支相对于其与基准分支合并基础的变更，None 表示使用默认主分支
+    Branch(Option<String>),
+}
+
+/// 根据评审参数确定要评审的变更范围
+///
+/// # Arguments
+///
+/// * `args` - Review arguments from CLI
+///
+/// # Returns
+///
+/// * `Result<ReviewRange, AppError>` - 变更范围，参数冲突时返回错误
+pub fn parse_review_range(args: &ReviewArgs) -> Result<ReviewRange, AppError> {
+    let specified = [args.revision.is_some(), args.branch.is_some(), args.commit1.is_some()]
+        .iter()
+        .filter(|s| **s)
+        .count();
+    if specified > 1 {
+        return Err(AppError::Generic(
+            "提交范围、--branch 和 --commit1/--commit2 只能指定其中一种。".to_string(),
+        ));
+    }
+
+    match (&args.revision, &args.branch, &args.commit1, &args.commit2) {
+        (Some(rev), _, _, _) if rev.contains("..") => Ok(ReviewRange::Range(rev.clone())),
+        (Some(rev), _, _, _) => Ok(ReviewRange::Commit(rev.clone())),
+        (_, Some(base), _, _) => Ok(ReviewRange::Branch(
+            Some(base.trim().to_string()).filter(|b| !b.is_empty()),
+        )),
+        (_, _, Some(commit1), Some(commit2)) => Ok(ReviewRange::Range(format!("{}..{}", commit1, commit2))),
+        (_, _, Some(commit1), None) => Ok(ReviewRange::Range(format!("{}..HEAD", commit1))),
+        (_, _, None, Some(_)) => {
+            // This should not happen with the CLI parser, but handle it just in case
+            Err(AppError::Generic("如果指定了第二个提交，则必须同时指定第一个提交。".to_string()))
+        }
+        _ => Ok(ReviewRange::WorkingTree),
+    }
+}
+
+/// 执行 git diff 并返回输出，命令失败时返回错误
+fn run_review_diff(mut diff_args: Vec<String>, paths: &[String]) -> Result<String, AppError> {
+    diff_args.insert(0, "diff".to_string());
+    diff_args.push("--".to_string());
+    diff_args.extend(paths.iter().cloned());
+
+    let result = execute_git_command_and_capture_output(&diff_args)?;
+    if !result.is_success() {
+        return Err(AppError::Git(GitError::CommandFailed {
+            command: format!("git {}", diff_args.join(" ")),
+            status_code: result.exit_code(),
+            stdout: result.stdout,
+            stderr: result.stderr,
+        }));
+    }
+    Ok(result.stdout)
+}
+
+/// 判断引用能否解析为提交
+pub(crate) fn revision_exists(rev: &str) -> bool {
+    let args = vec![
+        "rev-parse".to_string(),
+        "--verify".to_string(),
+        "--quiet".to_string(),
+        format!("{}^{{commit}}", rev),
+    ];
+    execute_git_command_and_capture_output(&args).is_ok_and(|output| output.is_success())
+}
+
+/// 确定默认的主分支：优先使用 origin/HEAD 指向的分支，其次是 main 或 master
+fn default_base_branch() -> Result<String, AppError> {
+    let args = vec![
+        "symbolic-ref".to_string(),
+        "--quiet".to_string(),
+        "--short".to_string(),
+        "refs/remotes/origin/HEAD".to_string(),
+    ];
+    if let Ok(output) = execute_git_command_and_capture_output(&args)
+        && output.is_success()
+        && !output.stdout.trim().is_empty()
+    {
+        return Ok(output.stdout.trim().to_string());
+    }
+
+    ["main", "master", "origin/main", "origin/master"]
+        .iter()
+        .find(|b| revision_exists(b))
+        .map(|b| b.to_string())
+        .ok_or_else(|| AppError::Generic("无法确定主分支，请使用 --branch <BASE> 指定基准分支。".to_string()))
+}
+
 /// Extract diff information for review
 ///
-/// This function gets the diff between specified commits or the current staged changes
-async fn extract_diff_for_review(args: &ReviewArgs) -> Result<String, AppError> {
-    match (&args.commit1, &args.commit2) {
-        (Some(commit1), Some(commit2)) => {
-            // Compare two specific commits
-            tracing::info!("比较两个指定的提交: {} 和 {}", commit1, commit2);
-            let diff_args = vec![
-                "diff".to_string(),
-                format!("{}..{}", commit1, commit2),
-                "--".to_string(),
-            ];
-            let result = execute_git_command_and_capture_output(&diff_args)?;
-            Ok(result.stdout)
-        }
-        (Some(commit), None) => {
-            // Compare one commit with HEAD
-            tracing::info!("比较指定的提交与HEAD: {}", commit);
-            let diff_args = vec![
-                "diff".to_string(),
-                format!("{}..HEAD", commit),
-                "--".to_string(),
-            ];
-            let result = execute_git_command_and_capture_output(&diff_args)?;
-            Ok(result.stdout)
-        }
-        (None, None) => {
+/// This function gets the diff for a single commit, a revision range, the current
+/// branch against its merge-base, or the current staged/working tree changes,
+/// limited to the pathspecs given after `--`
+async fn extract_diff_for_review(args: &ReviewArgs, range: &ReviewRange) -> Result<String, AppError> {
+    let paths = &args.passthrough_args;
+
+    match range.clone() {
+        ReviewRange::Commit(rev) => {
+            tracing::info!("评审单个提交: {}", rev);
+            if !revision_exists(&rev) {
+                return Err(AppError::Generic(format!("无法解析提交引用: {}", rev)));
+            }
+            // 根提交没有父提交，与空树比较
+            if revision_exists(&format!("{}^", rev)) {
+                run_review_diff(vec![format!("{}^!", rev)], paths)
+            } else {
+                run_review_diff(vec![EMPTY_TREE_HASH.to_string(), rev], paths)
+            }
+        }
+        ReviewRange::Range(range) => {
+            tracing::info!("评审提交范围: {}", range);
+            run_review_diff(vec![range], paths)
+        }
+        ReviewRange::Branch(base) => {
+            let base = match base {
+                Some(base) => base,
+                None => default_base_branch()?,
+            };
+            tracing::info!("评审当前分支相对于 {} 的合并基础的变更", base);
+            run_review_diff(vec![format!("{}...HEAD", base)], paths)
+        }
+        ReviewRange::WorkingTree => {
             // Check if there are staged changes
-            let status_result = execute_git_command_and_capture_output(&["status".to_string(), "--porcelain".to_string()])?;
+            let mut status_args = vec!["status".to_string(), "--porcelain".to_string(), "--".to_string()];
+            status_args.extend(paths.iter().cloned());
+            let status_result = execute_git_command_and_capture_output(&status_args)?;
             
             if status_result.stdout.trim().is_empty() {
                 return Err(AppError::Generic("没有检测到变更，无法执行代码评审。请先暂存(git add)或提交一些变更。".to_string()));
@@ -50,24 +172,210 @@ async fn extract_diff_for_review(args: &ReviewArgs) -> Result<String, AppError>
             // If no commit specified, use staged changes or unstaged changes
             let has_staged = status_result.stdout.lines().any(|line| line.starts_with(|c| c == 'M' || c == 'A' || c == 'D' || c == 'R'));
             
-            let diff_args = if has_staged {
+            if has_staged {
                 tracing::info!("评审已暂存的变更");
-                vec!["diff".to_string(), "--staged".to_string()]
+                run_review_diff(vec!["--staged".to_string()], paths)
             } else {
                 tracing::info!("评审工作区的变更");
-                vec!["diff".to_string()]
+                run_review_diff(Vec::new(), paths)
+            }
+        }
+    }
+}
+
+/// 确定变更后的文件内容所在的版本，None 表示工作区
+pub(crate) fn post_change_revision(range: &ReviewRange) -> Option<String> {
+    match range {
+        ReviewRange::WorkingTree => None,
+        ReviewRange::Commit(rev) => Some(rev.clone()),
+        ReviewRange::Range(range) => {
+            let end = range.rsplit("..").next().unwrap_or_default().trim_start_matches('.');
+            Some(if end.is_empty() { "HEAD".to_string() } else { end.to_string() })
+        }
+        ReviewRange::Branch(_) => Some("HEAD".to_string()),
+    }
+}
+
+/// 解析变更后的文件，返回路径为仓库相对路径的语法树
+fn parse_post_change_file(
+    analyzer: &mut TreeSitterAnalyzer,
+    root: &std::path::Path,
+    path: &std::path::Path,
+    source: &FileVersionSource,
+) -> Option<FileAst> {
+    let Some(content) = read_file_version(root, path, source) else {
+        tracing::debug!("无法读取 {} 变更后的内容，跳过上下文提取", path.display());
+        return None;
+    };
+    match analyzer.parse_source(path, content) {
+        Ok(mut ast) => {
+            ast.path = path.to_path_buf();
+            Some(ast)
+        }
+        Err(e) => {
+            tracing::debug!("无法解析 {}，跳过上下文提取: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// 使用变更后文件的语法树构建评审的代码上下文
+fn build_code_context_for_review(
+    config: &AppConfig,
+    root: std::path::PathBuf,
+    range: &ReviewRange,
+    git_diff: &GitDiff,
+) -> ReviewContext {
+    let mut analyzer = match TreeSitterAnalyzer::new(config.tree_sitter.clone()) {
+        Ok(analyzer) => analyzer,
+        Err(e) => {
+            tracing::warn!("无法初始化 Tree-sitter 分析器，跳过代码上下文: {}", e);
+            return ReviewContext::default();
+        }
+    };
+    analyzer.set_project_root(root.clone());
+    // 评审暂存的变更时 hunk 的行号对应暂存区中的内容，而不是工作区
+    let source = post_change_source(&root, range, git_diff);
+
+    let files: Vec<ChangedFileAst> = git_diff
+        .changed_files
+        .iter()
+        .filter(|file| file.change_type != ChangeType::Deleted && !file.hunks.is_empty())
+        .filter_map(|file| {
+            let ast = parse_post_change_file(&mut analyzer, &root, &file.path, &source)?;
+            let changed_lines = file
+                .hunks
+                .iter()
+                .map(|hunk| {
+                    let start = hunk.new_range.start.max(1);
+                    (start, start + hunk.new_range.count.saturating_sub(1))
+                })
+                .collect();
+            Some(ChangedFileAst { ast, changed_lines })
+        })
+        .collect();
+
+    build_review_context(&files, config.tree_sitter.context_token_budget)
+}
+
+/// 文件某个版本的内容来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum FileVersionSource {
+    /// 工作区中的文件
+    WorkingTree,
+    /// 暂存区中的文件（`git show :<path>`）
+    Index,
+    /// 提交中的文件（`git show <rev>:<path>`）
+    Revision(String),
+}
+
+/// 返回两个提交的合并基础
+fn merge_base(a: &str, b: &str) -> Option<String> {
+    let args = vec!["merge-base".to_string(), a.to_string(), b.to_string()];
+    match execute_git_command_and_capture_output(&args) {
+        Ok(output) if output.is_success() && !output.stdout.trim().is_empty() => Some(output.stdout.trim().to_string()),
+        _ => None,
+    }
+}
+
+/// 确定变更前的文件内容所在的版本，None 表示变更前没有任何文件（如根提交）
+fn pre_change_source(range: &ReviewRange) -> Option<FileVersionSource> {
+    let revision = match range {
+        ReviewRange::WorkingTree => Some("HEAD".to_string()),
+        ReviewRange::Commit(rev) => Some(format!("{}^", rev)),
+        ReviewRange::Range(range) => match range.split_once("...") {
+            Some((start, end)) => merge_base(
+                if start.is_empty() { "HEAD" } else { start },
+                if end.is_empty() { "HEAD" } else { end },
+            ),
+            None => range.split_once("..").map(|(start, _)| if start.is_empty() { "HEAD" } else { start }.to_string()),
+        },
+        ReviewRange::Branch(base) => {
+            let base = match base {
+                Some(base) => base.clone(),
+                None => default_base_branch().ok()?,
             };
-            
-            let result = execute_git_command_and_capture_output(&diff_args)?;
-            Ok(result.stdout)
+            merge_base(&base, "HEAD")
         }
-        (None, Some(_)) => {
-            // This should not happen with the CLI parser, but handle it just in case
-            Err(AppError::Generic("如果指定了第二个提交，则必须同时指定第一个提交。".to_string()))
+    };
+    revision.filter(|rev| revision_exists(rev)).map(FileVersionSource::Revision)
+}
+
+/// 确定变更后的文件内容所在的版本：评审工作区变更时，有暂存的变更则使用暂存区
+fn post_change_source(root: &std::path::Path, range: &ReviewRange, git_diff: &GitDiff) -> FileVersionSource {
+    match post_change_revision(range) {
+        Some(rev) => FileVersionSource::Revision(rev),
+        None => {
+            let mut args = vec!["-C".to_string(), root.display().to_string()];
+            args.extend(["diff", "--cached", "--quiet", "--"].map(String::from));
+            args.extend(git_diff.changed_files.iter().map(|f| f.path.display().to_string()));
+            match execute_git_command_and_capture_output(&args) {
+                // --quiet 在有差异时以 1 退出
+                Ok(output) if !output.is_success() => FileVersionSource::Index,
+                _ => FileVersionSource::WorkingTree,
+            }
         }
     }
 }
 
+/// 读取文件在某个版本中的内容，文件在该版本中不存在时返回 None
+pub(crate) fn read_file_version(root: &std::path::Path, path: &std::path::Path, source: &FileVersionSource) -> Option<String> {
+    let spec = match source {
+        FileVersionSource::WorkingTree => return fs::read_to_string(root.join(path)).ok(),
+        FileVersionSource::Index => format!(":{}", path.display()),
+        FileVersionSource::Revision(rev) => format!("{}:{}", rev, path.display()),
+    };
+    let args = vec!["-C".to_string(), root.display().to_string(), "show".to_string(), spec];
+    match execute_git_command_and_capture_output(&args) {
+        Ok(output) if output.is_success() => Some(output.stdout),
+        _ => None,
+    }
+}
+
+/// 解析变更前后两个版本的文件并比较其中的定义
+///
+/// 变更前的内容取自基准提交，变更后的内容取自提交、暂存区或工作区，
+/// 因此评审历史提交范围时不受当前工作区影响。
+fn definition_diff_for_review(config: &AppConfig, range: &ReviewRange, git_diff: &GitDiff) -> Option<AstDiff> {
+    let root = get_repository_root().ok()?;
+    let mut analyzer = match TreeSitterAnalyzer::new(config.tree_sitter.clone()) {
+        Ok(analyzer) => analyzer,
+        Err(e) => {
+            tracing::warn!("无法初始化 Tree-sitter 分析器，跳过定义级比较: {}", e);
+            return None;
+        }
+    };
+    analyzer.set_project_root(root.clone());
+    let old_source = pre_change_source(range);
+    let new_source = post_change_source(&root, range, git_diff);
+
+    let files: Vec<FileVersions> = git_diff
+        .changed_files
+        .iter()
+        .filter(|file| analyzer.detect_language(&file.path).ok().flatten().is_some())
+        .map(|file| {
+            let old_path = match file.change_type {
+                ChangeType::Added => None,
+                _ => Some(file.old_path.clone().unwrap_or_else(|| file.path.clone())),
+            };
+            let new_path = match file.change_type {
+                ChangeType::Deleted => None,
+                _ => Some(file.path.clone()),
+            };
+            FileVersions {
+                old_source: old_path.as_ref().zip(old_source.as_ref()).and_then(|(p, s)| read_file_version(&root, p, s)),
+                new_source: new_path.as_ref().and_then(|p| read_file_version(&root, p, &new_source)),
+                old_path,
+                new_path,
+            }
+        })
+        .collect();
+    if files.is_empty() {
+        return None;
+    }
+    Some(analyzer.diff_file_versions(&files))
+}
+
 /// Determine analysis depth from args
 fn get_analysis_depth(args: &ReviewArgs) -> AnalysisDepth {
     match args.depth.to_lowercase().as_str() {
@@ -84,12 +392,19 @@ fn should_use_tree_sitter(args: &ReviewArgs) -> bool {
 
 /// Advanced diff analysis using TreeSitter for language-aware parsing
 /// Creates a detailed GitDiff structure with structural code analysis
+///
+/// 分析器使用配置中的语法库、线程数、超时等设置，只按命令行覆盖分析深度，
+/// 并打开 `root` 仓库中的持久化分析缓存。变更后的文件内容取自被评审的版本，
+/// 而不是可能已经变化的工作区。
 async fn analyze_diff_with_tree_sitter(
     diff_text: &str,
     depth: AnalysisDepth,
-) -> Result<(GitDiff, String), AppError> {
+    config: &TreeSitterConfig,
+    root: std::path::PathBuf,
+    range: &ReviewRange,
+) -> Result<(GitDiff, String, DiffAnalysis), AppError> {
     // Initialize Tree-sitter analyzer with config
-    let mut config = TreeSitterConfig::default();
+    let mut config = config.clone();
     config.analysis_depth = match depth {
         AnalysisDepth::Basic => "shallow".to_string(),
         AnalysisDepth::Normal => "medium".to_string(),
@@ -98,13 +413,16 @@ async fn analyze_diff_with_tree_sitter(
     
     let mut analyzer = TreeSitterAnalyzer::new(config)
         .map_err(|e| AppError::TreeSitter(e))?;
+    analyzer.set_project_root(root.clone());
     
     // Parse the diff to get structured representation
     let git_diff = analyzer.parse_git_diff_text(diff_text)
         .map_err(|e| AppError::TreeSitter(e))?;
     
     // Generate analysis summary based on the diff
-    let analysis = analyzer.analyze_diff(diff_text)
+    let source = post_change_source(&root, range, &git_diff);
+    let analysis = analyzer
+        .analyze_diff_with_sources(diff_text, &|path| read_file_version(&root, path, &source))
         .map_err(|e| AppError::TreeSitter(e))?;
     
     // Create a more detailed analysis text from the TreeSitter analysis
@@ -133,6 +451,15 @@ async fn analyze_diff_with_tree_sitter(
     analysis_text.push_str(&format!("- 类型/结构变更: **{}**\n", change_analysis.type_changes));
     analysis_text.push_str(&format!("- 接口/特征变更: **{}**\n", change_analysis.interface_changes));
     analysis_text.push_str(&format!("- 其他结构变更: **{}**\n\n", change_analysis.other_changes));
+
+    // 显示移动的代码，避免被误读为删除后重新实现
+    if !analysis.moved_code.is_empty() {
+        analysis_text.push_str("#### 移动的代码\n\n");
+        for moved in &analysis.moved_code {
+            analysis_text.push_str(&format!("- {}\n", moved.describe()));
+        }
+        analysis_text.push('\n');
+    }
     
     // 按语言分类显示文件
     let mut java_files = Vec::new();
@@ -166,6 +493,7 @@ async fn analyze_diff_with_tree_sitter(
                             "added" | "added_content" => "➕ ",
                             "deleted" => "❌ ",
                             "modified" | "modified_with_deletion" => "🔄 ",
+                            "moved" => "🚚 ",
                             _ => "",
                         },
                         None => "",
@@ -208,6 +536,7 @@ async fn analyze_diff_with_tree_sitter(
                             "added" | "added_content" => "➕ ",
                             "deleted" => "❌ ",
                             "modified" | "modified_with_deletion" => "🔄 ",
+                            "moved" => "🚚 ",
                             _ => "",
                         },
                         None => "",
@@ -310,6 +639,28 @@ async fn analyze_diff_with_tree_sitter(
                 } else {
                     analysis_text.push_str("  - 关注所有权和生命周期管理\n");
                 }
+            } else if lang_change.starts_with("Python") {
+                analysis_text.push_str("- 🐍 **Python特定变更**\n");
+                if lang_change.contains("PublicApi") {
+                    analysis_text.push_str("  - 关注 __all__ 导出列表变化对调用方导入的影响\n");
+                } else if lang_change.contains("Decorator") {
+                    analysis_text.push_str("  - 关注装饰器变化对路由、属性访问和缓存行为的影响\n");
+                } else if lang_change.contains("Async") {
+                    analysis_text.push_str("  - 关注异步函数中的阻塞调用和遗漏的 await\n");
+                } else {
+                    analysis_text.push_str("  - 关注公开类的继承关系和属性变化\n");
+                }
+            } else if lang_change.starts_with("Go") {
+                analysis_text.push_str("- 🐹 **Go特定变更**\n");
+                if lang_change.contains("Interface") {
+                    analysis_text.push_str("  - 关注接口方法变化对所有实现类型的影响\n");
+                } else if lang_change.contains("MethodSet") {
+                    analysis_text.push_str("  - 关注方法集变化是否导致类型不再满足接口\n");
+                } else if lang_change.contains("Struct") {
+                    analysis_text.push_str("  - 关注结构体字段变化对序列化标签和零值的影响\n");
+                } else {
+                    analysis_text.push_str("  - 关注导出标识符变化对包外调用方的兼容性\n");
+                }
             } else {
                 analysis_text.push_str("- 🔧 **特定语言变更**\n");
                 analysis_text.push_str("  - 关注语言特定惯用法和最佳实践\n");
@@ -341,7 +692,7 @@ async fn analyze_diff_with_tree_sitter(
         }
     }
     
-    Ok((git_diff, analysis_text))
+    Ok((git_diff, analysis_text, analysis))
 }
 
 /// Generate the prompt for AI review
@@ -385,8 +736,8 @@ async fn generate_ai_review_prompt(
     
     // Combine everything into the final prompt
     let prompt = format!(
-        "{}\n\n## 代码评审请求\n\n{}\n\n## 评审指南\n\n{}\n\n## TreeSitter结构分析\n\n{}\n\n## 代码变更\n\n```diff\n{}\n```",
-        base_prompt, focus_instruction, review_guide, analysis, diff_text
+        "{}\n\n## 代码评审请求\n\n{}\n\n## 评审指南\n\n{}\n\n## 结构化问题列表\n\n{}\n\n## TreeSitter结构分析\n\n{}\n\n## 代码变更\n\n```diff\n{}\n```",
+        base_prompt, focus_instruction, review_guide, FINDINGS_JSON_INSTRUCTION, analysis, diff_text
     );
     
     Ok(prompt)
@@ -415,14 +766,23 @@ fn expand_tilde(path: &str) -> String {
 /// Format and save or display review results
 async fn format_and_output_review(
     review_text: &str, 
+    issues: &[Issue],
     args: &ReviewArgs
 ) -> Result<(), AppError> {
+    let issues_section = render_issues_markdown(issues);
+    let full_text = if issues_section.is_empty() {
+        review_text.to_string()
+    } else {
+        format!("{}\n\n{}", review_text, issues_section)
+    };
+
     // Process based on requested format
     let formatted_output = match args.format.to_lowercase().as_str() {
         "json" => {
             // Convert to JSON format
             serde_json::json!({
                 "review": review_text,
+                "findings": issues,
                 "timestamp": "2023-01-01T00:00:00Z",
                 "format_version": "1.0"
             }).to_string()
@@ -438,12 +798,12 @@ async fn format_and_output_review(
                 <div>{}</div>\n\
                 <p><em>由 Gitie 生成</em></p>\n\
                 </body>\n</html>",
-                review_text.replace("\n", "<br>")
+                full_text.replace("\n", "<br>")
             )
         },
         _ => {
             // Default to text format (markdown)
-            review_text.to_string()
+            full_text
         }
     };
     
@@ -473,9 +833,17 @@ async fn format_and_output_review(
 /// Main handler for the review command
 pub async fn handle_review(args: ReviewArgs, config: &AppConfig) -> Result<(), AppError> {
     tracing::info!("执行代码评审");
+
+    // 修复会写入工作区文件，只有评审工作区或暂存区的变更时才能保证行号对应
+    let range = parse_review_range(&args)?;
+    if args.fix && range != ReviewRange::WorkingTree {
+        return Err(AppError::Generic(
+            "--fix 只能用于评审工作区或暂存区的变更，评审历史提交时无法将修复应用到工作区".to_string(),
+        ));
+    }
     
     // Extract the Git diff
-    let diff_text = extract_diff_for_review(&args).await?;
+    let diff_text = extract_diff_for_review(&args, &range).await?;
     
     if diff_text.trim().is_empty() {
         return Err(AppError::Generic("没有检测到代码变更，无法执行评审。".to_string()));
@@ -489,15 +857,11 @@ pub async fn handle_review(args: ReviewArgs, config: &AppConfig) -> Result<(), A
     let use_tree_sitter = should_use_tree_sitter(&args);
     
     // Analyze the diff with appropriate analyzer
-    let (git_diff, analysis_text, analysis_results) = if use_tree_sitter {
+    let (git_diff, mut analysis_text, analysis_results) = if use_tree_sitter {
         tracing::info!("使用TreeSitter进行深度代码分析");
-        let (diff, text) = analyze_diff_with_tree_sitter(&diff_text, depth).await?;
-        // 获取额外的分析结果用于语言信息
-        let mut analyzer = TreeSitterAnalyzer::new(TreeSitterConfig::default())
-            .map_err(|e| AppError::TreeSitter(e))?;
-        let analysis_obj = analyzer.analyze_diff(&diff_text)
-            .map_err(|e| AppError::TreeSitter(e))?;
-        (diff, text, Some(analysis_obj))
+        let (diff, text, analysis) =
+            analyze_diff_with_tree_sitter(&diff_text, depth, &config.tree_sitter, get_repository_root()?, &range).await?;
+        (diff, text, Some(analysis))
     } else {
         tracing::info!("使用简化的代码分析");
         // Fallback to simple diff parser
@@ -523,6 +887,39 @@ pub async fn handle_review(args: ReviewArgs, config: &AppConfig) -> Result<(), A
         (git_diff, simple_analysis, None)
     };
     
+    // 比较变更前后两个版本的语法树，列出精确的定义级变更
+    if use_tree_sitter
+        && let Some(ast_diff) = definition_diff_for_review(config, &range, &git_diff)
+    {
+        tracing::info!(
+            "定义级变更: 新增 {}，删除 {}，修改 {}，重命名 {}，移动 {}",
+            ast_diff.count(DefinitionChangeKind::Added),
+            ast_diff.count(DefinitionChangeKind::Removed),
+            ast_diff.count(DefinitionChangeKind::Modified),
+            ast_diff.count(DefinitionChangeKind::Renamed),
+            ast_diff.count(DefinitionChangeKind::Moved)
+        );
+        let section = ast_diff.to_markdown();
+        if !section.is_empty() {
+            analysis_text.push_str(&section);
+            analysis_text.push('\n');
+        }
+    }
+
+    // 变更所在的完整函数或类，以及它们引用的定义
+    let code_context = if use_tree_sitter && config.tree_sitter.context_token_budget > 0 {
+        let code_context = build_code_context_for_review(config, get_repository_root()?, &range, &git_diff);
+        tracing::info!(
+            "评审上下文: {} 个代码片段，约 {} tokens，省略 {} 个",
+            code_context.snippets.len(),
+            code_context.estimated_tokens,
+            code_context.omitted
+        );
+        Some(code_context)
+    } else {
+        None
+    };
+
     // 为AI审查增加更多有用的上下文
     let language_info = if let Some(ref analysis) = analysis_results {
         // 从TreeSitter分析中获取详细语言信息
@@ -543,20 +940,28 @@ pub async fn handle_review(args: ReviewArgs, config: &AppConfig) -> Result<(), A
             .join(", ")
     };
     
-    // Generate AI prompt with enhanced context
-    let prompt = generate_ai_review_prompt(config, &diff_text, &analysis_text, &args, &git_diff, &language_info).await?;
-    
-    // Try to send to AI
+    // 按语言分组发送至 AI，--focus 选择了评审角色时每个角色单独评审一轮
     tracing::info!("发送至 AI 进行代码评审");
-    let ai_response = match send_prompt_and_get_response(
-        config, 
-        &prompt,
-        "您是一位经验丰富的代码评审专家，精通多种编程语言和软件开发最佳实践。"
-    ).await {
-        Ok(response) => response,
+    let ai_review = match run_review_passes(config, &diff_text, &analysis_text, &args, &language_info, code_context).await {
+        Ok(response) => Some(response),
         Err(e) => {
             // 如果AI请求失败，使用简单结果
             tracing::warn!("AI请求失败: {}，使用简单评审结果", e);
+            None
+        }
+    };
+
+    // 合并 AI 的结构化问题与规则引擎的问题
+    let rule_issues = SimpleReviewer::new(config.tree_sitter.clone()).review_issues(&git_diff);
+    let ai_issues = match &ai_review {
+        Some(review) => collect_ai_findings(config, review).await.unwrap_or_default(),
+        None => Vec::new(),
+    };
+    let issues = merge_issues(ai_issues, rule_issues);
+
+    let ai_response = match ai_review {
+        Some(review) => strip_findings_blocks(&review),
+        None => {
             let mut simple_response = String::new();
             simple_response.push_str("# 代码评审结果\n\n");
             simple_response.push_str("无法连接到 AI 服务，请检查网络连接和 API 配置。\n\n");
@@ -567,13 +972,397 @@ pub async fn handle_review(args: ReviewArgs, config: &AppConfig) -> Result<(), A
         }
     };
     
+    // 为可以机械修复的问题生成补丁建议，评审历史提交时基于被评审的版本生成，仅作展示
+    let root = get_repository_root()?;
+    let fix_source = match post_change_revision(&range) {
+        Some(rev) => FileVersionSource::Revision(rev),
+        None => FileVersionSource::WorkingTree,
+    };
+    let fixes = suggest_fixes(&collect_added_lines(&diff_text), |path| {
+        read_file_version(&root, std::path::Path::new(path), &fix_source)
+    });
+    let ai_response = if !args.fix && !fixes.is_empty() {
+        format!("{}\n\n{}", ai_response, format_fix_suggestions(&fixes))
+    } else {
+        ai_response
+    };
+
     // Format and output the review
-    format_and_output_review(&ai_response, &args).await?;
+    format_and_output_review(&ai_response, &issues, &args).await?;
+
+    if args.fix {
+        apply_fixes_interactively(&fixes, &root)?;
+    }
     
     Ok(())
 }
 
+/// 结构化问题列表格式错误时请求模型重新输出的最大次数
+const MAX_FINDINGS_RETRIES: usize = 2;
+
+/// 从 AI 评审中提取结构化问题，格式错误时将错误反馈给模型并重试
+///
+/// 重试后仍无法解析时返回 None，评审报告照常输出，只是不包含 AI 的结构化问题。
+async fn collect_ai_findings(config: &AppConfig, review_text: &str) -> Option<Vec<Issue>> {
+    let mut error = match parse_ai_findings(review_text) {
+        Ok(issues) => return Some(issues),
+        Err(e) => e,
+    };
+
+    for attempt in 1..=MAX_FINDINGS_RETRIES {
+        tracing::debug!("AI 问题列表无法解析（第 {} 次重试）: {}", attempt, error);
+        let prompt = format!(
+            "下面的代码评审末尾的结构化问题列表缺失或格式错误：{}\n\n\
+            请根据评审内容重新输出问题列表，只输出 JSON 数组本身，不要包含其他文字。\n\n{}\n\n## 代码评审\n\n{}",
+            error, FINDINGS_JSON_INSTRUCTION, review_text
+        );
+        match send_prompt_and_get_response(config, &prompt, BASE_REVIEW_SYSTEM_PROMPT).await {
+            Ok(response) => match parse_ai_findings(&response) {
+                Ok(issues) => return Some(issues),
+                Err(e) => error = e,
+            },
+            Err(e) => {
+                tracing::warn!("无法获取 AI 问题列表: {}", e);
+                return None;
+            }
+        }
+    }
+
+    tracing::warn!("AI 问题列表格式错误，已忽略: {}", error);
+    None
+}
+
+/// 执行评审：`--focus` 选择了评审角色时并发执行各角色的评审并合并，否则执行一轮评审
+async fn run_review_passes(
+    config: &AppConfig,
+    diff_text: &str,
+    analysis_text: &str,
+    args: &ReviewArgs,
+    languages: &str,
+    code_context: Option<ReviewContext>,
+) -> Result<String, AppError> {
+    let personas = parse_focus_personas(args.focus.as_deref());
+    if personas.is_empty() {
+        return review_by_language(config, diff_text, analysis_text, args, languages, code_context.as_ref(), None).await;
+    }
+
+    tracing::info!(
+        "执行 {} 轮专项评审: {}",
+        personas.len(),
+        personas.iter().map(|p| p.display_name()).collect::<Vec<_>>().join(", ")
+    );
+    let mut passes = tokio::task::JoinSet::new();
+    for (index, persona) in personas.iter().copied().enumerate() {
+        let config = config.clone();
+        let diff_text = diff_text.to_string();
+        let analysis_text = analysis_text.to_string();
+        let languages = languages.to_string();
+        let code_context = code_context.clone();
+        let mut persona_args = args.clone();
+        persona_args.focus = Some(persona.focus_instruction().to_string());
+        passes.spawn(async move {
+            let result = review_by_language(
+                &config,
+                &diff_text,
+                &analysis_text,
+                &persona_args,
+                &languages,
+                code_context.as_ref(),
+                None,
+            ).await;
+            (index, persona, result)
+        });
+    }
+
+    // 按 --focus 中的顺序整理结果，不受完成先后影响
+    let mut results = Vec::with_capacity(personas.len());
+    while let Some(joined) = passes.join_next().await {
+        match joined {
+            Ok(result) => results.push(result),
+            Err(e) => tracing::warn!("专项评审任务异常结束: {}", e),
+        }
+    }
+    results.sort_by_key(|(index, _, _)| *index);
+
+    let mut reports = Vec::with_capacity(results.len());
+    let mut last_error = None;
+    for (_, persona, result) in results {
+        match result {
+            Ok(report) => reports.push((persona, report)),
+            Err(e) => {
+                tracing::warn!("{}评审失败: {}", persona.display_name(), e);
+                last_error = Some(e);
+            }
+        }
+    }
+    match reports.len() {
+        0 => return Err(last_error.unwrap_or_else(|| AppError::Generic("所有专项评审均失败".to_string()))),
+        1 => return Ok(reports.remove(0).1),
+        _ => {}
+    }
+
+    tracing::info!("合并 {} 份专项评审报告", reports.len());
+    let merge_prompt = format!("{}\n{}", build_merge_prompt(&reports), FINDINGS_JSON_INSTRUCTION);
+    match send_prompt_and_get_response(config, &merge_prompt, BASE_REVIEW_SYSTEM_PROMPT).await {
+        Ok(merged) => Ok(merged),
+        Err(e) => {
+            tracing::warn!("合并评审报告失败: {}，按角色列出各评审结果", e);
+            Ok(concat_persona_reports(&reports))
+        }
+    }
+}
+
+/// 按语言分组请求 AI 评审并合并结果
+///
+/// 每组只包含该语言文件的 diff 与代码上下文，系统提示附加 `review-<lang>-prompt.md`
+/// 中的语言评审规范。没有对应语言提示的文件使用通用提示，`languages` 作为其语言说明。
+async fn review_by_language(
+    config: &AppConfig,
+    diff_text: &str,
+    analysis_text: &str,
+    args: &ReviewArgs,
+    languages: &str,
+    code_context: Option<&ReviewContext>,
+    extra_instruction: Option<&str>,
+) -> Result<String, AppError> {
+    let groups = group_diff_by_language(diff_text);
+    let mut reviews = Vec::with_capacity(groups.len());
+
+    for group in groups {
+        let group_diff = parse_simple_diff(&group.diff);
+        let mut group_analysis = analysis_text.to_string();
+        if let Some(context) = code_context {
+            let snippets: Vec<_> = context
+                .snippets
+                .iter()
+                .filter(|s| {
+                    group.paths.contains(&s.path)
+                        || (s.referenced && review_language_for_path(&s.path) == group.language)
+                })
+                .cloned()
+                .collect();
+            let group_context = ReviewContext {
+                estimated_tokens: snippets.iter().map(|s| estimate_tokens(&s.text)).sum(),
+                snippets,
+                omitted: context.omitted,
+            };
+            group_analysis = format!("{}\n{}", group_analysis, group_context.to_markdown());
+        }
+        let group_languages = if group.language == OTHER_LANGUAGE { languages } else { group.display_name() };
+
+        let mut prompt = generate_ai_review_prompt(config, &group.diff, &group_analysis, args, &group_diff, group_languages).await?;
+        if let Some(instruction) = extra_instruction {
+            prompt = format!("{}\n\n## 严重程度统计\n\n{}", prompt, instruction);
+        }
+
+        tracing::info!("评审 {} 个 {} 文件", group.paths.len(), group.display_name());
+        let response = send_prompt_and_get_response(config, &prompt, &review_system_prompt(&group.language)).await?;
+        reviews.push((group, response));
+    }
+
+    Ok(merge_language_reviews(&reviews))
+}
+
+/// 将修复建议格式化为评审报告中的章节
+fn format_fix_suggestions(fixes: &[FixSuggestion]) -> String {
+    let mut section = format!(
+        "## 自动修复建议\n\n发现 {} 个可以自动修复的问题，可使用 `gitie review --fix` 逐个确认并应用。\n",
+        fixes.len()
+    );
+    for fix in fixes {
+        section.push_str(&format!(
+            "\n### {} ({}:{})\n\n```diff\n{}```\n",
+            fix.issue.title,
+            fix.issue.location.file_path,
+            fix.issue.location.start_line,
+            render_fix_diff(fix)
+        ));
+    }
+    section
+}
+
+/// 以彩色输出统一 diff
+fn print_colored_diff(diff: &str) {
+    for line in diff.lines() {
+        if line.starts_with("+++") || line.starts_with("---") {
+            println!("{}", line.bold());
+        } else if line.starts_with("@@") {
+            println!("{}", line.cyan());
+        } else if line.starts_with('+') {
+            println!("{}", line.green());
+        } else if line.starts_with('-') {
+            println!("{}", line.red());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+/// 逐个展示修复建议，并将用户接受的修复应用到工作区文件
+fn apply_fixes_interactively(fixes: &[FixSuggestion], root: &std::path::Path) -> Result<(), AppError> {
+    if fixes.is_empty() {
+        println!("{}", "没有可以自动修复的问题。".green());
+        return Ok(());
+    }
+
+    let interactive = std::io::stdin().is_terminal();
+    if !interactive {
+        tracing::warn!("标准输入不是终端，仅输出修复建议，不修改文件");
+    }
+
+    let mut accepted: Vec<&FixSuggestion> = Vec::new();
+    let mut accept_all = false;
+    for (index, fix) in fixes.iter().enumerate() {
+        println!(
+            "\n{} {}",
+            format!("[{}/{}]", index + 1, fixes.len()).bold(),
+            fix.issue.title.bold()
+        );
+        print_colored_diff(&render_fix_diff(fix));
+
+        if !interactive {
+            continue;
+        }
+        if accept_all {
+            accepted.push(fix);
+            continue;
+        }
+        match ask("应用此修复？", "y,n,a,q")?.as_str() {
+            "y" | "yes" => accepted.push(fix),
+            "a" => {
+                accept_all = true;
+                accepted.push(fix);
+            }
+            "q" => break,
+            _ => {}
+        }
+    }
+
+    // 按文件分组应用修复
+    let mut by_file: std::collections::BTreeMap<&str, Vec<&FixSuggestion>> = std::collections::BTreeMap::new();
+    for fix in accepted {
+        by_file.entry(fix.issue.location.file_path.as_str()).or_default().push(fix);
+    }
+
+    let mut applied = 0;
+    for (path, file_fixes) in by_file {
+        let full_path = root.join(path);
+        let content = fs::read_to_string(&full_path)
+            .map_err(|e| AppError::IO(format!("无法读取文件: {}", path), e))?;
+        match apply_fixes_to_content(&content, &file_fixes) {
+            Ok(fixed) => {
+                fs::write(&full_path, fixed)
+                    .map_err(|e| AppError::IO(format!("无法写入文件: {}", path), e))?;
+                applied += file_fixes.len();
+            }
+            Err(reason) => println!("{} {}", "跳过修复:".yellow(), reason),
+        }
+    }
+
+    if applied > 0 {
+        println!(
+            "\n{}",
+            format!("已应用 {} 个修复，可使用 git diff 查看修改。", applied).green()
+        );
+    }
+    Ok(())
+}
+
+/// 提交前评审的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewPolicy {
+    /// 显示评审结果并询问是否继续提交
+    Ask,
+    /// 存在错误级别的问题时自动阻止提交
+    BlockOnError,
+    /// 仅显示评审结果，从不阻止提交
+    NeverBlock,
+}
+
+impl ReviewPolicy {
+    /// 根据命令行参数确定策略，未指定时在终端中询问，否则只显示结果而不阻止提交
+    ///
+    /// 规则引擎的启发式检查（如含 `token` 的行）会产生误报，CI 和钩子中只有显式指定
+    /// `--review-policy block-on-error` 时才按错误级别阻止提交。
+    pub fn resolve(policy: Option<&str>, interactive: bool) -> Self {
+        match policy {
+            Some("ask") if interactive => ReviewPolicy::Ask,
+            Some("ask") => {
+                tracing::warn!("标准输入不是终端，无法询问是否继续提交，改用 block-on-error 策略");
+                ReviewPolicy::BlockOnError
+            }
+            Some("block-on-error") => ReviewPolicy::BlockOnError,
+            Some("never-block") => ReviewPolicy::NeverBlock,
+            _ if interactive => ReviewPolicy::Ask,
+            _ => ReviewPolicy::NeverBlock,
+        }
+    }
+}
+
+/// 评审中各严重程度问题的数量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SeverityCounts {
+    pub errors: usize,
+    pub warnings: usize,
+    pub infos: usize,
+}
+
+lazy_static::lazy_static! {
+    static ref SEVERITY_SUMMARY: regex::Regex = regex::Regex::new(
+        r"(?i)严重程度统计[:：]\s*error\s*=\s*(\d+)\s*[,，]\s*warning\s*=\s*(\d+)\s*[,，]\s*info\s*=\s*(\d+)"
+    ).unwrap();
+}
+
+/// 要求 AI 在评审末尾输出的结构化严重程度统计
+const SEVERITY_SUMMARY_INSTRUCTION: &str = "在评审的最后单独输出一行严重程度统计，格式严格为：\n\
+    `严重程度统计: error=<数量>, warning=<数量>, info=<数量>`\n\
+    其中 error 表示必须在提交前修复的问题（如缺陷、安全漏洞、编译错误），warning 表示应当修复的问题，info 表示可选的改进建议。";
+
+/// 从 AI 评审文本中解析严重程度统计，未找到统计行时返回 None
+///
+/// 按语言分组评审时每组各有一行统计，结果为各组之和。
+pub fn parse_severity_summary(review_text: &str) -> Option<SeverityCounts> {
+    let mut total: Option<SeverityCounts> = None;
+    for caps in SEVERITY_SUMMARY.captures_iter(review_text) {
+        let count = |i: usize| caps.get(i).and_then(|m| m.as_str().parse::<usize>().ok()).unwrap_or(0);
+        let counts = total.get_or_insert_with(SeverityCounts::default);
+        counts.errors += count(1);
+        counts.warnings += count(2);
+        counts.infos += count(3);
+    }
+    total
+}
+
+/// 合并规则引擎结果与 AI 评审的严重程度统计
+fn collect_severity_counts(rule_results: &[SimpleReviewResult], ai_counts: Option<SeverityCounts>) -> SeverityCounts {
+    let mut counts = ai_counts.unwrap_or_default();
+    for result in rule_results {
+        match result.severity {
+            Severity::Error => counts.errors += 1,
+            Severity::Warning => counts.warnings += 1,
+            Severity::Info | Severity::Hint => {}
+        }
+    }
+    counts
+}
+
+/// 统计问题列表中各严重程度的数量
+fn count_issue_severities(issues: &[Issue]) -> SeverityCounts {
+    let mut counts = SeverityCounts::default();
+    for issue in issues {
+        match issue.severity {
+            Severity::Error => counts.errors += 1,
+            Severity::Warning => counts.warnings += 1,
+            Severity::Info | Severity::Hint => counts.infos += 1,
+        }
+    }
+    counts
+}
+
 /// Handler for the commit command with review option
+///
+/// Returns `Ok(true)` when the commit should be cancelled. Under the
+/// `block-on-error` policy, error-level findings abort with an error instead.
 pub async fn handle_commit_with_review(
     args: &crate::cli_interface::args::CommitArgs, 
     config: &AppConfig
@@ -596,6 +1385,8 @@ pub async fn handle_commit_with_review(
     
     // Create ReviewArgs from CommitArgs
     let review_args = ReviewArgs {
+        revision: None,
+        branch: None,
         depth: "normal".to_string(),
         focus: None,
         lang: None,
@@ -605,6 +1396,7 @@ pub async fn handle_commit_with_review(
         no_tree_sitter: false,
         review_ts: false,
         passthrough_args: vec![],
+        fix: false,
         commit1: None,
         commit2: None,
     };
@@ -631,24 +1423,34 @@ pub async fn handle_commit_with_review(
         .collect::<Vec<_>>()
         .join(", ");
     
+    let policy = ReviewPolicy::resolve(args.review_policy.as_deref(), std::io::stdin().is_terminal());
+    tracing::debug!("提交前评审策略: {:?}", policy);
+
     // Try to get AI review
-    let review_text = match generate_ai_review_prompt(config, &diff_text, &analysis_text, &review_args, &git_diff, &language_info).await {
-        Ok(prompt) => {
-            match send_prompt_and_get_response(
-                config, 
-                &prompt,
-                "您是一位经验丰富的代码评审专家，精通多种编程语言和软件开发最佳实践。"
-            ).await {
-                Ok(response) => response,
-                Err(_) => {
-                    // Fall back to simple message
-                    "# 代码评审结果\n\n无法连接到 AI 服务，请检查网络连接和 API 配置。\n\n建议手动检查代码质量后再提交。".to_string()
-                }
-            }
-        },
-        Err(_) => {
+    let ai_review = match review_by_language(
+        config,
+        &diff_text,
+        &analysis_text,
+        &review_args,
+        &language_info,
+        None,
+        Some(SEVERITY_SUMMARY_INSTRUCTION),
+    ).await {
+        Ok(response) => Some(response),
+        Err(e) => {
+            tracing::warn!("AI 评审失败: {}", e);
+            None
+        }
+    };
+    let ai_issues = match &ai_review {
+        Some(review) => collect_ai_findings(config, review).await,
+        None => None,
+    };
+    let review_text = match ai_review {
+        Some(review) => strip_findings_blocks(&review),
+        None => {
             // Fall back to simple message
-            "# 代码评审结果\n\n无法生成代码评审提示，请检查配置。\n\n建议手动检查代码质量后再提交。".to_string()
+            "# 代码评审结果\n\n无法连接到 AI 服务，请检查网络连接和 API 配置。\n\n建议手动检查代码质量后再提交。".to_string()
         }
     };
     
@@ -657,18 +1459,212 @@ pub async fn handle_commit_with_review(
     println!("{}", "===================".green());
     println!("\n{}", review_text);
     
-    // Ask user if they want to continue with the commit
-    print!("\n{} (y/n): ", "是否继续提交？".bold().yellow());
-    std::io::stdout().flush().unwrap();
-    
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input).unwrap();
-    
-    if input.trim().to_lowercase() == "y" {
-        println!("继续提交...");
-        Ok(false) // Continue with commit
-    } else {
-        println!("取消提交。");
-        Ok(true) // Cancel commit
+    // 合并规则引擎与 AI 评审的问题；没有结构化问题列表时使用 AI 的严重程度统计
+    let reviewer = SimpleReviewer::new(config.tree_sitter.clone());
+    let counts = match ai_issues {
+        Some(ai_issues) => {
+            let issues = merge_issues(ai_issues, reviewer.review_issues(&git_diff));
+            let issues_section = render_issues_markdown(&issues);
+            if !issues_section.is_empty() {
+                println!("\n{}", issues_section);
+            }
+            count_issue_severities(&issues)
+        }
+        None => {
+            let ai_counts = parse_severity_summary(&review_text);
+            if ai_counts.is_none() {
+                tracing::debug!("AI 评审未包含严重程度统计，仅使用规则引擎的结果");
+            }
+            collect_severity_counts(&reviewer.review(&git_diff), ai_counts)
+        }
+    };
+    println!(
+        "\n{} 错误: {}, 警告: {}, 提示: {}",
+        "评审统计:".bold(),
+        counts.errors.to_string().red(),
+        counts.warnings.to_string().yellow(),
+        counts.infos
+    );
+
+    match policy {
+        ReviewPolicy::NeverBlock => {
+            println!("继续提交...");
+            Ok(false)
+        }
+        ReviewPolicy::BlockOnError if counts.errors > 0 => Err(AppError::Generic(format!(
+            "代码评审发现 {} 个错误级别的问题，已阻止提交（可使用 --review-policy never-block 跳过）",
+            counts.errors
+        ))),
+        ReviewPolicy::BlockOnError => {
+            println!("未发现错误级别的问题，继续提交...");
+            Ok(false)
+        }
+        ReviewPolicy::Ask => {
+            if confirm("是否继续提交？")? {
+                println!("继续提交...");
+                Ok(false) // Continue with commit
+            } else {
+                println!("取消提交。");
+                Ok(true) // Cancel commit
+            }
+        }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_review_policy_resolution() {
+        assert_eq!(ReviewPolicy::resolve(None, true), ReviewPolicy::Ask);
+        assert_eq!(ReviewPolicy::resolve(None, false), ReviewPolicy::NeverBlock);
+        assert_eq!(ReviewPolicy::resolve(Some("block-on-error"), false), ReviewPolicy::BlockOnError);
+        assert_eq!(ReviewPolicy::resolve(Some("ask"), false), ReviewPolicy::BlockOnError);
+        assert_eq!(ReviewPolicy::resolve(Some("never-block"), false), ReviewPolicy::NeverBlock);
+    }
+
+    fn review_args(args: &[&str]) -> ReviewArgs {
+        use crate::cli_interface::args::{GitieArgs, GitieSubCommand};
+        use clap::Parser;
+
+        let mut full = vec!["gitie", "review"];
+        full.extend_from_slice(args);
+        match GitieArgs::try_parse_from(full).unwrap().command {
+            GitieSubCommand::Review(args) => args,
+            other => panic!("unexpected subcommand: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_review_range() {
+        let cases: &[(&[&str], ReviewRange)] = &[
+            (&[], ReviewRange::WorkingTree),
+            (&["HEAD~2"], ReviewRange::Commit("HEAD~2".to_string())),
+            (&["main..feature"], ReviewRange::Range("main..feature".to_string())),
+            (&["main...HEAD", "--", "src/"], ReviewRange::Range("main...HEAD".to_string())),
+            (&["--branch"], ReviewRange::Branch(None)),
+            (&["--branch", "develop"], ReviewRange::Branch(Some("develop".to_string()))),
+            (&["--commit1", "abc"], ReviewRange::Range("abc..HEAD".to_string())),
+        ];
+        for (args, expected) in cases {
+            assert_eq!(&parse_review_range(&review_args(args)).unwrap(), expected, "args: {:?}", args);
+        }
+
+        assert_eq!(review_args(&["HEAD", "--", "src/", "docs/"]).passthrough_args, vec!["src/", "docs/"]);
+        assert!(parse_review_range(&review_args(&["HEAD", "--commit1", "abc"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_severity_summary() {
+        let review = "# 评审\n\n- 问题\n\n严重程度统计: error=2, warning=1, info=0";
+        assert_eq!(
+            parse_severity_summary(review),
+            Some(SeverityCounts { errors: 2, warnings: 1, infos: 0 })
+        );
+        assert_eq!(parse_severity_summary("没有统计"), None);
+        assert_eq!(
+            parse_severity_summary(&format!("{}\n## Java\n严重程度统计: error=0, warning=2, info=3", review)),
+            Some(SeverityCounts { errors: 2, warnings: 3, infos: 3 })
+        );
+
+        let rules = vec![SimpleReviewResult {
+            title: "检测到硬编码凭证".to_string(),
+            content: String::new(),
+            severity: Severity::Error,
+        }];
+        assert_eq!(collect_severity_counts(&rules, None).errors, 1);
+    }
+
+    #[tokio::test]
+    async fn test_tree_sitter_analysis_reuses_disk_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let status = std::process::Command::new("git").arg("init").arg("-q").arg(&root).status().unwrap();
+        assert!(status.success());
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "pub fn added() -> u32 {\n    1\n}\n").unwrap();
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\nnew file mode 100644\n--- /dev/null\n+++ b/src/lib.rs\n@@ -0,0 +1,3 @@\n+pub fn added() -> u32 {\n+    1\n+}\n";
+        let config = TreeSitterConfig::default();
+
+        let (_, text, _) = analyze_diff_with_tree_sitter(diff, AnalysisDepth::Normal, &config, root.clone(), &ReviewRange::WorkingTree).await.unwrap();
+        assert!(text.contains("`added`"), "{}", text);
+        let cache_dir = root.join(".git/gitie/analysis-cache");
+        let entries: Vec<_> = fs::read_dir(&cache_dir)
+            .unwrap()
+            .map(|prefix| prefix.unwrap().path())
+            .filter(|prefix| prefix.is_dir())
+            .flat_map(|prefix| fs::read_dir(prefix).unwrap())
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(entries.len(), 1);
+
+        // 改写缓存中的定义名称，第二次分析显示改写后的名称说明结构取自磁盘缓存
+        let data = fs::read_to_string(&entries[0]).unwrap().replace("\"added\"", "\"from_cache\"");
+        fs::write(&entries[0], data).unwrap();
+        let (_, text, analysis) = analyze_diff_with_tree_sitter(diff, AnalysisDepth::Normal, &config, root, &ReviewRange::WorkingTree).await.unwrap();
+        assert!(text.contains("`from_cache`"), "{}", text);
+        assert!(analysis.file_analyses[0].affected_nodes.iter().any(|node| node.name == "from_cache"));
+    }
+
+    fn git(dir: &std::path::Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git").arg("-C").arg(dir).args(args).output().unwrap();
+        assert!(output.status.success(), "git {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    #[tokio::test]
+    async fn test_tree_sitter_analysis_of_past_commit_ignores_working_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        git(&root, &["init", "-q"]);
+        git(&root, &["config", "user.name", "Gitie"]);
+        git(&root, &["config", "user.email", "gitie@example.com"]);
+        git(&root, &["config", "commit.gpgsign", "false"]);
+        fs::write(root.join("lib.rs"), "pub fn first() -> u32 {\n    1\n}\n").unwrap();
+        git(&root, &["add", "."]);
+        git(&root, &["commit", "-q", "-m", "first"]);
+        fs::write(root.join("lib.rs"), "pub fn first() -> u32 {\n    1\n}\n\npub fn second() -> u32 {\n    2\n}\n").unwrap();
+        git(&root, &["commit", "-q", "-am", "second"]);
+        let diff = git(&root, &["diff", "HEAD^!"]);
+
+        // 提交之后工作区中的文件被改写，新增函数所在的行变成了另一个函数
+        fs::write(root.join("lib.rs"), "pub fn first() -> u32 {\n    1\n}\n\npub fn rewritten() -> u32 {\n    3\n}\n").unwrap();
+
+        let config = TreeSitterConfig::default();
+        let range = ReviewRange::Commit("HEAD".to_string());
+        let (_, text, analysis) = analyze_diff_with_tree_sitter(&diff, AnalysisDepth::Normal, &config, root, &range).await.unwrap();
+        let names: Vec<&str> = analysis.file_analyses[0].affected_nodes.iter().map(|node| node.name.as_str()).collect();
+        assert!(names.contains(&"second"), "{:?}", names);
+        assert!(!text.contains("rewritten"), "{}", text);
+    }
+
+    #[test]
+    fn test_code_context_for_staged_review_reads_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        git(&root, &["init", "-q"]);
+        fs::write(root.join("lib.rs"), "pub fn first() -> u32 {\n    1\n}\n").unwrap();
+        git(&root, &["add", "."]);
+        fs::write(root.join("lib.rs"), "pub fn first() -> u32 {\n    1\n}\n\npub fn staged() -> u32 {\n    2\n}\n").unwrap();
+        git(&root, &["add", "."]);
+        let diff = git(&root, &["diff", "--staged"]);
+        // 未暂存的修改使工作区中的行号整体下移
+        fs::write(
+            root.join("lib.rs"),
+            "pub fn unstaged() -> u32 {\n    0\n}\n\npub fn first() -> u32 {\n    1\n}\n\npub fn staged() -> u32 {\n    2\n}\n",
+        )
+        .unwrap();
+
+        let config = AppConfig {
+            ai: Default::default(),
+            tree_sitter: TreeSitterConfig::default(),
+            commit: Default::default(),
+            prompts: Default::default(),
+        };
+        let git_diff = crate::tree_sitter_analyzer::parse_utils::parse_git_diff_text(&diff).unwrap();
+        let context = build_code_context_for_review(&config, root, &ReviewRange::WorkingTree, &git_diff);
+        let names: Vec<&str> = context.snippets.iter().filter(|s| !s.referenced).map(|s| s.name.as_str()).collect();
+        assert!(names.contains(&"staged") && !names.contains(&"unstaged"), "{:?}", names);
+    }
+}
@@ -0,0 +1,137 @@
+//! 专项评审角色
+//!
+//! `--focus security,performance` 选择一个或多个专项评审角色，每个角色以自己的关注点
+//! 对同一 diff 单独进行一轮 AI 评审，最后由一轮合并评审去除重复问题并按严重程度排序。
+
+/// 专项评审角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewPersona {
+    /// 安全评审
+    Security,
+    /// 性能评审
+    Performance,
+    /// API 兼容性评审
+    ApiCompatibility,
+    /// 测试覆盖评审
+    TestCoverage,
+}
+
+impl ReviewPersona {
+    /// 全部评审角色，按报告中的顺序排列
+    pub const ALL: [ReviewPersona; 4] = [
+        ReviewPersona::Security,
+        ReviewPersona::Performance,
+        ReviewPersona::ApiCompatibility,
+        ReviewPersona::TestCoverage,
+    ];
+
+    /// 从 `--focus` 中的名称解析角色
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "security" | "sec" => Some(ReviewPersona::Security),
+            "performance" | "perf" => Some(ReviewPersona::Performance),
+            "api" | "api-compatibility" | "compatibility" => Some(ReviewPersona::ApiCompatibility),
+            "tests" | "test" | "test-coverage" | "coverage" => Some(ReviewPersona::TestCoverage),
+            _ => None,
+        }
+    }
+
+    /// 角色在报告中的显示名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ReviewPersona::Security => "安全",
+            ReviewPersona::Performance => "性能",
+            ReviewPersona::ApiCompatibility => "API 兼容性",
+            ReviewPersona::TestCoverage => "测试覆盖",
+        }
+    }
+
+    /// 角色本轮评审的关注点
+    pub fn focus_instruction(&self) -> &'static str {
+        match self {
+            ReviewPersona::Security => "您是安全评审者，只报告安全问题：注入、认证与授权缺陷、敏感信息泄露、\
+                硬编码凭证、不安全的反序列化、路径遍历、不安全的加密用法以及依赖中的已知漏洞。",
+            ReviewPersona::Performance => "您是性能评审者，只报告性能问题：算法复杂度退化、循环中的重复计算或 I/O、\
+                不必要的内存分配与拷贝、锁竞争、阻塞异步运行时以及缺少缓存或批处理的热点路径。",
+            ReviewPersona::ApiCompatibility => "您是 API 兼容性评审者，只报告兼容性问题：公共函数、类型、字段或配置的\
+                删除与签名变更、默认值和行为变化、序列化格式变化，以及调用方需要随之修改的地方。",
+            ReviewPersona::TestCoverage => "您是测试覆盖评审者，只报告测试问题：新增或修改的逻辑缺少测试、\
+                未覆盖的边界条件和错误路径、被删除或放宽的断言，以及不稳定或依赖外部环境的测试。",
+        }
+    }
+}
+
+/// 解析 `--focus` 选择的评审角色
+///
+/// `all` 选择全部角色。包含无法识别的名称时视为自由文本的关注点，返回空列表。
+pub fn parse_focus_personas(focus: Option<&str>) -> Vec<ReviewPersona> {
+    let Some(focus) = focus else {
+        return Vec::new();
+    };
+    let names: Vec<&str> = focus.split(',').map(|n| n.trim()).filter(|n| !n.is_empty()).collect();
+    if names.iter().any(|n| n.eq_ignore_ascii_case("all")) {
+        return ReviewPersona::ALL.to_vec();
+    }
+
+    let mut personas = Vec::new();
+    for name in names {
+        match ReviewPersona::from_name(name) {
+            Some(persona) if !personas.contains(&persona) => personas.push(persona),
+            Some(_) => {}
+            None => {
+                tracing::debug!("--focus 包含未知的评审角色 '{}'，作为自由文本关注点处理", name);
+                return Vec::new();
+            }
+        }
+    }
+    personas
+}
+
+/// 构建合并各角色评审结果的提示
+pub fn build_merge_prompt(reports: &[(ReviewPersona, String)]) -> String {
+    let mut prompt = String::from(
+        "以下是多位专项评审者对同一代码变更分别给出的评审报告。请将它们合并为一份评审报告：\n\
+        1. 去除重复的问题：位置和原因相同的问题只保留一次，并注明提出该问题的评审者\n\
+        2. 按严重程度从高到低排序所有问题，严重程度相同时按影响范围排序\n\
+        3. 保留每个问题的位置、描述和改进建议，不要添加报告中没有的问题\n\
+        4. 最后给出最重要的 1-3 个需要关注的点\n",
+    );
+    for (persona, report) in reports {
+        prompt.push_str(&format!("\n## {}评审报告\n\n{}\n", persona.display_name(), report.trim()));
+    }
+    prompt
+}
+
+/// 合并评审失败时，按角色顺序拼接各评审报告
+pub fn concat_persona_reports(reports: &[(ReviewPersona, String)]) -> String {
+    let mut text = String::from("# 代码评审结果\n");
+    for (persona, report) in reports {
+        text.push_str(&format!("\n## {}评审\n\n{}\n", persona.display_name(), report.trim()));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_focus_personas() {
+        assert_eq!(
+            parse_focus_personas(Some("security, perf,security")),
+            vec![ReviewPersona::Security, ReviewPersona::Performance]
+        );
+        assert_eq!(parse_focus_personas(Some("all")), ReviewPersona::ALL.to_vec());
+        assert!(parse_focus_personas(Some("性能问题")).is_empty());
+        assert!(parse_focus_personas(Some("security,性能问题")).is_empty());
+        assert!(parse_focus_personas(None).is_empty());
+
+        let reports = vec![
+            (ReviewPersona::Security, "硬编码密码".to_string()),
+            (ReviewPersona::TestCoverage, "缺少测试".to_string()),
+        ];
+        let prompt = build_merge_prompt(&reports);
+        assert!(prompt.contains("## 安全评审报告\n\n硬编码密码"));
+        assert!(prompt.contains("## 测试覆盖评审报告\n\n缺少测试"));
+    }
+}
@@ -0,0 +1,184 @@
+//! 按语言划分的评审提示
+//!
+//! 将待评审的 diff 按文件语言分组，每组使用 `assets/review-<lang>-prompt.md` 中对应语言的
+//! 安全评审规范作为系统提示。用户可以在 `~/.config/gitie` 中放置同名文件覆盖内置提示。
+
+use crate::{
+    ai_module::prompt_handler::load_prompt_file, config_management::settings::AppConfig,
+    tree_sitter_analyzer::simple_diff::detect_language_from_path,
+};
+use std::path::PathBuf;
+
+/// 通用的评审系统提示
+pub const BASE_REVIEW_SYSTEM_PROMPT: &str = "您是一位经验丰富的代码评审专家，精通多种编程语言和软件开发最佳实践。";
+
+/// 没有对应语言提示的文件所属的分组
+pub const OTHER_LANGUAGE: &str = "other";
+
+/// 语言评审提示：(分组语言, 提示文件名, 内置内容)
+const LANGUAGE_PROMPTS: &[(&str, &str, &str)] = &[
+    ("rust", "review-rust-prompt.md", include_str!("../../assets/review-rust-prompt.md")),
+    ("java", "review-java-prompt.md", include_str!("../../assets/review-java-prompt.md")),
+    ("python", "review-python-prompt.md", include_str!("../../assets/review-python-prompt.md")),
+    ("go", "review-go-prompt.md", include_str!("../../assets/review-go-prompt.md")),
+    ("javascript", "review-js-prompt.md", include_str!("../../assets/review-js-prompt.md")),
+    ("c", "review-c-prompt.md", include_str!("../../assets/review-c-prompt.md")),
+    ("cpp", "review-cpp-prompt.md", include_str!("../../assets/review-cpp-prompt.md")),
+];
+
+/// 同一语言的一组待评审文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanguageGroup {
+    /// 分组语言，没有对应提示时为 `other`
+    pub language: String,
+    /// 组内文件路径
+    pub paths: Vec<PathBuf>,
+    /// 组内文件的 diff
+    pub diff: String,
+}
+
+impl LanguageGroup {
+    /// 分组的显示名称
+    pub fn display_name(&self) -> &'static str {
+        match self.language.as_str() {
+            "rust" => "Rust",
+            "java" => "Java",
+            "python" => "Python",
+            "go" => "Go",
+            "javascript" => "JavaScript/TypeScript",
+            "c" => "C",
+            "cpp" => "C++",
+            _ => "其他文件",
+        }
+    }
+}
+
+/// 将文件路径映射到评审提示的分组语言
+pub fn review_language_for_path(path: &PathBuf) -> String {
+    match detect_language_from_path(path).as_deref() {
+        Some("typescript") | Some("javascript") => "javascript".to_string(),
+        Some(language) if LANGUAGE_PROMPTS.iter().any(|(l, _, _)| *l == language) => language.to_string(),
+        _ => OTHER_LANGUAGE.to_string(),
+    }
+}
+
+/// 将统一 diff 按文件拆分，返回 (文件路径, 该文件的 diff)
+pub fn split_diff_by_file(diff_text: &str) -> Vec<(PathBuf, String)> {
+    let mut files: Vec<(PathBuf, String)> = Vec::new();
+    let mut old_path: Option<String> = None;
+
+    for line in diff_text.split_inclusive('\n') {
+        if line.starts_with("diff --git ") {
+            // 先用 b/ 路径占位，之后由 +++ 行修正
+            let path = line
+                .trim_end()
+                .rsplit_once(" b/")
+                .map(|(_, p)| p.to_string())
+                .unwrap_or_default();
+            files.push((PathBuf::from(path), String::new()));
+            old_path = None;
+        } else if let Some(path) = line.strip_prefix("--- a/") {
+            old_path = Some(path.trim_end().to_string());
+        } else if line.starts_with("+++ ") && files.last().is_some_and(|(_, d)| !d.contains("\n@@")) {
+            let new_path = line.trim_end().strip_prefix("+++ b/").map(|p| p.to_string());
+            if let (Some((path, _)), Some(p)) = (files.last_mut(), new_path.or(old_path.clone())) {
+                *path = PathBuf::from(p);
+            }
+        }
+        if let Some((_, diff)) = files.last_mut() {
+            diff.push_str(line);
+        }
+    }
+
+    files
+}
+
+/// 按语言将 diff 分组，保持各语言首次出现的顺序
+pub fn group_diff_by_language(diff_text: &str) -> Vec<LanguageGroup> {
+    let mut groups: Vec<LanguageGroup> = Vec::new();
+    for (path, diff) in split_diff_by_file(diff_text) {
+        let language = review_language_for_path(&path);
+        match groups.iter_mut().find(|g| g.language == language) {
+            Some(group) => {
+                group.paths.push(path);
+                group.diff.push_str(&diff);
+            }
+            None => groups.push(LanguageGroup { language, paths: vec![path], diff }),
+        }
+    }
+    groups
+}
+
+/// 加载语言对应的评审提示，优先使用用户目录中的覆盖版本
+pub fn load_language_review_prompt(language: &str) -> Option<String> {
+    let (_, file_name, default) = LANGUAGE_PROMPTS.iter().find(|(l, _, _)| *l == language)?;
+    let dirs: Vec<String> = AppConfig::get_user_config_dir()
+        .map(|dir| vec![dir.to_string_lossy().to_string()])
+        .unwrap_or_default();
+    let prompt = load_prompt_file(file_name, &dirs).unwrap_or_else(|e| {
+        tracing::debug!("{}，使用内置的 {} 评审提示", e, language);
+        default.to_string()
+    });
+    Some(prompt)
+}
+
+/// 构建语言分组的评审系统提示
+pub fn review_system_prompt(language: &str) -> String {
+    match load_language_review_prompt(language) {
+        Some(prompt) => format!(
+            "{}\n\n请依据以下评审规范评审代码变更：\n\n{}",
+            BASE_REVIEW_SYSTEM_PROMPT, prompt
+        ),
+        None => BASE_REVIEW_SYSTEM_PROMPT.to_string(),
+    }
+}
+
+/// 合并各语言分组的评审结果，只有一个分组时原样返回
+pub fn merge_language_reviews(reviews: &[(LanguageGroup, String)]) -> String {
+    if let [(_, review)] = reviews {
+        return review.clone();
+    }
+
+    let mut merged = String::from("# 代码评审结果\n");
+    for (group, review) in reviews {
+        merged.push_str(&format!(
+            "\n## {} ({} 个文件)\n\n{}\n",
+            group.display_name(),
+            group.paths.len(),
+            review.trim()
+        ));
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/src/main.rs b/src/main.rs\n--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1 +1 @@\n-a\n+b\ndiff --git a/web/app.ts b/web/app.ts\nnew file mode 100644\n--- /dev/null\n+++ b/web/app.ts\n@@ -0,0 +1 @@\n+let x = 1;\ndiff --git a/src/lib.rs b/src/lib.rs\ndeleted file mode 100644\n--- a/src/lib.rs\n+++ /dev/null\n@@ -1 +0,0 @@\n-old\ndiff --git a/README.md b/README.md\n--- a/README.md\n+++ b/README.md\n@@ -1 +1 @@\n-x\n+y\n";
+
+    #[test]
+    fn test_group_diff_by_language() {
+        let groups = group_diff_by_language(DIFF);
+        let summary: Vec<(&str, usize)> = groups.iter().map(|g| (g.language.as_str(), g.paths.len())).collect();
+        assert_eq!(summary, vec![("rust", 2), ("javascript", 1), (OTHER_LANGUAGE, 1)]);
+        assert_eq!(groups[0].paths, vec![PathBuf::from("src/main.rs"), PathBuf::from("src/lib.rs")]);
+        assert!(groups[0].diff.contains("+++ /dev/null"));
+        assert!(!groups[0].diff.contains("app.ts"));
+        assert_eq!(groups.iter().map(|g| g.diff.len()).sum::<usize>(), DIFF.len());
+    }
+
+    #[test]
+    fn test_language_prompts_and_merge() {
+        assert!(load_language_review_prompt("rust").is_some_and(|p| p.contains("Rust")));
+        assert!(load_language_review_prompt(OTHER_LANGUAGE).is_none());
+
+        let groups = group_diff_by_language(DIFF);
+        let reviews: Vec<(LanguageGroup, String)> =
+            groups.into_iter().map(|g| (g, "没有问题".to_string())).collect();
+        let merged = merge_language_reviews(&reviews);
+        assert!(merged.contains("## Rust (2 个文件)"));
+        assert!(merged.contains("## JavaScript/TypeScript (1 个文件)"));
+        assert_eq!(merge_language_reviews(&reviews[..1]), "没有问题");
+    }
+}
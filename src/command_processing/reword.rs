@@ -0,0 +1,244 @@
+//! 提交消息改写模块
+//!
+//! 使用 AI 根据提交的实际变更重写已有提交的提交消息。
+//! 改写通过脚本化的变基完成：先用 `git commit-tree` 生成消息已更新的替代提交，
+//! 再将其后的提交通过 `git rebase --onto` 重放到替代提交之上。
+
+use crate::{
+    cli_interface::{args::RewordArgs, ui::confirm},
+    command_processing::commit::{
+        EMPTY_TREE_HASH, append_previous_message_context, cleanup_commit_message_file,
+        create_commit_message_file, generate_ai_commit_message, get_commit_message,
+    },
+    command_processing::style::append_style_context,
+    config_management::settings::AppConfig,
+    core::errors::{AppError, GitError},
+    git_module::{execute_git_command_and_capture_output, get_repository_root, passthrough_to_git},
+};
+use colored::Colorize;
+use std::path::Path;
+use std::process::Command;
+
+/// 在仓库根目录中执行 git 命令的参数
+fn git_args_in(root: &Path, args: &[&str]) -> Vec<String> {
+    let mut owned = vec!["-C".to_string(), root.display().to_string()];
+    owned.extend(args.iter().map(|a| a.to_string()));
+    owned
+}
+
+/// 在仓库根目录中执行 git 命令并返回标准输出，失败时返回 CommandFailed 错误
+fn run_git(root: &Path, args: &[&str]) -> Result<String, AppError> {
+    let owned = git_args_in(root, args);
+    let output = execute_git_command_and_capture_output(&owned)?;
+    if !output.is_success() {
+        return Err(AppError::Git(GitError::CommandFailed {
+            command: format!("git {}", args.join(" ")),
+            status_code: output.exit_code(),
+            stdout: output.stdout,
+            stderr: output.stderr,
+        }));
+    }
+    Ok(output.stdout.trim().to_string())
+}
+
+/// 提交的作者信息，用于在生成替代提交时保留原作者和时间
+struct AuthorInfo {
+    name: String,
+    email: String,
+    date: String,
+}
+
+/// 读取提交的作者信息
+fn get_author_info(root: &Path, commit: &str) -> Result<AuthorInfo, AppError> {
+    let output = run_git(root, &["log", "-1", "--date=raw", "--format=%an%n%ae%n%ad", commit])?;
+    let mut lines = output.lines();
+    Ok(AuthorInfo {
+        name: lines.next().unwrap_or_default().to_string(),
+        email: lines.next().unwrap_or_default().to_string(),
+        date: lines.next().unwrap_or_default().to_string(),
+    })
+}
+
+/// 获取提交相对于其第一个父提交的差异，根提交与空树比较
+fn get_commit_diff(root: &Path, commit: &str, parents: &[String]) -> Result<String, AppError> {
+    let base = parents.first().map_or(EMPTY_TREE_HASH, String::as_str);
+    let owned = git_args_in(root, &["diff", "--no-color", base, commit]);
+    let output = execute_git_command_and_capture_output(&owned)?;
+    if !output.is_success() {
+        return Err(AppError::Git(GitError::CommandFailed {
+            command: owned.join(" "),
+            status_code: output.exit_code(),
+            stdout: output.stdout,
+            stderr: output.stderr,
+        }));
+    }
+    Ok(output.stdout)
+}
+
+/// 生成与原提交树、父提交和作者一致，仅提交消息不同的替代提交
+fn create_replacement_commit(
+    root: &Path,
+    commit: &str,
+    parents: &[String],
+    message: &str,
+) -> Result<String, AppError> {
+    let author = get_author_info(root, commit)?;
+    let tree = format!("{}^{{tree}}", commit);
+    let message_file = create_commit_message_file(message)?;
+
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(root).arg("commit-tree").arg(&tree);
+    for parent in parents {
+        cmd.arg("-p").arg(parent);
+    }
+    cmd.arg("-F")
+        .arg(&message_file)
+        .env("GIT_AUTHOR_NAME", &author.name)
+        .env("GIT_AUTHOR_EMAIL", &author.email)
+        .env("GIT_AUTHOR_DATE", &author.date);
+
+    let result = cmd.output();
+    cleanup_commit_message_file(&message_file);
+    let output = result.map_err(|e| AppError::IO("无法执行 git commit-tree".to_string(), e))?;
+    if !output.status.success() {
+        return Err(AppError::Git(crate::git_module::map_output_to_git_command_error(
+            "git commit-tree",
+            output,
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// 解析要改写的提交及其父提交，只允许改写当前分支历史中的提交
+fn resolve_reword_target(root: &Path, rev: &str) -> Result<(String, Vec<String>), AppError> {
+    let commit = run_git(root, &["rev-parse", "--verify", &format!("{}^{{commit}}", rev)])
+        .map_err(|_| AppError::Generic(format!("无法解析提交引用: {}", rev)))?;
+
+    if run_git(root, &["merge-base", "--is-ancestor", &commit, "HEAD"]).is_err() {
+        return Err(AppError::Generic(format!(
+            "提交 {} 不在当前分支的历史中，无法改写",
+            rev
+        )));
+    }
+
+    let parents: Vec<String> = run_git(root, &["rev-list", "--parents", "-n", "1", &commit])?
+        .split_whitespace()
+        .skip(1)
+        .map(|s| s.to_string())
+        .collect();
+
+    Ok((commit, parents))
+}
+
+/// 将提交的消息改写为 `message`
+///
+/// 生成树、父提交和作者都与原提交相同的替代提交，再将原提交之后的历史重放到替代提交之上。
+fn reword_commit(root: &Path, commit: &str, parents: &[String], message: &str) -> Result<(), AppError> {
+    let replacement = create_replacement_commit(root, commit, parents, message)?;
+    tracing::debug!("替代提交: {} -> {}", commit, replacement);
+
+    let rebase_args = git_args_in(
+        root,
+        &["rebase", "--quiet", "--rebase-merges", "--autostash", "--onto", &replacement, commit],
+    );
+    tracing::debug!("执行 Git 命令: git {}", rebase_args.join(" "));
+    passthrough_to_git(&rebase_args)
+}
+
+/// Handles the `reword` subcommand
+///
+/// # Arguments
+///
+/// * `args` - Reword arguments from CLI
+/// * `config` - Application configuration
+///
+/// # Returns
+///
+/// * `Result<(), AppError>` - Success or an error
+pub async fn handle_reword(args: RewordArgs, config: &AppConfig) -> Result<(), AppError> {
+    tracing::info!("改写提交消息: {}", args.rev);
+
+    let root = get_repository_root()?;
+    let (commit, parents) = resolve_reword_target(&root, &args.rev)?;
+
+    let previous_message = get_commit_message(&commit)?;
+    let diff = get_commit_diff(&root, &commit, &parents)?;
+    if diff.trim().is_empty() {
+        tracing::warn!("提交 {} 没有内容变更，仅依据原提交消息改写", args.rev);
+    }
+
+    let user_prompt = append_previous_message_context(
+        format!("Git diff:\n{}\nGenerate commit message.", diff.trim()),
+        &previous_message,
+    );
+    let user_prompt = append_style_context(user_prompt, &config.commit);
+    let new_message = generate_ai_commit_message(config, user_prompt).await?;
+    tracing::info!("AI 消息:\n---\n{}\n---", new_message);
+
+    println!("{}", "原提交消息:".bold());
+    println!("{}\n", previous_message);
+    println!("{}", "新提交消息:".bold().green());
+    println!("{}", new_message);
+
+    if !args.yes && !confirm("是否使用新的提交消息改写该提交？")? {
+        println!("已取消改写。");
+        return Ok(());
+    }
+
+    reword_commit(&root, &commit, &parents, &new_message)?;
+
+    println!("{}", format!("已改写提交 {} 的提交消息。", args.rev).green());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git(root: &Path, args: &[&str]) -> String {
+        let output = Command::new("git").arg("-C").arg(root).args(args).output().unwrap();
+        assert!(output.status.success(), "git {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[test]
+    fn test_reword_commit_keeps_everything_but_the_message() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        git(root, &["init", "-q"]);
+        git(root, &["config", "user.name", "Committer"]);
+        git(root, &["config", "user.email", "committer@example.com"]);
+        git(root, &["config", "commit.gpgsign", "false"]);
+        for (i, date) in ["1700000000 +0800", "1700001000 +0800", "1700002000 +0800"].iter().enumerate() {
+            std::fs::write(root.join(format!("file{}.txt", i)), format!("{}\n", i)).unwrap();
+            git(root, &["add", "."]);
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(root)
+                .args(["commit", "-q", "-m", &format!("commit {}", i)])
+                .env("GIT_AUTHOR_NAME", "Original Author")
+                .env("GIT_AUTHOR_EMAIL", "author@example.com")
+                .env("GIT_AUTHOR_DATE", date)
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+        }
+        let format = "%an <%ae> %ad%n%T";
+        let before_target = git(root, &["log", "-1", "--date=raw", &format!("--format={}", format), "HEAD~1"]);
+        let before_head = git(root, &["log", "-1", "--date=raw", &format!("--format={}%n%B", format), "HEAD"]);
+        let root_commit = git(root, &["rev-parse", "HEAD~2"]);
+
+        let (commit, parents) = resolve_reword_target(root, "HEAD~1").unwrap();
+        assert_eq!(parents, vec![root_commit.clone()]);
+        reword_commit(root, &commit, &parents, "docs: 改写后的消息").unwrap();
+
+        assert_eq!(git(root, &["log", "-1", "--format=%B", "HEAD~1"]), "docs: 改写后的消息");
+        assert_eq!(git(root, &["log", "-1", "--date=raw", &format!("--format={}", format), "HEAD~1"]), before_target);
+        assert_ne!(git(root, &["rev-parse", "HEAD~1"]), commit);
+        // 后续提交被重放到替代提交之上，内容、作者和消息不变；之前的提交不受影响
+        assert_eq!(git(root, &["log", "-1", "--date=raw", &format!("--format={}%n%B", format), "HEAD"]), before_head);
+        assert_eq!(git(root, &["rev-parse", "HEAD~2"]), root_commit);
+        assert_eq!(git(root, &["rev-list", "--count", "HEAD"]), "3");
+        assert!(git(root, &["status", "--porcelain"]).is_empty());
+    }
+}
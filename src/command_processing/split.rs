@@ -0,0 +1,766 @@
+//! 暂存变更拆分模块
+//!
+//! 将一次性暂存的大量变更按 AI 建议的分组拆分为多个逻辑内聚的提交。
+//! 变更以 hunk 为最小单位，通过 `git apply --cached` 按组重新暂存后依次提交。
+
+use crate::{
+    ai_module::prompt_handler::send_prompt_and_get_response,
+    cli_interface::{args::CommitArgs, ui::confirm},
+    command_processing::commit::{
+        cleanup_commit_message_file, commit_trailer_args, create_commit_message_file,
+        filter_passthrough_args,
+    },
+    command_processing::commit_options::parse_commit_args,
+    command_processing::issues::{append_issue_context, current_issue_keys, enforce_issue_footer},
+    command_processing::review::{FileVersionSource, read_file_version},
+    command_processing::style::append_style_context,
+    config_management::settings::AppConfig,
+    core::errors::{AppError, GitError},
+    git_module::execute_git_command_and_capture_output,
+    tree_sitter_analyzer::{TreeSitterAnalyzer, core::DiffAnalysis},
+};
+use colored::Colorize;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// 暂存补丁中的单个 hunk
+#[derive(Debug, Clone)]
+pub struct PatchHunk {
+    /// hunk 头部，例如 `@@ -1,3 +1,4 @@ fn main()`
+    pub header: String,
+    /// hunk 内容行，保留 `+`/`-`/空格前缀以及 `\ No newline at end of file`
+    pub lines: Vec<String>,
+}
+
+/// 暂存补丁中的单个文件
+#[derive(Debug, Clone)]
+pub struct PatchFile {
+    /// 文件路径（相对于仓库根目录）
+    pub path: String,
+    /// 文件头部，即 `diff --git` 到第一个 hunk 之前的所有行
+    pub header: Vec<String>,
+    /// 文件中的 hunk 列表
+    pub hunks: Vec<PatchHunk>,
+}
+
+impl PatchFile {
+    /// 判断文件是否必须作为整体提交
+    ///
+    /// 新增、删除、重命名、模式变更和二进制文件无法按 hunk 拆分
+    pub fn is_atomic(&self) -> bool {
+        self.hunks.is_empty()
+            || self.header.iter().any(|line| {
+                line.starts_with("new file mode")
+                    || line.starts_with("deleted file mode")
+                    || line.starts_with("rename from")
+                    || line.starts_with("copy from")
+                    || line.starts_with("old mode")
+                    || line.starts_with("GIT binary patch")
+                    || line.starts_with("Binary files")
+            })
+    }
+}
+
+/// 拆分的最小单位：一个 hunk，或者一个必须整体提交的文件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitUnit {
+    /// 所属文件在补丁中的索引
+    pub file_index: usize,
+    /// hunk 索引，`None` 表示整个文件
+    pub hunk_index: Option<usize>,
+}
+
+/// 一个待执行的提交分组
+#[derive(Debug, Clone)]
+pub struct CommitGroup {
+    /// 提交消息
+    pub message: String,
+    /// 该提交包含的拆分单位索引（按补丁顺序排列）
+    pub units: Vec<usize>,
+}
+
+/// AI 返回的拆分计划
+#[derive(Debug, Deserialize)]
+struct SplitPlanResponse {
+    commits: Vec<SplitPlanEntry>,
+}
+
+/// AI 拆分计划中的单个提交
+#[derive(Debug, Deserialize)]
+struct SplitPlanEntry {
+    message: String,
+    #[serde(default)]
+    hunks: Vec<String>,
+}
+
+/// 解析 `git diff --staged --binary` 的输出
+///
+/// # Arguments
+///
+/// * `diff_text` - 暂存区的补丁文本
+///
+/// # Returns
+///
+/// * `Vec<PatchFile>` - 按补丁顺序排列的文件列表
+pub fn parse_staged_patch(diff_text: &str) -> Vec<PatchFile> {
+    let mut files: Vec<PatchFile> = Vec::new();
+
+    for line in diff_text.lines() {
+        if line.starts_with("diff --git ") {
+            // 从 "diff --git a/x b/y" 中提取新路径，稍后由 "+++ b/" 行修正
+            let path = line
+                .rfind(" b/")
+                .map(|pos| line[pos + 3..].to_string())
+                .unwrap_or_default();
+            files.push(PatchFile {
+                path,
+                header: vec![line.to_string()],
+                hunks: Vec::new(),
+            });
+            continue;
+        }
+
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+
+        if line.starts_with("@@ ") {
+            file.hunks.push(PatchHunk {
+                header: line.to_string(),
+                lines: Vec::new(),
+            });
+        } else if let Some(hunk) = file.hunks.last_mut() {
+            hunk.lines.push(line.to_string());
+        } else {
+            if let Some(path) = line.strip_prefix("+++ b/") {
+                file.path = path.to_string();
+            }
+            file.header.push(line.to_string());
+        }
+    }
+
+    files
+}
+
+/// 将补丁文件拆分为最小拆分单位
+pub fn collect_split_units(files: &[PatchFile]) -> Vec<SplitUnit> {
+    let mut units = Vec::new();
+    for (file_index, file) in files.iter().enumerate() {
+        if file.is_atomic() {
+            units.push(SplitUnit { file_index, hunk_index: None });
+        } else {
+            for hunk_index in 0..file.hunks.len() {
+                units.push(SplitUnit { file_index, hunk_index: Some(hunk_index) });
+            }
+        }
+    }
+    units
+}
+
+/// 根据选中的拆分单位重新组装补丁
+///
+/// # Arguments
+///
+/// * `files` - 完整的补丁文件列表
+/// * `units` - 所有拆分单位
+/// * `selected` - 选中的拆分单位索引
+///
+/// # Returns
+///
+/// * `String` - 可供 `git apply --cached` 使用的补丁文本
+pub fn build_patch(files: &[PatchFile], units: &[SplitUnit], selected: &[usize]) -> String {
+    let mut patch = String::new();
+
+    for (file_index, file) in files.iter().enumerate() {
+        let chosen: Vec<&SplitUnit> = selected
+            .iter()
+            .filter_map(|i| units.get(*i))
+            .filter(|u| u.file_index == file_index)
+            .collect();
+        if chosen.is_empty() {
+            continue;
+        }
+
+        for line in &file.header {
+            patch.push_str(line);
+            patch.push('\n');
+        }
+
+        let whole_file = chosen.iter().any(|u| u.hunk_index.is_none());
+        for (hunk_index, hunk) in file.hunks.iter().enumerate() {
+            if whole_file || chosen.iter().any(|u| u.hunk_index == Some(hunk_index)) {
+                patch.push_str(&hunk.header);
+                patch.push('\n');
+                for line in &hunk.lines {
+                    patch.push_str(line);
+                    patch.push('\n');
+                }
+            }
+        }
+    }
+
+    patch
+}
+
+/// 拆分单位的显示标签，例如 `H3 src/main.rs @@ -1,3 +1,4 @@`
+fn unit_label(files: &[PatchFile], units: &[SplitUnit], index: usize) -> String {
+    let unit = &units[index];
+    let file = &files[unit.file_index];
+    match unit.hunk_index {
+        Some(h) => format!("H{} {} {}", index + 1, file.path, file.hunks[h].header),
+        None => format!("H{} {} (整个文件)", index + 1, file.path),
+    }
+}
+
+/// 从 AI 响应中解析拆分计划并规范化
+///
+/// 无效或重复的 hunk 编号会被忽略，未被分配的拆分单位会并入最后一个提交
+pub fn parse_split_plan(response: &str, unit_count: usize) -> Result<Vec<CommitGroup>, AppError> {
+    let start = response.find('{');
+    let end = response.rfind('}');
+    let json_text = match (start, end) {
+        (Some(s), Some(e)) if s < e => &response[s..=e],
+        _ => {
+            return Err(AppError::Generic(
+                "AI 返回的拆分计划不是有效的 JSON".to_string(),
+            ));
+        }
+    };
+
+    let plan: SplitPlanResponse = serde_json::from_str(json_text)
+        .map_err(|e| AppError::Generic(format!("无法解析 AI 返回的拆分计划: {}", e)))?;
+
+    let mut assigned: HashMap<usize, usize> = HashMap::new();
+    let mut groups: Vec<CommitGroup> = Vec::new();
+
+    for entry in plan.commits {
+        let message = entry.message.trim().to_string();
+        if message.is_empty() {
+            continue;
+        }
+        let mut group_units = Vec::new();
+        for id in &entry.hunks {
+            let index = id
+                .trim()
+                .trim_start_matches(['H', 'h'])
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1));
+            match index {
+                Some(i) if i < unit_count && !assigned.contains_key(&i) => {
+                    assigned.insert(i, groups.len());
+                    group_units.push(i);
+                }
+                _ => tracing::warn!("忽略无效或重复的 hunk 编号: {}", id),
+            }
+        }
+        if !group_units.is_empty() {
+            groups.push(CommitGroup { message, units: group_units });
+        }
+    }
+
+    if groups.is_empty() {
+        return Err(AppError::Generic("AI 返回的拆分计划不包含任何提交".to_string()));
+    }
+
+    let leftovers: Vec<usize> = (0..unit_count).filter(|i| !assigned.contains_key(i)).collect();
+    if !leftovers.is_empty() {
+        tracing::warn!("{} 个 hunk 未被分配，将并入最后一个提交", leftovers.len());
+        if let Some(last) = groups.last_mut() {
+            last.units.extend(leftovers);
+        }
+    }
+
+    for group in &mut groups {
+        group.units.sort_unstable();
+    }
+
+    Ok(groups)
+}
+
+/// 构建发送给 AI 的拆分请求
+fn build_split_prompt(
+    files: &[PatchFile],
+    units: &[SplitUnit],
+    analysis: Option<&DiffAnalysis>,
+) -> String {
+    let mut prompt = String::new();
+    prompt.push_str("以下是暂存区中的全部变更块（hunk），每个变更块都有唯一编号：\n\n");
+
+    for index in 0..units.len() {
+        let unit = &units[index];
+        let file = &files[unit.file_index];
+        prompt.push_str(&format!("### {}\n```diff\n", unit_label(files, units, index)));
+        match unit.hunk_index {
+            Some(h) => {
+                for line in &file.hunks[h].lines {
+                    prompt.push_str(line);
+                    prompt.push('\n');
+                }
+            }
+            None => {
+                for hunk in &file.hunks {
+                    for line in &hunk.lines {
+                        prompt.push_str(line);
+                        prompt.push('\n');
+                    }
+                }
+            }
+        }
+        prompt.push_str("```\n\n");
+    }
+
+    if let Some(analysis) = analysis {
+        prompt.push_str("## 结构分析\n\n");
+        for file_analysis in &analysis.file_analyses {
+            if file_analysis.affected_nodes.is_empty() {
+                continue;
+            }
+            prompt.push_str(&format!("- {}:", file_analysis.path.display()));
+            for node in &file_analysis.affected_nodes {
+                prompt.push_str(&format!(" {} `{}`;", node.node_type, node.name));
+            }
+            prompt.push('\n');
+        }
+        prompt.push('\n');
+    }
+
+    prompt.push_str(
+        "请将这些变更块划分为若干个逻辑内聚、可以独立审查的提交（例如将重构与新功能分开），\
+        并为每个提交撰写符合规范的提交消息。每个变更块必须且只能出现在一个提交中，\
+        提交按应当执行的先后顺序排列。\n\
+        只输出如下格式的 JSON，不要输出其他内容：\n\
+        {\"commits\": [{\"message\": \"提交消息\", \"hunks\": [\"H1\", \"H2\"]}]}",
+    );
+
+    prompt
+}
+
+/// 获取仓库根目录
+fn get_repository_root() -> Result<String, AppError> {
+    let output = execute_git_command_and_capture_output(&[
+        "rev-parse".to_string(),
+        "--show-toplevel".to_string(),
+    ])?;
+    if !output.is_success() {
+        return Err(AppError::Git(GitError::NotARepository));
+    }
+    Ok(output.stdout.trim().to_string())
+}
+
+/// 在仓库根目录执行 git 命令，失败时返回 CommandFailed 错误
+fn run_git_in_root(root: &str, args: &[&str]) -> Result<String, AppError> {
+    let mut full_args = vec!["-C".to_string(), root.to_string()];
+    full_args.extend(args.iter().map(|a| a.to_string()));
+    let output = execute_git_command_and_capture_output(&full_args)?;
+    if !output.is_success() {
+        return Err(AppError::Git(GitError::CommandFailed {
+            command: format!("git {}", args.join(" ")),
+            status_code: output.exit_code(),
+            stdout: output.stdout,
+            stderr: output.stderr,
+        }));
+    }
+    Ok(output.stdout)
+}
+
+/// 将补丁写入临时文件并应用到暂存区
+fn apply_patch_to_index(root: &str, patch: &str, name: &str) -> Result<(), AppError> {
+    let patch_path: PathBuf =
+        std::env::temp_dir().join(format!("gitie_split_{}_{}.patch", std::process::id(), name));
+    std::fs::write(&patch_path, patch)
+        .map_err(|e| AppError::IO("无法创建临时补丁文件".to_string(), e))?;
+    let patch_arg = patch_path.to_string_lossy().to_string();
+    let result = run_git_in_root(root, &["apply", "--cached", "--recount", &patch_arg]);
+    if let Err(e) = std::fs::remove_file(&patch_path) {
+        tracing::warn!("无法删除临时补丁文件: {}", e);
+    }
+    result.map(|_| ())
+}
+
+/// 清空暂存区并重新暂存指定的拆分单位，用于出错时恢复
+fn restore_units(root: &str, files: &[PatchFile], units: &[SplitUnit], remaining: &[usize]) {
+    tracing::warn!("拆分提交中断，正在恢复剩余的暂存变更");
+    let patch = build_patch(files, units, remaining);
+    let restored = run_git_in_root(root, &["reset", "-q"])
+        .and_then(|_| apply_patch_to_index(root, &patch, "restore"));
+    if let Err(e) = restored {
+        tracing::error!("恢复暂存区失败: {}", e);
+        eprintln!(
+            "{}",
+            "无法自动恢复暂存区，剩余变更仍保留在工作区中，请手动重新暂存。".red()
+        );
+    }
+}
+
+/// 对暂存的变更进行 Tree-sitter 分析，失败时返回 None
+fn analyze_for_split(config: &AppConfig, root: &str, diff_text: &str) -> Option<DiffAnalysis> {
+    let mut analyzer = match TreeSitterAnalyzer::new(config.tree_sitter.clone()) {
+        Ok(analyzer) => analyzer,
+        Err(e) => {
+            tracing::warn!("Tree-sitter 初始化失败，拆分时不使用结构分析: {}", e);
+            return None;
+        }
+    };
+    analyzer.set_project_root(PathBuf::from(root));
+    // 拆分的是暂存区中的变更，工作区中可能还有未暂存的修改
+    let root = PathBuf::from(root);
+    match analyzer.analyze_diff_with_sources(diff_text, &|path| read_file_version(&root, path, &FileVersionSource::Index)) {
+        Ok(analysis) => Some(analysis),
+        Err(e) => {
+            tracing::warn!("Tree-sitter 分析失败，拆分时不使用结构分析: {}", e);
+            None
+        }
+    }
+}
+
+/// 检查与 `--split` 冲突的提交参数
+///
+/// 每个拆分提交只能包含重新暂存的分组，`-a`/`--all`、`--only`、`--include`、`--patch`、
+/// 路径参数等会让 git commit 额外提交其他变更；修补提交无法拆分为多个提交。
+pub fn validate_split_args(args: &CommitArgs) -> Result<(), AppError> {
+    let mut conflicts = Vec::new();
+    if args.amend {
+        conflicts.push("--amend".to_string());
+    }
+    for group in parse_commit_args(&args.passthrough_args) {
+        if group.options.is_empty() {
+            conflicts.push(format!("路径参数 {}", group.tokens.join(" ")));
+            continue;
+        }
+        for option in &group.options {
+            let Some(spec) = option.spec else {
+                continue;
+            };
+            let conflicting = ["amend", "only", "include", "patch", "pathspec-from-file"].iter().any(|name| option.is(name))
+                || (option.is("all") && !args.auto_stage);
+            if conflicting {
+                conflicts.push(format!("--{}", spec.long));
+            }
+        }
+    }
+    if conflicts.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::Generic(format!("--split 不能与 {} 同时使用", conflicts.join("、"))))
+    }
+}
+
+/// 清空暂存区，按分组依次重新暂存并提交
+///
+/// 某个分组暂存或提交失败时，将该分组及之后的变更恢复到暂存区后返回错误。
+fn commit_groups(
+    root: &str,
+    files: &[PatchFile],
+    units: &[SplitUnit],
+    groups: &[CommitGroup],
+    passthrough: &[String],
+) -> Result<(), AppError> {
+    run_git_in_root(root, &["reset", "-q"])?;
+
+    for (i, group) in groups.iter().enumerate() {
+        let remaining: Vec<usize> = groups[i..].iter().flat_map(|g| g.units.clone()).collect();
+
+        let patch = build_patch(files, units, &group.units);
+        if let Err(e) = apply_patch_to_index(root, &patch, &i.to_string()) {
+            tracing::error!("暂存第 {} 个提交的变更失败", i + 1);
+            restore_units(root, files, units, &remaining);
+            return Err(e);
+        }
+
+        let message_file = create_commit_message_file(&group.message)?;
+        let mut commit_args = vec![
+            "commit".to_string(),
+            "-F".to_string(),
+            message_file.to_string_lossy().to_string(),
+        ];
+        commit_args.extend(passthrough.iter().cloned());
+        let commit_args: Vec<&str> = commit_args.iter().map(|a| a.as_str()).collect();
+
+        let result = run_git_in_root(root, &commit_args);
+        cleanup_commit_message_file(&message_file);
+        match result {
+            Ok(stdout) => {
+                if !stdout.trim().is_empty() {
+                    println!("{}", stdout.trim_end());
+                }
+            }
+            Err(e) => {
+                tracing::error!("第 {} 个拆分提交失败", i + 1);
+                restore_units(root, files, units, &remaining);
+                return Err(e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 将暂存的变更拆分为多个逻辑提交
+///
+/// # Arguments
+///
+/// * `args` - Commit arguments from CLI
+/// * `config` - Application configuration
+///
+/// # Returns
+///
+/// * `Result<bool, AppError>` - true 表示已完成拆分提交，false 表示无需拆分，应继续普通提交流程
+pub async fn handle_commit_split(args: &CommitArgs, config: &AppConfig) -> Result<bool, AppError> {
+    tracing::info!("拆分暂存的变更为多个提交");
+    validate_split_args(args)?;
+
+    let root = get_repository_root()?;
+    if run_git_in_root(&root, &["rev-parse", "--verify", "-q", "HEAD"]).is_err() {
+        return Err(AppError::Generic(
+            "仓库中还没有任何提交，无法拆分暂存的变更。".to_string(),
+        ));
+    }
+
+    let diff_text = run_git_in_root(&root, &["diff", "--staged", "--binary", "--no-color", "--no-ext-diff"])?;
+    if diff_text.trim().is_empty() {
+        return Err(AppError::Git(GitError::NoStagedChanges));
+    }
+
+    let files = parse_staged_patch(&diff_text);
+    let units = collect_split_units(&files);
+    if units.len() < 2 {
+        println!("{}", "暂存区只有一个变更块，无需拆分。".yellow());
+        return Ok(false);
+    }
+
+    let analysis = analyze_for_split(config, &root, &diff_text);
+    let issue_keys = current_issue_keys(&config.commit);
+    let prompt = append_issue_context(
+        build_split_prompt(&files, &units, analysis.as_ref()),
+        &issue_keys,
+        &config.commit,
+    );
+    let prompt = append_style_context(prompt, &config.commit);
+
+    tracing::info!("请求 AI 生成拆分计划，共 {} 个变更块", units.len());
+    let response = send_prompt_and_get_response(
+        config,
+        &prompt,
+        "你是一位资深软件工程师，擅长将一次性暂存的大量代码变更整理为若干个逻辑清晰、可独立审查的提交。",
+    )
+    .await?;
+    let mut groups = parse_split_plan(&response, units.len())?;
+    for group in &mut groups {
+        group.message = enforce_issue_footer(&group.message, &issue_keys, &config.commit);
+    }
+
+    // 展示拆分计划
+    println!("{}", "拆分计划".bold().green());
+    println!("{}", "========".green());
+    for (i, group) in groups.iter().enumerate() {
+        let title = group.message.lines().next().unwrap_or_default();
+        println!("\n{} {}", format!("提交 {}:", i + 1).bold(), title);
+        for unit in &group.units {
+            println!("  - {}", unit_label(&files, &units, *unit));
+        }
+    }
+
+    if groups.len() < 2 {
+        println!("\n{}", "AI 认为这些变更属于同一个提交。".yellow());
+    }
+
+    if !confirm("是否按此计划依次提交？")? {
+        println!("已取消拆分提交，暂存区保持不变。");
+        return Ok(true);
+    }
+
+    // 清空暂存区，逐组重新暂存并提交
+    let mut passthrough = commit_trailer_args(args, config);
+    passthrough.extend(filter_passthrough_args(args));
+    commit_groups(&root, &files, &units, &groups, &passthrough)?;
+
+    println!("\n{}", format!("已完成 {} 个拆分提交。", groups.len()).green());
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_DIFF: &str = "diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,4 @@
+ fn a() {}
++fn b() {}
+ fn c() {}
+ fn d() {}
+@@ -10,2 +11,2 @@ fn e() {
+-    old();
++    new();
+ }
+diff --git a/README.md b/README.md
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/README.md
+@@ -0,0 +1 @@
++hello
+";
+
+    #[test]
+    fn test_parse_staged_patch() {
+        let files = parse_staged_patch(SAMPLE_DIFF);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "src/lib.rs");
+        assert_eq!(files[0].hunks.len(), 2);
+        assert_eq!(files[0].header.len(), 4);
+        assert!(!files[0].is_atomic());
+        assert_eq!(files[1].path, "README.md");
+        assert!(files[1].is_atomic());
+
+        let units = collect_split_units(&files);
+        assert_eq!(units.len(), 3);
+        assert_eq!(units[2], SplitUnit { file_index: 1, hunk_index: None });
+    }
+
+    #[test]
+    fn test_build_patch_selects_hunks() {
+        let files = parse_staged_patch(SAMPLE_DIFF);
+        let units = collect_split_units(&files);
+
+        let patch = build_patch(&files, &units, &[1]);
+        assert!(patch.starts_with("diff --git a/src/lib.rs b/src/lib.rs\n"));
+        assert!(patch.contains("+    new();"));
+        assert!(!patch.contains("+fn b() {}"));
+        assert!(!patch.contains("README.md"));
+
+        let full = build_patch(&files, &units, &[0, 1, 2]);
+        assert_eq!(full, SAMPLE_DIFF);
+    }
+
+    #[test]
+    fn test_parse_split_plan() {
+        let response = "```json\n{\"commits\": [\
+            {\"message\": \"refactor: 调整 e\", \"hunks\": [\"H2\", \"H9\"]},\
+            {\"message\": \"feat: 新增 b\", \"hunks\": [\"H1\", \"H2\"]}]}\n```";
+        let groups = parse_split_plan(response, 3).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].units, vec![1]);
+        // H2 重复被忽略，未分配的 H3 并入最后一个提交
+        assert_eq!(groups[1].units, vec![0, 2]);
+
+        assert!(parse_split_plan("no json here", 3).is_err());
+        assert!(parse_split_plan("{\"commits\": []}", 3).is_err());
+    }
+
+    fn commit_args(args: &[&str]) -> CommitArgs {
+        use crate::cli_interface::args::{GitieArgs, GitieSubCommand};
+        use clap::Parser;
+
+        let mut full = vec!["gitie", "commit", "--split"];
+        full.extend_from_slice(args);
+        match GitieArgs::try_parse_from(full).unwrap().command {
+            GitieSubCommand::Commit(args) => args,
+            other => panic!("unexpected subcommand: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_split_args() {
+        // `--` 之后的参数透传给 git commit
+        let cases: &[(&[&str], bool)] = &[
+            (&[], true),
+            (&["-s", "--", "--no-verify", "-s", "--split"], true),
+            (&["-a", "--", "-a"], true),
+            (&["--", "-a"], false),
+            (&["--", "--all"], false),
+            (&["--", "--only"], false),
+            (&["--", "-i"], false),
+            (&["--", "-p"], false),
+            (&["--", "--amend"], false),
+            (&["--amend"], false),
+            (&["--", "src/lib.rs"], false),
+            (&["--", "--", "src/lib.rs"], false),
+            (&["--", "-m", "src/lib.rs"], true),
+        ];
+        for (args, ok) in cases {
+            let result = validate_split_args(&commit_args(args));
+            assert_eq!(result.is_ok(), *ok, "{:?}: {:?}", args, result);
+        }
+    }
+
+    fn git(dir: &std::path::Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git").arg("-C").arg(dir).args(args).output().unwrap();
+        assert!(output.status.success(), "git {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    #[test]
+    fn test_commit_groups_in_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        git(root, &["init", "-q"]);
+        git(root, &["config", "user.name", "Gitie"]);
+        git(root, &["config", "user.email", "gitie@example.com"]);
+        git(root, &["config", "commit.gpgsign", "false"]);
+        let original: Vec<String> = (1..=20).map(|i| format!("line {}", i)).collect();
+        std::fs::write(root.join("lib.txt"), original.join("\n") + "\n").unwrap();
+        git(root, &["add", "."]);
+        git(root, &["commit", "-q", "-m", "init"]);
+
+        let mut changed = original.clone();
+        changed[1] = "line 2 changed".to_string();
+        changed[17] = "line 18 changed".to_string();
+        std::fs::write(root.join("lib.txt"), changed.join("\n") + "\n").unwrap();
+        std::fs::write(root.join("new.txt"), "new\n").unwrap();
+        git(root, &["add", "."]);
+
+        let root_str = root.to_string_lossy().to_string();
+        let diff_text = run_git_in_root(&root_str, &["diff", "--staged", "--binary", "--no-color", "--no-ext-diff"]).unwrap();
+        let files = parse_staged_patch(&diff_text);
+        let units = collect_split_units(&files);
+        assert_eq!(units.len(), 3);
+
+        let groups = vec![
+            CommitGroup { message: "fix: 第二行".to_string(), units: vec![0] },
+            CommitGroup { message: "feat: 第十八行和新文件".to_string(), units: vec![1, 2] },
+        ];
+        commit_groups(&root_str, &files, &units, &groups, &["--no-verify".to_string()]).unwrap();
+
+        let log = git(root, &["log", "--format=%s", "-3"]);
+        assert_eq!(log.lines().collect::<Vec<_>>(), vec!["feat: 第十八行和新文件", "fix: 第二行", "init"]);
+        let first = git(root, &["show", "HEAD~1:lib.txt"]);
+        assert!(first.contains("line 2 changed") && first.contains("line 18\n"), "{}", first);
+        assert_eq!(git(root, &["show", "HEAD~1", "--name-only", "--format="]).trim(), "lib.txt");
+        // 所有变更都已提交，暂存区和工作区都是干净的
+        assert!(git(root, &["status", "--porcelain"]).trim().is_empty());
+    }
+
+    #[test]
+    fn test_analyze_for_split_reads_staged_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        git(root, &["init", "-q"]);
+        std::fs::write(root.join("lib.rs"), "pub fn first() -> u32 {\n    1\n}\n").unwrap();
+        git(root, &["add", "."]);
+        std::fs::write(root.join("lib.rs"), "pub fn first() -> u32 {\n    1\n}\n\npub fn staged() -> u32 {\n    2\n}\n").unwrap();
+        git(root, &["add", "."]);
+        // 未暂存的修改使工作区中的行号整体下移，新增函数所在的行变成了另一个函数
+        std::fs::write(
+            root.join("lib.rs"),
+            "pub fn unstaged() -> u32 {\n    0\n}\n\npub fn first() -> u32 {\n    1\n}\n\npub fn staged() -> u32 {\n    2\n}\n",
+        )
+        .unwrap();
+
+        let config = AppConfig {
+            ai: Default::default(),
+            tree_sitter: Default::default(),
+            commit: Default::default(),
+            prompts: HashMap::new(),
+        };
+        let root_str = root.to_string_lossy().to_string();
+        let diff_text = git(root, &["diff", "--staged"]);
+        let analysis = analyze_for_split(&config, &root_str, &diff_text).unwrap();
+        let names: Vec<&str> = analysis.file_analyses[0].affected_nodes.iter().map(|node| node.name.as_str()).collect();
+        assert!(names.contains(&"staged"), "{:?}", names);
+        assert!(!names.contains(&"unstaged"), "{:?}", names);
+    }
+}
@@ -0,0 +1,353 @@
+//! 提交风格学习模块
+//!
+//! 从仓库历史中采样最近的提交消息，推断仓库的提交风格（语言、时态、前缀格式、标题长度），
+//! 并将其连同若干示例一起提供给提交提示，使生成的消息符合仓库已有的约定。
+
+use crate::{
+    config_management::settings::CommitConfig, git_module::execute_git_command_and_capture_output,
+};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+/// 提供给 AI 的示例提交数量上限
+const MAX_EXAMPLES: usize = 5;
+
+/// 每个示例保留的最大行数
+const MAX_EXAMPLE_LINES: usize = 6;
+
+lazy_static! {
+    static ref CONVENTIONAL_PREFIX: Regex = Regex::new(r"^[a-z]+(\([^)]*\))?!?: ").unwrap();
+    static ref BRACKET_PREFIX: Regex = Regex::new(r"^\[[^\]]+\]\s*").unwrap();
+    static ref GITMOJI_PREFIX: Regex = Regex::new(r"^(:[a-z0-9_+-]+:|\p{Extended_Pictographic})").unwrap();
+}
+
+/// 提交消息的主要语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StyleLanguage {
+    Chinese,
+    English,
+    Mixed,
+}
+
+/// 提交标题的前缀格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefixFormat {
+    /// `type(scope): 描述`
+    Conventional,
+    /// `[模块] 描述`
+    Bracket,
+    /// `:emoji: 描述`
+    Gitmoji,
+    /// 无固定前缀
+    None,
+}
+
+/// 英文标题的时态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tense {
+    /// `Add ...`
+    Imperative,
+    /// `Added ...`
+    Past,
+    /// `Adds ...`
+    ThirdPerson,
+    Unknown,
+}
+
+/// 从历史提交中推断出的提交风格
+#[derive(Debug, Clone)]
+pub struct CommitStyle {
+    pub language: StyleLanguage,
+    pub prefix_format: PrefixFormat,
+    pub tense: Tense,
+    /// 标题长度（字符数）的中位数
+    pub median_subject_length: usize,
+    /// 标题长度（字符数）的最大值
+    pub max_subject_length: usize,
+    /// 带有正文的提交所占比例
+    pub body_ratio: f32,
+    /// 用作示例的提交消息
+    pub examples: Vec<String>,
+}
+
+/// 采样最近的提交消息
+///
+/// # Arguments
+///
+/// * `config` - 提交配置，决定采样数量和采样的分支
+///
+/// # Returns
+///
+/// * `Vec<String>` - 提交消息列表（不含合并提交），采样失败时为空
+pub fn sample_commit_messages(config: &CommitConfig) -> Vec<String> {
+    if config.style_samples == 0 {
+        return Vec::new();
+    }
+
+    let rev = if config.style_mainline.trim().is_empty() {
+        "HEAD".to_string()
+    } else {
+        config.style_mainline.trim().to_string()
+    };
+
+    let args = vec![
+        "log".to_string(),
+        "--no-merges".to_string(),
+        format!("-n{}", config.style_samples),
+        "--format=%B%x00".to_string(),
+        rev.clone(),
+        "--".to_string(),
+    ];
+    match execute_git_command_and_capture_output(&args) {
+        Ok(output) if output.is_success() => output
+            .stdout
+            .split('\0')
+            .map(|m| m.trim().to_string())
+            .filter(|m| !m.is_empty())
+            .collect(),
+        Ok(_) => {
+            tracing::debug!("无法读取 {} 的提交历史，跳过风格学习", rev);
+            Vec::new()
+        }
+        Err(e) => {
+            tracing::warn!("读取提交历史失败: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// 判断字符是否为中日韩统一表意文字
+fn is_cjk(c: char) -> bool {
+    matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}')
+}
+
+/// 去掉标题中的前缀，返回描述部分
+fn strip_prefix(subject: &str) -> &str {
+    for regex in [&*CONVENTIONAL_PREFIX, &*BRACKET_PREFIX, &*GITMOJI_PREFIX] {
+        if let Some(m) = regex.find(subject) {
+            return subject[m.end()..].trim_start();
+        }
+    }
+    subject
+}
+
+/// 推断单个英文标题的时态
+fn detect_tense(subject: &str) -> Tense {
+    let first_word = strip_prefix(subject)
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    if first_word.is_empty() || !first_word.chars().all(|c| c.is_ascii_alphabetic()) {
+        Tense::Unknown
+    } else if first_word.ends_with("ed") {
+        Tense::Past
+    } else if first_word.ends_with('s') && !first_word.ends_with("ss") {
+        Tense::ThirdPerson
+    } else {
+        Tense::Imperative
+    }
+}
+
+/// 返回出现次数超过一半的值，否则返回 None
+fn majority<T: PartialEq + Copy>(values: &[T]) -> Option<T> {
+    values
+        .iter()
+        .find(|v| values.iter().filter(|x| x == v).count() * 2 > values.len())
+        .copied()
+}
+
+/// 从提交消息中推断提交风格
+///
+/// # Arguments
+///
+/// * `messages` - 最近的提交消息
+///
+/// # Returns
+///
+/// * `Option<CommitStyle>` - 推断出的风格，没有可用样本时返回 None
+pub fn analyze_commit_style(messages: &[String]) -> Option<CommitStyle> {
+    let messages: Vec<&String> = messages
+        .iter()
+        .filter(|m| !m.trim().is_empty() && !m.starts_with("Merge ") && !m.starts_with("Revert "))
+        .collect();
+    if messages.is_empty() {
+        return None;
+    }
+
+    let subjects: Vec<&str> = messages
+        .iter()
+        .map(|m| m.lines().next().unwrap_or_default().trim())
+        .collect();
+
+    let chinese_count = subjects.iter().filter(|s| s.chars().any(is_cjk)).count();
+    let language = if chinese_count * 4 >= subjects.len() * 3 {
+        StyleLanguage::Chinese
+    } else if chinese_count * 4 <= subjects.len() {
+        StyleLanguage::English
+    } else {
+        StyleLanguage::Mixed
+    };
+
+    let prefixes: Vec<PrefixFormat> = subjects
+        .iter()
+        .map(|s| {
+            if CONVENTIONAL_PREFIX.is_match(s) {
+                PrefixFormat::Conventional
+            } else if BRACKET_PREFIX.is_match(s) {
+                PrefixFormat::Bracket
+            } else if GITMOJI_PREFIX.is_match(s) {
+                PrefixFormat::Gitmoji
+            } else {
+                PrefixFormat::None
+            }
+        })
+        .collect();
+    let prefix_format = majority(&prefixes).unwrap_or(PrefixFormat::None);
+
+    let tenses: Vec<Tense> = subjects
+        .iter()
+        .filter(|s| !s.chars().any(is_cjk))
+        .map(|s| detect_tense(s))
+        .filter(|t| *t != Tense::Unknown)
+        .collect();
+    let tense = majority(&tenses).unwrap_or(Tense::Unknown);
+
+    let mut lengths: Vec<usize> = subjects.iter().map(|s| s.chars().count()).collect();
+    lengths.sort_unstable();
+    let median_subject_length = lengths[lengths.len() / 2];
+    let max_subject_length = *lengths.last().unwrap_or(&0);
+
+    let with_body = messages.iter().filter(|m| m.trim().lines().count() > 1).count();
+    let body_ratio = with_body as f32 / messages.len() as f32;
+
+    let examples = messages
+        .iter()
+        .take(MAX_EXAMPLES)
+        .map(|m| {
+            m.lines()
+                .take(MAX_EXAMPLE_LINES)
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect();
+
+    Some(CommitStyle {
+        language,
+        prefix_format,
+        tense,
+        median_subject_length,
+        max_subject_length,
+        body_ratio,
+        examples,
+    })
+}
+
+/// 将提交风格格式化为提示文本
+pub fn format_style_context(style: &CommitStyle) -> String {
+    let language = match style.language {
+        StyleLanguage::Chinese => "中文",
+        StyleLanguage::English => "英文",
+        StyleLanguage::Mixed => "中英文混合（沿用示例中的做法）",
+    };
+    let prefix = match style.prefix_format {
+        PrefixFormat::Conventional => "Conventional Commits，如 `feat(scope): 描述`",
+        PrefixFormat::Bracket => "方括号前缀，如 `[模块] 描述`",
+        PrefixFormat::Gitmoji => "以 emoji 开头，如 `:bug: 描述`",
+        PrefixFormat::None => "无固定前缀",
+    };
+
+    let mut context = String::from("## 仓库提交风格\n\n请遵循本仓库已有的提交消息约定：\n");
+    context.push_str(&format!("- 语言: {}\n", language));
+    context.push_str(&format!("- 标题格式: {}\n", prefix));
+    match style.tense {
+        Tense::Imperative => context.push_str("- 英文标题使用祈使句（如 `Add ...`）\n"),
+        Tense::Past => context.push_str("- 英文标题使用过去式（如 `Added ...`）\n"),
+        Tense::ThirdPerson => context.push_str("- 英文标题使用第三人称（如 `Adds ...`）\n"),
+        Tense::Unknown => {}
+    }
+    context.push_str(&format!(
+        "- 标题长度: 通常约 {} 个字符，不超过 {} 个字符\n",
+        style.median_subject_length,
+        style.max_subject_length.max(style.median_subject_length)
+    ));
+    if style.body_ratio < 0.3 {
+        context.push_str("- 大多数提交只有标题，没有正文\n");
+    } else if style.body_ratio > 0.7 {
+        context.push_str("- 大多数提交在标题后附有正文说明\n");
+    }
+
+    if !style.examples.is_empty() {
+        context.push_str("\n最近的提交示例：\n");
+        for example in &style.examples {
+            context.push_str(&format!("```\n{}\n```\n", example));
+        }
+    }
+
+    context
+}
+
+/// 在提交提示中附加从仓库历史学习到的提交风格
+pub fn append_style_context(prompt: String, config: &CommitConfig) -> String {
+    let messages = sample_commit_messages(config);
+    match analyze_commit_style(&messages) {
+        Some(style) => {
+            tracing::info!(
+                "从 {} 个历史提交中学习提交风格: {:?}, {:?}, {:?}",
+                messages.len(),
+                style.language,
+                style.prefix_format,
+                style.tense
+            );
+            format!("{}\n\n{}", prompt, format_style_context(&style))
+        }
+        None => prompt,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_messages(messages: &[&str]) -> Vec<String> {
+        messages.iter().map(|m| m.to_string()).collect()
+    }
+
+    #[test]
+    fn test_analyze_conventional_english_style() {
+        let messages = to_messages(&[
+            "feat(cli): add reword subcommand\n\nRewrites older messages.",
+            "fix: handle empty diff",
+            "refactor(git): extract branch helper",
+            "Merge branch 'main' into feature",
+            "docs: update README",
+        ]);
+        let style = analyze_commit_style(&messages).unwrap();
+        assert_eq!(style.language, StyleLanguage::English);
+        assert_eq!(style.prefix_format, PrefixFormat::Conventional);
+        assert_eq!(style.tense, Tense::Imperative);
+        assert_eq!(style.examples.len(), 4);
+        assert!(style.max_subject_length >= style.median_subject_length);
+    }
+
+    #[test]
+    fn test_analyze_chinese_bracket_style() {
+        let messages = to_messages(&["[评审] 增加评审策略", "[提交] 支持拆分提交", "[配置] 新增提交配置"]);
+        let style = analyze_commit_style(&messages).unwrap();
+        assert_eq!(style.language, StyleLanguage::Chinese);
+        assert_eq!(style.prefix_format, PrefixFormat::Bracket);
+        assert_eq!(style.tense, Tense::Unknown);
+
+        let context = format_style_context(&style);
+        assert!(context.contains("中文"));
+        assert!(context.contains("[评审] 增加评审策略"));
+    }
+
+    #[test]
+    fn test_detect_tense() {
+        assert_eq!(detect_tense("Added support for X"), Tense::Past);
+        assert_eq!(detect_tense("fix: adds missing check"), Tense::ThirdPerson);
+        assert_eq!(detect_tense("Bump version"), Tense::Imperative);
+        assert!(analyze_commit_style(&[]).is_none());
+    }
+}
@@ -47,6 +47,35 @@ pub struct TreeSitterConfig {
     /// 支持的语言列表
     #[serde(default = "default_languages")]
     pub languages: Vec<String>,
+
+    /// 代码评审时附带的完整代码上下文的 token 预算，为 0 时不附带
+    #[serde(default = "default_context_token_budget")]
+    pub context_token_budget: usize,
+
+    /// 并行分析变更文件的线程数，为 0 时使用可用的 CPU 核数
+    #[serde(default)]
+    pub analysis_threads: usize,
+
+    /// 单个文件的分析时间限制（毫秒），超时的文件跳过语法树分析，为 0 时不限制
+    #[serde(default = "default_file_timeout_ms")]
+    pub file_timeout_ms: u64,
+
+    /// 运行时从动态库加载的语法，键为语言名称，需同时列在 `languages` 中才会加载
+    #[serde(default)]
+    pub grammars: HashMap<String, GrammarConfig>,
+}
+
+/// 编译为动态库（`.so`/`.dylib`/`.dll`）的 tree-sitter 语法
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GrammarConfig {
+    /// 动态库路径，支持以 `~` 开头
+    pub path: PathBuf,
+    /// 动态库导出的语言函数名，默认为 `tree_sitter_<语言名称>`
+    #[serde(default)]
+    pub symbol: Option<String>,
+    /// 使用该语法分析的文件扩展名（不含点）
+    #[serde(default)]
+    pub extensions: Vec<String>,
 }
 
 impl Default for TreeSitterConfig {
@@ -56,12 +85,89 @@ impl Default for TreeSitterConfig {
             analysis_depth: default_analysis_depth(),
             cache_enabled: default_cache_enabled(),
             languages: default_languages(),
+            context_token_budget: default_context_token_budget(),
+            analysis_threads: 0,
+            file_timeout_ms: default_file_timeout_ms(),
+            grammars: HashMap::new(),
         }
     }
 }
 
 // TreeSitterConfig is already clonable via #[derive(Clone)]
 
+// 提交配置
+#[derive(Deserialize, Debug, Clone)]
+pub struct CommitConfig {
+    /// 从分支名中提取工单号的正则表达式，为空时不提取
+    /// 若包含捕获组，则使用第一个捕获组作为工单号
+    #[serde(default = "default_issue_pattern")]
+    pub issue_pattern: String,
+
+    /// 工单引用页脚模板，`{issues}` 会被替换为逗号分隔的工单号，为空时不强制添加页脚
+    #[serde(default = "default_issue_footer")]
+    pub issue_footer: String,
+
+    /// 学习提交风格时采样的历史提交数量，为 0 时不学习
+    #[serde(default = "default_style_samples")]
+    pub style_samples: usize,
+
+    /// 学习提交风格时采样的主线分支，为空时使用当前分支
+    #[serde(default)]
+    pub style_mainline: String,
+
+    /// 是否为每个提交添加 Signed-off-by 签名（DCO）
+    #[serde(default)]
+    pub signoff: bool,
+
+    /// 为每个提交添加的自定义 trailer，例如 "Reviewed-by: Alice <alice@example.com>"
+    #[serde(default)]
+    pub trailers: Vec<String>,
+
+    /// 生成提交消息前运行的本地检查命令，例如 "cargo fmt --check"
+    /// 命令中的 `{files}` 会被替换为暂存的文件列表。
+    /// 检查运行在工作区上，暂存文件还有未暂存的修改时检查的是工作区内容
+    #[serde(default)]
+    pub pre_commit_checks: Vec<String>,
+
+    /// 检查失败时是否使用 AI 解释失败原因
+    #[serde(default)]
+    pub explain_check_failures: bool,
+
+    /// 暂存的变更包含破坏性公共 API 变更时，是否为 AI 生成的提交消息加上 `!` 和 `BREAKING CHANGE` 页脚
+    /// 只适用于发布公共 API 的库；需要启用 Tree-sitter 分析
+    #[serde(default)]
+    pub mark_breaking_changes: bool,
+}
+
+impl Default for CommitConfig {
+    fn default() -> Self {
+        Self {
+            issue_pattern: default_issue_pattern(),
+            issue_footer: default_issue_footer(),
+            style_samples: default_style_samples(),
+            style_mainline: String::new(),
+            signoff: false,
+            trailers: Vec::new(),
+            pre_commit_checks: Vec::new(),
+            explain_check_failures: false,
+            mark_breaking_changes: false,
+        }
+    }
+}
+
+/// 默认不从分支名提取工单号：通用的 `[A-Z]+-\d+` 会把 `UTF-8` 之类的名称误认为工单号
+fn default_issue_pattern() -> String {
+    String::new()
+}
+
+fn default_issue_footer() -> String {
+    "Refs: {issues}".to_string()
+}
+
+fn default_style_samples() -> usize {
+    20
+}
+
 fn default_analysis_depth() -> String {
     "medium".to_string()
 }
@@ -71,7 +177,22 @@ fn default_cache_enabled() -> bool {
 }
 
 fn default_languages() -> Vec<String> {
-    vec!["rust".to_string(), "javascript".to_string(), "python".to_string()]
+    vec![
+        "rust".to_string(),
+        "javascript".to_string(),
+        "python".to_string(),
+        "go".to_string(),
+        "c".to_string(),
+        "cpp".to_string(),
+    ]
+}
+
+fn default_context_token_budget() -> usize {
+    4000
+}
+
+fn default_file_timeout_ms() -> u64 {
+    5000
 }
 
 // AI 配置的部分加载辅助结构体
@@ -98,6 +219,37 @@ struct PartialTreeSitterConfig {
     cache_enabled: Option<bool>,
     #[serde(default)]
     languages: Option<Vec<String>>,
+    #[serde(default)]
+    context_token_budget: Option<usize>,
+    #[serde(default)]
+    analysis_threads: Option<usize>,
+    #[serde(default)]
+    file_timeout_ms: Option<u64>,
+    #[serde(default)]
+    grammars: Option<HashMap<String, GrammarConfig>>,
+}
+
+// 提交配置的部分加载辅助结构体
+#[derive(Deserialize, Debug, Default, Clone)]
+struct PartialCommitConfig {
+    #[serde(default)]
+    issue_pattern: Option<String>,
+    #[serde(default)]
+    issue_footer: Option<String>,
+    #[serde(default)]
+    style_samples: Option<usize>,
+    #[serde(default)]
+    style_mainline: Option<String>,
+    #[serde(default)]
+    signoff: Option<bool>,
+    #[serde(default)]
+    trailers: Option<Vec<String>>,
+    #[serde(default)]
+    pre_commit_checks: Option<Vec<String>>,
+    #[serde(default)]
+    explain_check_failures: Option<bool>,
+    #[serde(default)]
+    mark_breaking_changes: Option<bool>,
 }
 
 // 应用总体配置
@@ -109,6 +261,9 @@ pub struct AppConfig {
     #[serde(default)]
     pub tree_sitter: TreeSitterConfig,
 
+    #[serde(default)]
+    pub commit: CommitConfig,
+
     #[serde(skip)] // System prompt is loaded separately
     pub prompts: HashMap<String, String>,
 }
@@ -118,6 +273,7 @@ pub struct AppConfig {
 struct PartialAppConfig {
     ai: Option<PartialAIConfig>,
     tree_sitter: Option<PartialTreeSitterConfig>,
+    commit: Option<PartialCommitConfig>,
 }
 
 impl AppConfig {
@@ -469,7 +625,8 @@ impl AppConfig {
         Self::load_config_from_file(&user_config_path, &user_prompt_paths)
     }
 
-    fn get_user_file_path(filename: &str) -> Result<std::path::PathBuf, ConfigError> {
+    /// 用户配置目录（~/.config/gitie），用户可以在其中覆盖内置的提示文件
+    pub fn get_user_config_dir() -> Result<std::path::PathBuf, ConfigError> {
         let home_str = std::env::var("HOME").unwrap_or_else(|_| {
             dirs::home_dir()
                 .expect("Could not determine home directory")
@@ -478,7 +635,11 @@ impl AppConfig {
         });
 
         let home = PathBuf::from(home_str);
-        Ok(home.join(USER_CONFIG_DIR).join(filename))
+        Ok(home.join(USER_CONFIG_DIR))
+    }
+
+    fn get_user_file_path(filename: &str) -> Result<std::path::PathBuf, ConfigError> {
+        Ok(Self::get_user_config_dir()?.join(filename))
     }
 
     fn load_config_from_file(
@@ -599,25 +760,59 @@ impl AppConfig {
         let analysis_depth = partial_tree_sitter_config.analysis_depth.unwrap_or_else(default_analysis_depth);
         let cache_enabled = partial_tree_sitter_config.cache_enabled.unwrap_or(true);
         let languages = partial_tree_sitter_config.languages.unwrap_or_else(default_languages);
+        let context_token_budget = partial_tree_sitter_config
+            .context_token_budget
+            .unwrap_or_else(default_context_token_budget);
+        let analysis_threads = partial_tree_sitter_config.analysis_threads.unwrap_or(0);
+        let file_timeout_ms = partial_tree_sitter_config.file_timeout_ms.unwrap_or_else(default_file_timeout_ms);
+        let grammars = partial_tree_sitter_config.grammars.unwrap_or_default();
         
         let tree_sitter_config = TreeSitterConfig {
             enabled,
             analysis_depth: analysis_depth.clone(),
             cache_enabled,
             languages: languages.clone(),
+            context_token_budget,
+            analysis_threads,
+            file_timeout_ms,
+            grammars,
         };
         
-        debug!("Tree-sitter 配置: 启用状态: {}, 分析深度: {}, 缓存启用: {}, 支持语言数量: {}",
+        debug!("Tree-sitter 配置: 启用状态: {}, 分析深度: {}, 缓存启用: {}, 支持语言数量: {}, 上下文预算: {}",
             enabled,
             analysis_depth,
             cache_enabled,
-            languages.len()
+            languages.len(),
+            context_token_budget
         );
         
         if enabled {
             debug!("Tree-sitter 支持的语言: {}", languages.join(", "));
         }
 
+        let partial_commit_config = partial_config.commit.unwrap_or_default();
+        let commit_config = CommitConfig {
+            issue_pattern: partial_commit_config.issue_pattern.unwrap_or_else(default_issue_pattern),
+            issue_footer: partial_commit_config.issue_footer.unwrap_or_else(default_issue_footer),
+            style_samples: partial_commit_config.style_samples.unwrap_or_else(default_style_samples),
+            style_mainline: partial_commit_config.style_mainline.unwrap_or_default(),
+            signoff: partial_commit_config.signoff.unwrap_or(false),
+            trailers: partial_commit_config.trailers.unwrap_or_default(),
+            pre_commit_checks: partial_commit_config.pre_commit_checks.unwrap_or_default(),
+            explain_check_failures: partial_commit_config.explain_check_failures.unwrap_or(false),
+            mark_breaking_changes: partial_commit_config.mark_breaking_changes.unwrap_or(false),
+        };
+
+        debug!("提交配置: 工单号正则: {}, 工单页脚: {}, 风格采样数: {}, 风格主线: {}, 签名: {}, trailer 数量: {}, 提交前检查数量: {}",
+            commit_config.issue_pattern,
+            commit_config.issue_footer,
+            commit_config.style_samples,
+            if commit_config.style_mainline.is_empty() { "当前分支" } else { &commit_config.style_mainline },
+            commit_config.signoff,
+            commit_config.trailers.len(),
+            commit_config.pre_commit_checks.len()
+        );
+
         if prompts.is_empty() {
             warn!("未能加载任何提示文件，配置可能不完整");
         } else if prompts.len() < prompt_paths.len() {
@@ -630,6 +825,7 @@ impl AppConfig {
         let config = Self {
             ai: ai_config,
             tree_sitter: tree_sitter_config,
+            commit: commit_config,
             prompts,
         };
         
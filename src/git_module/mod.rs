@@ -101,6 +101,44 @@ pub fn is_in_git_repository() -> Result<bool, AppError> {
     }
 }
 
+/// Returns the short name of the currently checked-out branch
+///
+/// # Returns
+///
+/// * `Result<Option<String>, AppError>` - The branch name, None on a detached HEAD, or an error
+pub fn get_current_branch() -> Result<Option<String>, AppError> {
+    let output = execute_git_command_and_capture_output(&[
+        "symbolic-ref".to_string(),
+        "--short".to_string(),
+        "-q".to_string(),
+        "HEAD".to_string(),
+    ])?;
+
+    let branch = output.stdout.trim();
+    if output.is_success() && !branch.is_empty() {
+        Ok(Some(branch.to_string()))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Returns the top-level directory of the current working tree
+///
+/// # Returns
+///
+/// * `Result<PathBuf, AppError>` - The repository root, or an error outside a repository
+pub fn get_repository_root() -> Result<std::path::PathBuf, AppError> {
+    let output = execute_git_command_and_capture_output(&[
+        "rev-parse".to_string(),
+        "--show-toplevel".to_string(),
+    ])?;
+
+    if !output.is_success() {
+        return Err(AppError::Git(GitError::NotARepository));
+    }
+    Ok(std::path::PathBuf::from(output.stdout.trim()))
+}
+
 /// Execute Git command and optionally handle errors
 ///
 /// Executes Git command, captures output, and based on execution status decides 
@@ -9,8 +9,11 @@ mod tree_sitter_analyzer;
 
 use crate::ai_module::explainer::{explain_git_error, explain_git_command_output};
 use crate::cli_interface::args::{CommitArgs, GitieArgs, GitieSubCommand, ReviewArgs, args_contain_help, should_use_ai, generate_gitie_help};
+use crate::command_processing::api_diff::handle_api_diff;
 use crate::command_processing::commit::handle_commit;
+use crate::command_processing::deviation::handle_deviation;
 use crate::command_processing::review::{handle_review, handle_commit_with_review};
+use crate::command_processing::reword::handle_reword;
 use crate::config_management::settings::AppConfig;
 use crate::core::errors::{AppError, GitError};
 use crate::git_module::{
@@ -182,6 +185,8 @@ async fn main() -> Result<(), AppError> {
             tracing::warn!("解析review命令失败");
             // 创建默认的ReviewArgs
             let default_review_args = ReviewArgs {
+                revision: None,
+                branch: None,
                 depth: "normal".to_string(),
                 focus: None,
                 lang: None,
@@ -191,6 +196,7 @@ async fn main() -> Result<(), AppError> {
                 no_tree_sitter: false,
                 review_ts: false,
                 passthrough_args: vec![],
+                fix: false,
                 commit1: None,
                 commit2: None,
             };
@@ -198,6 +204,69 @@ async fn main() -> Result<(), AppError> {
         }
     }
     
+    // 检查是否为 deviation 命令
+    if filtered_args.first().is_some_and(|a| a == "deviation") && filtered_args.iter().all(|a| a != "--help" && a != "-h") {
+        tracing::info!("检测到deviation命令");
+
+        let mut deviation_args_vec = vec!["gitie".to_string()];
+        deviation_args_vec.extend_from_slice(&filtered_args);
+
+        tracing::debug!("重构的deviation命令: {:?}", deviation_args_vec);
+
+        match GitieArgs::try_parse_from(&deviation_args_vec) {
+            Ok(GitieArgs { command: GitieSubCommand::Deviation(deviation_args) }) => {
+                return handle_deviation(deviation_args, &config).await;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!("解析deviation命令失败");
+                return Err(AppError::Generic(format!("deviation 参数错误: {}", e)));
+            }
+        }
+    }
+
+    // 检查是否为 api-diff 命令
+    if filtered_args.first().is_some_and(|a| a == "api-diff") && filtered_args.iter().all(|a| a != "--help" && a != "-h") {
+        tracing::info!("检测到api-diff命令");
+
+        let mut api_diff_args_vec = vec!["gitie".to_string()];
+        api_diff_args_vec.extend_from_slice(&filtered_args);
+
+        tracing::debug!("重构的api-diff命令: {:?}", api_diff_args_vec);
+
+        match GitieArgs::try_parse_from(&api_diff_args_vec) {
+            Ok(GitieArgs { command: GitieSubCommand::ApiDiff(api_diff_args) }) => {
+                return handle_api_diff(api_diff_args, &config).await;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!("解析api-diff命令失败");
+                return Err(AppError::Generic(format!("api-diff 参数错误: {}", e)));
+            }
+        }
+    }
+
+    // 检查是否为 reword 命令
+    if filtered_args.first().is_some_and(|a| a == "reword") && filtered_args.iter().all(|a| a != "--help" && a != "-h") {
+        tracing::info!("检测到reword命令");
+
+        let mut reword_args_vec = vec!["gitie".to_string()];
+        reword_args_vec.extend_from_slice(&filtered_args);
+
+        tracing::debug!("重构的reword命令: {:?}", reword_args_vec);
+
+        match GitieArgs::try_parse_from(&reword_args_vec) {
+            Ok(GitieArgs { command: GitieSubCommand::Reword(reword_args) }) => {
+                return handle_reword(reword_args, &config).await;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!("解析reword命令失败");
+                return Err(AppError::Generic(format!("reword 参数错误: {}", e)));
+            }
+        }
+    }
+
     // 如果是commit命令，使用AI辅助生成提交信息
     if filtered_args.contains(&"commit".to_string()) && filtered_args.iter().all(|a| a != "--help" && a != "-h") {
         tracing::info!("检测到commit命令");
@@ -217,12 +286,8 @@ async fn main() -> Result<(), AppError> {
             match parsed_args.command {
                 GitieSubCommand::Commit(commit_args) => {
                     // 检查是否需要进行提交前代码评审
-                    if commit_args.review {
-                        if let Ok(should_cancel) = handle_commit_with_review(&commit_args, &config).await {
-                            if should_cancel {
-                                return Ok(());
-                            }
-                        }
+                    if commit_args.review && handle_commit_with_review(&commit_args, &config).await? {
+                        return Ok(());
                     }
                     return handle_commit(commit_args, &config).await;
                 }
@@ -250,16 +315,18 @@ async fn main() -> Result<(), AppError> {
                     || filtered_args.contains(&"-a".to_string()),
                 message: None,
                 review: filtered_args.contains(&"--review".to_string()),
+                review_policy: None,
+                split: filtered_args.contains(&"--split".to_string()),
+                amend: false,
+                signoff: false,
+                gpg_sign: None,
+                trailers: Vec::new(),
                 passthrough_args,
             };
             
             // 检查是否需要进行提交前代码评审
-            if default_commit_args.review {
-                if let Ok(should_cancel) = handle_commit_with_review(&default_commit_args, &config).await {
-                    if should_cancel {
-                        return Ok(());
-                    }
-                }
+            if default_commit_args.review && handle_commit_with_review(&default_commit_args, &config).await? {
+                return Ok(());
             }
             
             return handle_commit(default_commit_args, &config).await;
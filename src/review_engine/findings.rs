@@ -0,0 +1,268 @@
+//! AI 评审的结构化问题
+//!
+//! 要求模型在评审末尾输出符合 [`FINDINGS_JSON_INSTRUCTION`] 中 schema 的 JSON 问题列表，
+//! 使用 serde 校验后转换为与规则引擎相同的 [`Issue`]，以便输出格式和提交策略统一处理。
+
+use super::{CodeLocation, Issue, RuleCategory, Severity};
+use serde::Deserialize;
+
+/// 要求 AI 输出结构化问题列表的说明，包含 JSON Schema
+pub const FINDINGS_JSON_INSTRUCTION: &str = "在评审的最后，用一个 ```json 代码块输出结构化的问题列表。\
+    内容必须是符合以下 JSON Schema 的数组，没有发现问题时输出 `[]`：\n\
+    ```\n\
+    {\"type\": \"array\", \"items\": {\"type\": \"object\",\n\
+      \"required\": [\"file\", \"line\", \"severity\", \"category\", \"message\"],\n\
+      \"properties\": {\n\
+        \"file\": {\"type\": \"string\", \"description\": \"相对仓库根目录的文件路径\"},\n\
+        \"line\": {\"type\": \"integer\", \"minimum\": 1, \"description\": \"变更后文件中的行号\"},\n\
+        \"severity\": {\"enum\": [\"error\", \"warning\", \"info\"]},\n\
+        \"category\": {\"enum\": [\"security\", \"performance\", \"bugs\", \"style\", \"complexity\", \"best-practices\"]},\n\
+        \"message\": {\"type\": \"string\"},\n\
+        \"suggestion\": {\"type\": \"string\"}}}}\n\
+    ```";
+
+/// AI 输出的问题严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum FindingSeverity {
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+/// AI 输出的问题类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum FindingCategory {
+    Security,
+    Performance,
+    #[serde(alias = "bug")]
+    Bugs,
+    Style,
+    Complexity,
+    #[serde(alias = "best_practices")]
+    BestPractices,
+}
+
+/// AI 输出的单个问题
+#[derive(Debug, Clone, Deserialize)]
+struct AiFinding {
+    file: String,
+    line: usize,
+    severity: FindingSeverity,
+    category: FindingCategory,
+    message: String,
+    #[serde(default)]
+    suggestion: Option<String>,
+}
+
+impl AiFinding {
+    fn validate(&self) -> Result<(), String> {
+        if self.file.trim().is_empty() {
+            return Err("file 不能为空".to_string());
+        }
+        if self.line == 0 {
+            return Err(format!("{} 的 line 必须从 1 开始", self.file));
+        }
+        if self.message.trim().is_empty() {
+            return Err(format!("{}:{} 的 message 不能为空", self.file, self.line));
+        }
+        Ok(())
+    }
+
+    fn into_issue(self, index: usize) -> Issue {
+        let title = self.message.lines().next().unwrap_or_default().trim().to_string();
+        Issue {
+            id: format!("ai:{}", index + 1),
+            title,
+            description: self.message.trim().to_string(),
+            location: CodeLocation {
+                file_path: self.file.trim().trim_start_matches("./").to_string(),
+                start_line: self.line,
+                end_line: self.line,
+                start_column: None,
+                end_column: None,
+            },
+            severity: match self.severity {
+                FindingSeverity::Error => Severity::Error,
+                FindingSeverity::Warning => Severity::Warning,
+                FindingSeverity::Info => Severity::Info,
+                FindingSeverity::Hint => Severity::Hint,
+            },
+            category: match self.category {
+                FindingCategory::Security => RuleCategory::Security,
+                FindingCategory::Performance => RuleCategory::Performance,
+                FindingCategory::Bugs => RuleCategory::Bugs,
+                FindingCategory::Style => RuleCategory::Style,
+                FindingCategory::Complexity => RuleCategory::Complexity,
+                FindingCategory::BestPractices => RuleCategory::BestPractices,
+            },
+            code_snippet: None,
+            suggestion: self.suggestion.filter(|s| !s.trim().is_empty()),
+            explanation: None,
+        }
+    }
+}
+
+/// 返回文本中所有 ```json 代码块的 (起始位置, 结束位置, 内容)
+fn json_blocks(text: &str) -> Vec<(usize, usize, &str)> {
+    let mut blocks = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = text[offset..].find("```json") {
+        let start = offset + start;
+        let content_start = match text[start..].find('\n') {
+            Some(i) => start + i + 1,
+            None => break,
+        };
+        let Some(len) = text[content_start..].find("```") else {
+            break;
+        };
+        let end = content_start + len + 3;
+        blocks.push((start, end, &text[content_start..content_start + len]));
+        offset = end;
+    }
+    blocks
+}
+
+/// 从 AI 输出中解析并校验问题列表
+///
+/// 优先解析 ```json 代码块，没有代码块时将整段文本作为 JSON 解析。
+/// 多个代码块（如按语言分组的评审）的问题会合并。
+///
+/// # Returns
+///
+/// * `Result<Vec<Issue>, String>` - 解析失败时返回可反馈给模型的错误说明
+pub fn parse_ai_findings(text: &str) -> Result<Vec<Issue>, String> {
+    let blocks: Vec<&str> = json_blocks(text).into_iter().map(|(_, _, content)| content).collect();
+    let sources = if blocks.is_empty() { vec![text.trim()] } else { blocks };
+
+    let mut findings = Vec::new();
+    for source in sources {
+        let parsed: Vec<AiFinding> =
+            serde_json::from_str(source.trim()).map_err(|e| format!("问题列表不是有效的 JSON 数组: {}", e))?;
+        for finding in &parsed {
+            finding.validate()?;
+        }
+        findings.extend(parsed);
+    }
+
+    Ok(findings.into_iter().enumerate().map(|(i, f)| f.into_issue(i)).collect())
+}
+
+/// 从评审文本中移除问题列表的 JSON 代码块，供人阅读的输出使用
+pub fn strip_findings_blocks(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut last = 0;
+    for (start, end, content) in json_blocks(text) {
+        if content.trim_start().starts_with('[') {
+            stripped.push_str(&text[last..start]);
+            last = end;
+        }
+    }
+    stripped.push_str(&text[last..]);
+    stripped.trim_end().to_string()
+}
+
+/// 合并 AI 与规则引擎的问题
+///
+/// 规则引擎的问题与某个 AI 问题位于同一文件同一行且类别相同时视为重复，只保留 AI 的问题。
+/// 结果按严重程度、文件和行号排序。
+pub fn merge_issues(ai_issues: Vec<Issue>, rule_issues: Vec<Issue>) -> Vec<Issue> {
+    let mut merged = ai_issues;
+    for issue in rule_issues {
+        let duplicate = merged.iter().any(|existing| {
+            existing.category == issue.category
+                && existing.location.file_path == issue.location.file_path
+                && existing.location.start_line <= issue.location.end_line
+                && issue.location.start_line <= existing.location.end_line
+        });
+        if !duplicate {
+            merged.push(issue);
+        }
+    }
+    merged.sort_by(|a, b| {
+        severity_rank(a.severity)
+            .cmp(&severity_rank(b.severity))
+            .then_with(|| a.location.file_path.cmp(&b.location.file_path))
+            .then_with(|| a.location.start_line.cmp(&b.location.start_line))
+    });
+    merged
+}
+
+fn severity_rank(severity: Severity) -> u8 {
+    match severity {
+        Severity::Error => 0,
+        Severity::Warning => 1,
+        Severity::Info => 2,
+        Severity::Hint => 3,
+    }
+}
+
+/// 将问题列表格式化为评审报告中的 Markdown 章节
+pub fn render_issues_markdown(issues: &[Issue]) -> String {
+    if issues.is_empty() {
+        return String::new();
+    }
+
+    let mut text = format!("## 问题列表\n\n共 {} 个问题：\n\n", issues.len());
+    for issue in issues {
+        let severity = match issue.severity {
+            Severity::Error => "错误",
+            Severity::Warning => "警告",
+            Severity::Info => "信息",
+            Severity::Hint => "提示",
+        };
+        let source = if issue.id.starts_with("ai:") { "AI" } else { "规则" };
+        text.push_str(&format!(
+            "- [{}] `{}:{}` {}（{:?}，来源: {}）\n",
+            severity, issue.location.file_path, issue.location.start_line, issue.description, issue.category, source
+        ));
+        if let Some(suggestion) = &issue.suggestion {
+            text.push_str(&format!("  - 建议: {}\n", suggestion));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REVIEW: &str = "# 评审\n\n存在 SQL 注入。\n\n```json\n[{\"file\": \"src/db.rs\", \"line\": 12, \"severity\": \"error\", \"category\": \"security\", \"message\": \"拼接 SQL 导致注入\", \"suggestion\": \"使用参数化查询\"}]\n```\n";
+
+    #[test]
+    fn test_parse_ai_findings() {
+        let issues = parse_ai_findings(REVIEW).unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].id, "ai:1");
+        assert_eq!(issues[0].location.file_path, "src/db.rs");
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert_eq!(issues[0].category, RuleCategory::Security);
+        assert_eq!(issues[0].suggestion.as_deref(), Some("使用参数化查询"));
+
+        assert!(parse_ai_findings("[]").unwrap().is_empty());
+        assert!(parse_ai_findings("没有 JSON").is_err());
+        assert!(parse_ai_findings("[{\"file\": \"a.rs\", \"line\": 1, \"severity\": \"fatal\", \"category\": \"bugs\", \"message\": \"x\"}]").is_err());
+        assert!(parse_ai_findings("[{\"file\": \"a.rs\", \"line\": 0, \"severity\": \"info\", \"category\": \"bug\", \"message\": \"x\"}]").is_err());
+
+        assert_eq!(strip_findings_blocks(REVIEW), "# 评审\n\n存在 SQL 注入。");
+    }
+
+    #[test]
+    fn test_merge_issues_drops_duplicate_rule_findings() {
+        let ai = parse_ai_findings(REVIEW).unwrap();
+        let mut duplicate = ai[0].clone();
+        duplicate.id = "hardcoded-credential:1".to_string();
+        duplicate.severity = Severity::Warning;
+        let mut other = duplicate.clone();
+        other.location.start_line = 40;
+        other.location.end_line = 40;
+
+        let merged = merge_issues(ai, vec![other, duplicate]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].id, "ai:1");
+        assert_eq!(merged[1].location.start_line, 40);
+        assert!(render_issues_markdown(&merged).contains("[错误] `src/db.rs:12` 拼接 SQL 导致注入（Security，来源: AI）"));
+    }
+}
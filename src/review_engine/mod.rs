@@ -7,6 +7,9 @@ use serde::{Serialize, Deserialize};
 use crate::tree_sitter_analyzer::core::GitDiff;
 use crate::config_management::settings::TreeSitterConfig;
 
+pub mod findings;
+pub mod fixes;
+
 /// 规则类别
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum RuleCategory {
@@ -48,6 +51,44 @@ pub enum AnalysisDepth {
     Deep,
 }
 
+/// 问题在代码中的位置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeLocation {
+    /// 文件路径
+    pub file_path: String,
+    /// 起始行（从 1 开始）
+    pub start_line: usize,
+    /// 结束行（包含）
+    pub end_line: usize,
+    /// 起始列
+    pub start_column: Option<usize>,
+    /// 结束列
+    pub end_column: Option<usize>,
+}
+
+/// 带位置信息的评审问题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Issue {
+    /// 问题标识，格式为 `规则名:序号`
+    pub id: String,
+    /// 问题标题
+    pub title: String,
+    /// 问题描述
+    pub description: String,
+    /// 问题位置
+    pub location: CodeLocation,
+    /// 严重程度
+    pub severity: Severity,
+    /// 规则类别
+    pub category: RuleCategory,
+    /// 相关代码片段
+    pub code_snippet: Option<String>,
+    /// 修复建议
+    pub suggestion: Option<String>,
+    /// 详细解释
+    pub explanation: Option<String>,
+}
+
 /// 简化的评审结果
 #[derive(Debug, Clone)]
 pub struct SimpleReviewResult {
@@ -73,46 +114,95 @@ impl SimpleReviewer {
     
     /// 执行简单评审
     pub fn review(&self, diff: &GitDiff) -> Vec<SimpleReviewResult> {
-        let mut results = Vec::new();
+        let mut results: Vec<SimpleReviewResult> = self
+            .review_issues(diff)
+            .into_iter()
+            .map(|issue| SimpleReviewResult {
+                title: issue.title,
+                content: issue.description,
+                severity: issue.severity,
+            })
+            .collect();
+        
+        // 如果没有发现问题，添加一个积极的反馈
+        if results.is_empty() {
+            results.push(SimpleReviewResult {
+                title: "代码质量良好".to_string(),
+                content: "未发现明显问题，代码质量良好".to_string(),
+                severity: Severity::Info,
+            });
+        }
+        
+        results
+    }
+
+    /// 执行简单评审，返回带位置信息的问题
+    pub fn review_issues(&self, diff: &GitDiff) -> Vec<Issue> {
+        let mut issues = Vec::new();
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        let mut push = |rule: &'static str, issue: Issue| {
+            let count = counts.entry(rule).or_insert(0);
+            *count += 1;
+            issues.push(Issue { id: format!("{}:{}", rule, count), ..issue });
+        };
         
-        // 简单检查硬编码的凭据
         for file in &diff.changed_files {
+            let file_path = file.path.to_string_lossy().to_string();
             for hunk in &file.hunks {
+                let mut line_number = hunk.new_range.start;
                 for line in &hunk.lines {
-                    if line.starts_with('+') && (
-                        line.contains("password") || 
-                        line.contains("secret") || 
-                        line.contains("token") || 
+                    if line.starts_with('-') || line.starts_with('\\') {
+                        continue;
+                    }
+                    let location = CodeLocation {
+                        file_path: file_path.clone(),
+                        start_line: line_number,
+                        end_line: line_number,
+                        start_column: None,
+                        end_column: None,
+                    };
+                    line_number += 1;
+                    if !line.starts_with('+') {
+                        continue;
+                    }
+                    
+                    // 简单检查硬编码的凭据
+                    if line.contains("password") ||
+                        line.contains("secret") ||
+                        line.contains("token") ||
                         line.contains("api_key")
-                    ) {
-                        results.push(SimpleReviewResult {
+                    {
+                        push("hardcoded-credential", Issue {
+                            id: String::new(),
                             title: "检测到硬编码凭证".to_string(),
-                            content: "代码中可能包含硬编码的敏感信息，建议使用环境变量或配置文件存储".to_string(),
+                            description: "代码中可能包含硬编码的敏感信息，建议使用环境变量或配置文件存储".to_string(),
+                            location: location.clone(),
                             severity: Severity::Error,
+                            category: RuleCategory::Security,
+                            code_snippet: Some(line[1..].trim().to_string()),
+                            suggestion: Some("使用环境变量或配置文件存储敏感信息".to_string()),
+                            explanation: None,
                         });
                     }
                     
                     // 检查长行
-                    if line.starts_with('+') && line.len() > 100 {
-                        results.push(SimpleReviewResult {
+                    if line.len() > 100 {
+                        push("long-line", Issue {
+                            id: String::new(),
                             title: "行长度过长".to_string(),
-                            content: "检测到长度超过100字符的行，建议拆分以提高可读性".to_string(),
+                            description: "检测到长度超过100字符的行，建议拆分以提高可读性".to_string(),
+                            location,
                             severity: Severity::Info,
+                            category: RuleCategory::Style,
+                            code_snippet: None,
+                            suggestion: None,
+                            explanation: None,
                         });
                     }
                 }
             }
         }
         
-        // 如果没有发现问题，添加一个积极的反馈
-        if results.is_empty() {
-            results.push(SimpleReviewResult {
-                title: "代码质量良好".to_string(),
-                content: "未发现明显问题，代码质量良好".to_string(),
-                severity: Severity::Info,
-            });
-        }
-        
-        results
+        issues
     }
 }
\ No newline at end of file
@@ -1,8 +1,9 @@
 // src/tree_sitter_analyzer/analyzer.rs
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant, SystemTime};
 use tree_sitter::{Parser, Query, Language}; // Added Language here
 use tracing::{debug, error, warn};
 
@@ -15,19 +16,79 @@ use super::core::{ // Assuming core.rs will export these
     ChangePattern, ChangeScope,
     get_tree_sitter_rust, get_tree_sitter_java, 
     get_tree_sitter_python, get_tree_sitter_go,
+    get_tree_sitter_javascript, get_tree_sitter_typescript, get_tree_sitter_tsx,
+    get_tree_sitter_c, get_tree_sitter_cpp, load_grammar_library,
     // calculate_hash, // Assuming this will be in core or a utils.rs
     // parse_git_diff, // Assuming this will be in core or a utils.rs
 };
 use super::java::JavaProjectStructure;
 use super::java::{ // Import functions from java.rs module
-    extract_java_package_name, extract_java_imports, extract_java_class_name,
+    is_java_node_public_impl, extract_java_package_name, extract_java_imports, extract_java_class_name,
     extract_java_class_relations, extract_java_methods,
 };
-// use super::rust::{analyze_rust_changes}; // Example for rust
+use super::python::{self, PythonChangePattern,
+    extract_python_all_exports, is_python_node_public_impl,
+    python_decorators, is_python_route_decorator, python_definition_at,
+};
+use super::go::{self, GoChangePattern,
+    go_declaration_node, go_type_kind, go_receiver_type, is_go_node_public_impl,
+};
+use super::rust::is_rust_node_public_impl;
 
 // Now using calculate_hash from core.rs
 use super::core::calculate_hash;
+use super::queries::{builtin_query, load_query_source, query_dirs};
+use super::ast_diff::{
+    AstDiff, Definition, FileDefinitions, FileVersions,
+    definitions_within_lines, detect_moved_code, diff_definitions, extract_definitions,
+};
+use super::api_surface::{ApiItem, ApiReport, FileApi, diff_api, extract_api_items};
+use super::disk_cache::{AnalysisCache, CacheEntry, content_key, query_fingerprint};
+
+
+
+/// 单个变更文件的分析结果，由工作线程生成后按 diff 中的顺序合并
+struct FileOutcome {
+    /// 文件在工作区中不存在时为 None
+    analysis: Option<FileAnalysis>,
+    /// 支持的语言，用于统计语言分布
+    language: Option<String>,
+    removed_definitions: Vec<Definition>,
+    added_definitions: Vec<Definition>,
+    /// 本次解析的语法树，合并时放入内存缓存
+    parsed: Option<FileAst>,
+}
+
+/// 各语言的变更文件数，用于判断语言特定的变更模式
+#[derive(Debug, Default, Clone, Copy)]
+struct LanguageChangeCounts {
+    java: usize,
+    rust: usize,
+    python: usize,
+    go: usize,
+}
+
+impl LanguageChangeCounts {
+    fn record(&mut self, language: &str) {
+        match language {
+            "java" => self.java += 1,
+            "rust" => self.rust += 1,
+            "python" => self.python += 1,
+            "go" => self.go += 1,
+            _ => {}
+        }
+    }
+}
 
+/// 变更后文件的结构节点和定义，可能来自持久化缓存
+struct FileStructure {
+    path: PathBuf,
+    language_id: String,
+    source: String,
+    /// 文件中所有结构节点（未关联具体 hunk）
+    nodes: Vec<AffectedNode>,
+    definitions: Vec<Definition>,
+}
 
 #[derive(Debug)]
 pub struct TreeSitterAnalyzer {
@@ -36,6 +97,8 @@ pub struct TreeSitterAnalyzer {
     languages: HashMap<String, Language>,
     file_asts: HashMap<PathBuf, FileAst>, // Cache for parsed file ASTs
     queries: HashMap<String, Query>,      // Cache for compiled queries
+    query_fingerprints: HashMap<String, String>, // 结构查询的指纹，用于判断分析缓存是否过期
+    disk_cache: Option<AnalysisCache>,    // 仓库 .git/gitie 中的持久化分析缓存
                                       // parser_cache: HashMap<String, Parser>, // If we want to reuse parsers
 }
 
@@ -47,6 +110,8 @@ impl TreeSitterAnalyzer {
             languages: HashMap::new(),
             file_asts: HashMap::new(),
             queries: HashMap::new(),
+            query_fingerprints: HashMap::new(),
+            disk_cache: None,
         };
         analyzer.initialize_languages()?;
         analyzer.initialize_queries()?;
@@ -57,6 +122,15 @@ impl TreeSitterAnalyzer {
         self.project_root = root;
         // Potentially clear or update caches if project root changes
         self.file_asts.clear(); 
+        // 持久化缓存保存在项目的 Git 目录中，遵循 cache_enabled 配置
+        self.disk_cache = if self.config.cache_enabled { AnalysisCache::open(&self.project_root) } else { None };
+        if let Some(cache) = &self.disk_cache {
+            cache.prune_if_due();
+        }
+        // 仓库内的 .gitie/queries 可能覆盖内置查询，需要重新编译
+        if let Err(e) = self.initialize_queries() {
+            warn!("重新加载 tree-sitter 查询失败: {}", e);
+        }
     }
     
     /// Create a simple GitDiff from diff text
@@ -102,14 +176,16 @@ impl TreeSitterAnalyzer {
         for line in diff_text.lines() {
             // Process diff header lines
             if line.starts_with("diff --git ") {
-                // Save previous file if exists
-                if let Some(file) = current_file.take() {
+                // Save previous hunk and file if exists
+                if let Some(mut file) = current_file.take() {
+                    file.hunks.extend(current_hunk.take());
                     git_diff.changed_files.push(file);
                 }
-                
+
                 // Start new file
                 current_file = Some(ChangedFile {
                     path: PathBuf::new(),
+                    old_path: None,
                     change_type: ChangeType::Modified,
                     hunks: Vec::new(),
                     file_mode_change: None,
@@ -120,9 +196,23 @@ impl TreeSitterAnalyzer {
                 if let Some(ref mut file) = current_file {
                     if line.starts_with("+++ b/") && line.len() > 6 {
                         file.path = PathBuf::from(&line[6..]);
+                    } else if line.starts_with("--- a/") && line.len() > 6 && current_hunk.is_none() {
+                        file.old_path = Some(PathBuf::from(&line[6..]));
                     }
                 }
             }
+            // Process renames; pure renames have no ---/+++ lines
+            else if let Some(from) = line.strip_prefix("rename from ") {
+                if let Some(ref mut file) = current_file {
+                    file.old_path = Some(PathBuf::from(from));
+                    file.change_type = ChangeType::Renamed;
+                }
+            }
+            else if let Some(to) = line.strip_prefix("rename to ") {
+                if let Some(ref mut file) = current_file {
+                    file.path = PathBuf::from(to);
+                }
+            }
             // Process file mode changes
             else if line.starts_with("new file mode ") {
                 if let Some(ref mut file) = current_file {
@@ -183,6 +273,15 @@ impl TreeSitterAnalyzer {
             git_diff.changed_files.push(file);
         }
         
+        // Deleted files only have a `--- a/` path
+        for file in &mut git_diff.changed_files {
+            if file.path.as_os_str().is_empty()
+                && let Some(old_path) = &file.old_path
+            {
+                file.path = old_path.clone();
+            }
+        }
+        
         Ok(git_diff)
     }
     
@@ -221,134 +320,112 @@ impl TreeSitterAnalyzer {
         if self.config.languages.contains(&"go".to_string()) {
             self.languages.insert("go".to_string(), get_tree_sitter_go());
         }
-        // Potentially load tree_sitter_javascript if configured
+        // JavaScript 和 TypeScript 共用 "javascript" 语言标识，按扩展名选择语法
+        if self.config.languages.iter().any(|l| l == "javascript" || l == "typescript") {
+            self.languages.insert("javascript".to_string(), get_tree_sitter_javascript());
+            self.languages.insert("typescript".to_string(), get_tree_sitter_typescript());
+            self.languages.insert("tsx".to_string(), get_tree_sitter_tsx());
+        }
+        if self.config.languages.contains(&"c".to_string()) {
+            self.languages.insert("c".to_string(), get_tree_sitter_c());
+        }
+        // 头文件使用 C++ 语法解析，C++ 语法可以解析绝大多数 C 头文件
+        if self.config.languages.iter().any(|l| l == "cpp" || l == "c") {
+            self.languages.insert("cpp".to_string(), get_tree_sitter_cpp());
+        }
+        // 配置中以动态库提供的语法，同名时覆盖内置语法
+        for (name, grammar) in &self.config.grammars {
+            if !self.config.languages.contains(name) {
+                debug!("语法 {} 未列在 tree_sitter.languages 中，跳过加载", name);
+                continue;
+            }
+            match load_grammar_library(name, grammar) {
+                Ok(language) => {
+                    debug!("已从 {} 加载语法 {}", grammar.path.display(), name);
+                    self.languages.insert(name.clone(), language);
+                }
+                Err(e) => warn!("加载语法 {} 失败: {}", name, e),
+            }
+        }
         Ok(())
     }
-    
-    // Method to get Rust query pattern (moved from the original monolithic file)
-    pub(crate) fn get_rust_query_pattern(&self) -> String {
-        r#"
-        ; 函数定义
-        (function_item) @function.declaration
-        (function_item name: (identifier) @function.name)
-        
-        ; 结构体定义
-        (struct_item) @struct.declaration
-        (struct_item name: (type_identifier) @struct.name)
-        
-        ; 枚举定义
-        (enum_item) @enum.declaration
-        (enum_item name: (type_identifier) @enum.name)
-        
-        ; 特性定义
-        (trait_item) @trait.declaration
-        (trait_item name: (type_identifier) @trait.name)
-        
-        ; 实现块
-        (impl_item) @impl.declaration
-        ; TODO: Capture impl target type and trait if present
-        
-        ; 模块定义
-        (mod_item) @module.declaration
-        (mod_item name: (identifier) @module.name)
-        
-        ; 常量定义
-        (const_item) @const.declaration
-        (const_item name: (identifier) @const.name)
-        
-        ; 静态变量定义
-        (static_item) @static.declaration
-        (static_item name: (identifier) @static.name)
-        
-        ; 类型别名
-        (type_item) @type_alias.declaration
-        (type_item name: (type_identifier) @type_alias.name)
-        
-        ; 宏定义
-        (macro_definition) @macro.declaration
-        (macro_definition name: (identifier) @macro.name)
-        
-        ; 使用声明
-        (use_declaration) @use.declaration
 
-        ; 属性标记 (捕获整个属性)
-        (attribute_item) @attribute 
-        "#
-        .to_string()
+    /// 判断文件是否为 C/C++ 头文件
+    fn is_header_file(path: &Path) -> bool {
+        matches!(
+            path.extension().and_then(|s| s.to_str()).unwrap_or(""),
+            "h" | "hh" | "hpp" | "hxx"
+        )
     }
 
-    // Method to get Java query pattern (moved from the original monolithic file)
-    pub(crate) fn get_java_query_pattern(&self) -> String {
-        r#"
-        ; Class declarations
-        (class_declaration name: (identifier) @class.name) @class.declaration
-        
-        ; Interface declarations
-        (interface_declaration name: (identifier) @interface.name) @interface.declaration
-        
-        ; Enum declarations
-        (enum_declaration name: (identifier) @enum.name) @enum.declaration
-        
-        ; Method declarations
-        (method_declaration name: (identifier) @method.name) @method.declaration
-        
-        ; Constructor declarations
-        (constructor_declaration name: (identifier) @constructor.name) @constructor.declaration
-        
-        ; Field declarations (instance variables)
-        (field_declaration declarator: (variable_declarator name: (identifier) @field.name)) @field.declaration
-        
-        ; Static field declarations
-        (field_declaration (modifiers (marker_annotation name: (identifier) @annotation.name))? (type_identifier) (variable_declarator name: (identifier) @static.field.name)) @static.field.declaration
-        
-        ; Package declaration
-        (package_declaration (scoped_identifier) @package.name) @package.declaration
-        
-        ; Import statements
-        (import_declaration (scoped_identifier) @import.name) @import.declaration
-        (import_declaration (asterisk) @import.wildcard) @import.declaration
-        
-        ; Annotations
-        (marker_annotation name: (identifier) @annotation.name) @annotation.declaration
-        (annotation name: (identifier) @annotation.name) @annotation.declaration
-
-        ; Method invocations (useful for call graphs, but can be noisy)
-        ; (method_invocation name: (identifier) @method.call)
-
-        ; Object creation (constructors)
-        ; (object_creation_expression type: (type_identifier) @constructor.call)
-        "#
-        .to_string()
+    /// 返回解析文件所用的语法标识
+    ///
+    /// `detect_language` 将 JS/TS 文件统一标识为 "javascript"，
+    /// 但 `.ts` 和 `.tsx` 需要各自的 TypeScript 语法才能正确解析。
+    fn grammar_id(path: &Path, lang_id: &str) -> String {
+        let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("");
+        match (lang_id, extension) {
+            ("javascript", "ts") => "typescript".to_string(),
+            ("javascript", "tsx") => "tsx".to_string(),
+            _ => lang_id.to_string(),
+        }
     }
-
-
+    
+    /// 编译已加载语法的结构查询
+    ///
+    /// 查询由内置查询和用户、仓库查询目录中的 `.scm` 文件组合而成（见 [`super::queries`]）。
+    /// 自定义查询编译失败时记录警告并回退到内置查询。
     fn initialize_queries(&mut self) -> Result<(), TreeSitterError> {
-        if self.languages.contains_key("rust") {
-            let rust_query_pattern = self.get_rust_query_pattern();
-            let rust_lang = self.languages.get("rust").unwrap(); // Safe due to check
-            let query = Query::new(*rust_lang, &rust_query_pattern)
-                .map_err(|e| TreeSitterError::QueryError(format!("Rust query error: {}", e)))?;
-            self.queries.insert("rust".to_string(), query);
-        }
-        if self.languages.contains_key("java") {
-            let java_query_pattern = self.get_java_query_pattern();
-            let java_lang = self.languages.get("java").unwrap(); // Safe due to check
-            let query = Query::new(*java_lang, &java_query_pattern)
-                .map_err(|e| TreeSitterError::QueryError(format!("Java query error: {}", e)))?;
-            self.queries.insert("java".to_string(), query);
-        }
-        // Initialize queries for other languages...
+        self.queries.clear();
+        self.query_fingerprints.clear();
+        let mut grammars: Vec<&String> = self.languages.keys().collect();
+        grammars.sort();
+        for grammar in grammars {
+            let lang = self.languages[grammar];
+            let Some(query_source) = load_query_source(grammar, &query_dirs(grammar, &self.project_root)) else {
+                continue;
+            };
+            let query = match Query::new(lang, &query_source.source) {
+                Ok(query) => {
+                    self.query_fingerprints.insert(grammar.clone(), query_fingerprint(grammar, &query_source.source));
+                    query
+                }
+                Err(e) if !query_source.files.is_empty() => {
+                    let files: Vec<String> = query_source.files.iter().map(|f| f.display().to_string()).collect();
+                    warn!("自定义 {} 查询 ({}) 无效: {}，使用内置查询", grammar, files.join(", "), e);
+                    match builtin_query(grammar) {
+                        Some(builtin) => {
+                            self.query_fingerprints.insert(grammar.clone(), query_fingerprint(grammar, builtin));
+                            Query::new(lang, builtin)
+                                .map_err(|e| TreeSitterError::QueryError(format!("{} query error: {}", grammar, e)))?
+                        }
+                        None => continue,
+                    }
+                }
+                Err(e) => return Err(TreeSitterError::QueryError(format!("{} query error: {}", grammar, e))),
+            };
+            if !query_source.files.is_empty() {
+                debug!("{} 使用自定义查询文件: {:?}", grammar, query_source.files);
+            }
+            self.queries.insert(grammar.clone(), query);
+        }
         Ok(())
     }
 
     pub fn detect_language(&self, path: &Path) -> Result<Option<String>, TreeSitterError> {
         let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("");
+        // 动态库语法声明的扩展名优先于内置映射
+        if let Some(name) = self.runtime_grammar_for_extension(extension) {
+            return Ok(Some(name));
+        }
         match extension {
             "rs" => Ok(Some("rust".to_string())),
             "java" => Ok(Some("java".to_string())),
             "py" => Ok(Some("python".to_string())),
             "go" => Ok(Some("go".to_string())),
             "js" | "ts" | "jsx" | "tsx" => Ok(Some("javascript".to_string())), // Group JS/TS
+            "c" => Ok(Some("c".to_string())),
+            "cpp" | "cc" | "cxx" | "hpp" | "hh" | "hxx" | "h" => Ok(Some("cpp".to_string())),
             // 明确标识为非代码文件，不需要 tree-sitter 分析
             "md" | "markdown" | "txt" | "json" | "yml" | "yaml" | "toml" | "xml" | "html" | "css" | "svg" | "png" | "jpg" | "jpeg" | "gif" | "ico" => Ok(None),
             // 对于未知扩展名，返回 None 而不是错误，避免错误日志过多
@@ -356,6 +433,23 @@ impl TreeSitterAnalyzer {
         }
     }
     
+    /// 返回声明了该扩展名且已成功加载的动态库语法
+    fn runtime_grammar_for_extension(&self, extension: &str) -> Option<String> {
+        if extension.is_empty() {
+            return None;
+        }
+        let mut names: Vec<&String> = self.config.grammars
+            .iter()
+            .filter(|(name, grammar)| {
+                self.languages.contains_key(*name)
+                    && grammar.extensions.iter().any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(extension))
+            })
+            .map(|(name, _)| name)
+            .collect();
+        names.sort();
+        names.first().map(|name| name.to_string())
+    }
+
     fn is_cache_valid(&self, path: &Path, current_hash: &str) -> bool {
         if !self.config.cache_enabled {
             return false;
@@ -377,17 +471,31 @@ impl TreeSitterAnalyzer {
 
 
     pub fn parse_file(&mut self, file_path: &Path) -> Result<FileAst, TreeSitterError> {
+        let source_code = fs::read_to_string(file_path)
+            .map_err(|e| TreeSitterError::IoError(e))?;
+        self.parse_source(file_path, source_code)
+    }
+
+    /// Parses source code that does not (or not yet) live on disk, such as a blob
+    /// from another revision. `file_path` is used for language detection and caching.
+    pub fn parse_source(&mut self, file_path: &Path, source_code: String) -> Result<FileAst, TreeSitterError> {
+        let ast = self.parse_with(&mut Parser::new(), file_path, source_code, None)?;
+        self.cache_ast(ast.clone());
+        Ok(ast)
+    }
+
+    /// 使用给定的 `Parser` 解析源码，只读取内存缓存而不写入，可在工作线程中调用
+    ///
+    /// 超过 `deadline` 时中止解析并返回 [`TreeSitterError::AnalysisTimeout`]。
+    fn parse_with(&self, parser: &mut Parser, file_path: &Path, source_code: String, deadline: Option<Instant>) -> Result<FileAst, TreeSitterError> {
         let lang_id_opt = self.detect_language(file_path)?;
         let lang_id = lang_id_opt.ok_or_else(|| {
             TreeSitterError::UnsupportedLanguage(format!("Non-code file: {:?}", file_path))
         })?;
-        let language = self.languages.get(&lang_id).ok_or_else(|| {
+        let language = self.languages.get(&Self::grammar_id(file_path, &lang_id)).ok_or_else(|| {
             TreeSitterError::UnsupportedLanguage(format!("Language '{}' not initialized.", lang_id))
         })?;
 
-        let source_code = fs::read_to_string(file_path)
-            .map_err(|e| TreeSitterError::IoError(e))?;
-        
         let current_hash = calculate_hash(&source_code);
 
         if self.is_cache_valid(file_path, &current_hash) {
@@ -397,77 +505,148 @@ impl TreeSitterAnalyzer {
             }
         }
         
-        let mut parser = Parser::new();
         parser.set_language(*language)
             .map_err(|e| TreeSitterError::ParseError(format!("Failed to set language for parser: {}", e)))?;
+        // 超时为 0 表示不限制解析时间
+        Self::check_deadline(deadline, file_path)?;
+        let timeout = deadline.map_or(0, |d| d.saturating_duration_since(Instant::now()).as_micros().max(1) as u64);
+        parser.set_timeout_micros(timeout);
 
-        let tree = parser.parse(&source_code, None).ok_or_else(|| {
-            TreeSitterError::ParseError(format!("Failed to parse file: {:?}", file_path))
-        })?;
+        let Some(tree) = parser.parse(&source_code, None) else {
+            // 中止的解析会在下次调用时继续，复用 Parser 前需要重置
+            parser.reset();
+            return Err(match Self::check_deadline(deadline, file_path) {
+                Err(e) => e,
+                Ok(()) => TreeSitterError::ParseError(format!("Failed to parse file: {:?}", file_path)),
+            });
+        };
         
-        let ast = FileAst {
+        Ok(FileAst {
             path: file_path.to_path_buf(),
             tree,
             source: source_code,
             content_hash: current_hash,
             last_parsed: SystemTime::now(),
             language_id: lang_id,
-        };
+        })
+    }
 
-        if self.config.cache_enabled {
-            self.file_asts.insert(file_path.to_path_buf(), ast.clone());
-            debug!("Cached AST for {:?}", file_path);
+    /// 将解析结果放入内存中的语法树缓存
+    fn cache_ast(&mut self, ast: FileAst) {
+        if self.config.cache_enabled && !self.is_cache_valid(&ast.path, &ast.content_hash) {
+            debug!("Cached AST for {:?}", ast.path);
+            self.file_asts.insert(ast.path.clone(), ast);
+        }
+    }
+
+    /// 单个文件分析的截止时间，`file_timeout_ms` 为 0 时不限制
+    fn file_deadline(&self) -> Option<Instant> {
+        (self.config.file_timeout_ms > 0).then(|| Instant::now() + Duration::from_millis(self.config.file_timeout_ms))
+    }
+
+    /// 已超过截止时间时返回 [`TreeSitterError::AnalysisTimeout`]
+    fn check_deadline(deadline: Option<Instant>, path: &Path) -> Result<(), TreeSitterError> {
+        match deadline {
+            Some(deadline) if Instant::now() >= deadline => Err(TreeSitterError::AnalysisTimeout(format!(
+                "{} 的分析超过了 file_timeout_ms 限制",
+                path.display()
+            ))),
+            _ => Ok(()),
         }
-        
-        Ok(ast)
     }
 
-    // Placeholder for is_node_public - this needs the FileAst context
+    /// 判断节点是否属于文件所在模块的公共 API，规则由各语言实现
     pub fn is_node_public(&self, node: &tree_sitter::Node, file_ast: &FileAst) -> bool {
-        // Implementation depends on language-specific rules
-        // This is a simplified placeholder.
         match file_ast.language_id.as_str() {
-            "rust" => {
-                // Simplified: check for `pub` keyword in the node's S-expression or text
-                // A more robust way would be to traverse child nodes for `visibility_modifier`
-                // or check the parent `mod_item` or `impl_item` etc.
-                let node_sexp = node.to_sexp();
-                if node_sexp.starts_with("(visibility_modifier") && node.utf8_text(file_ast.source.as_bytes()).unwrap_or("").contains("pub") {
-                    return true;
-                }
-                // Check if it's a direct child of a `source_file` and doesn't have explicit private/crate visibility
-                // This is very simplified and likely incorrect for many Rust visibility rules.
-                let mut cursor = node.walk();
-                for child_node in node.children(&mut cursor) {
-                    if child_node.kind() == "visibility_modifier" {
-                        return child_node.utf8_text(file_ast.source.as_bytes()).unwrap_or("").contains("pub");
+            "rust" => is_rust_node_public_impl(node, file_ast),
+            "java" => is_java_node_public_impl(node, file_ast),
+            "python" => {
+                let all_exports = extract_python_all_exports(file_ast);
+                is_python_node_public_impl(node, file_ast, all_exports.as_deref())
+            }
+            "go" => is_go_node_public_impl(node, file_ast),
+            "c" | "cpp" => self.is_c_family_node_public(node, file_ast),
+            _ => false, // Default for other languages
+        }
+    }
+
+    /// 判断 C/C++ 节点是否属于公共 API
+    ///
+    /// 只有头文件中的声明才构成公共 API。`static` 声明、匿名命名空间以及
+    /// `detail`/`internal` 命名空间中的声明不公开；类成员按访问说明符判断，
+    /// `class` 默认私有，`struct` 和 `union` 默认公开。
+    fn is_c_family_node_public(&self, node: &tree_sitter::Node, file_ast: &FileAst) -> bool {
+        if !Self::is_header_file(&file_ast.path) {
+            return false;
+        }
+        let source = file_ast.source.as_bytes();
+        let mut in_class = false;
+        let mut current = *node;
+        while let Some(parent) = current.parent() {
+            match parent.kind() {
+                "field_declaration_list" => {
+                    in_class = true;
+                    // 向前查找最近的访问说明符
+                    let mut access = None;
+                    let mut sibling = current.prev_sibling();
+                    while let Some(s) = sibling {
+                        if s.kind() == "access_specifier" {
+                            access = s.utf8_text(source).ok().map(|t| t.trim().to_string());
+                            break;
+                        }
+                        sibling = s.prev_sibling();
                     }
-                }
-                // If no visibility modifier, default depends on context (e.g. in trait, it's public)
-                // This is a complex part of Rust's grammar.
-                false // Default to not public if unsure
-            }
-            "java" => {
-                // Check for `public` modifier
-                let mut cursor = node.walk();
-                for child_node in node.children(&mut cursor) {
-                    if child_node.kind() == "modifiers" {
-                        let modifiers_text = child_node.utf8_text(file_ast.source.as_bytes()).unwrap_or("");
-                        return modifiers_text.contains("public");
+                    let is_class = parent.parent().is_some_and(|p| p.kind() == "class_specifier");
+                    let public = match access.as_deref() {
+                        Some(a) => a.starts_with("public"),
+                        None => !is_class,
+                    };
+                    if !public {
+                        return false;
                     }
                 }
-                // Default to package-private if no explicit public modifier (for top-level types)
-                // or based on context for members.
-                false
+                "namespace_definition" => match parent.child_by_field_name("name") {
+                    None => return false,
+                    Some(name) => {
+                        if matches!(name.utf8_text(source).unwrap_or(""), "detail" | "internal" | "impl") {
+                            return false;
+                        }
+                    }
+                },
+                _ => {}
+            }
+            current = parent;
+        }
+
+        if !in_class {
+            let mut cursor = node.walk();
+            let is_static = node.children(&mut cursor).any(|child| {
+                child.kind() == "storage_class_specifier" && child.utf8_text(source).unwrap_or("") == "static"
+            });
+            if is_static {
+                return false;
             }
-            _ => false, // Default for other languages
         }
+        true
     }
     
     // analyze_diff, map_diff_to_ast, analyze_changes, etc. will go here
     // These are complex and will require careful porting from the original file.
 
     pub fn analyze_diff(&mut self, diff_text: &str) -> Result<DiffAnalysis, TreeSitterError> {
+        let root = self.project_root.clone();
+        self.analyze_diff_with_sources(diff_text, &|path| fs::read_to_string(root.join(path)).ok())
+    }
+
+    /// 分析 diff，变更后的文件内容由 `read_source` 提供，参数为 diff 中的相对路径
+    ///
+    /// [`Self::analyze_diff`] 读取工作区文件；评审历史提交或暂存区时工作区可能已经变化，
+    /// 调用方应从被评审的版本读取内容，否则 hunk 会对应到错误的代码行。
+    pub fn analyze_diff_with_sources(
+        &mut self,
+        diff_text: &str,
+        read_source: &(dyn Fn(&Path) -> Option<String> + Sync),
+    ) -> Result<DiffAnalysis, TreeSitterError> {
         // Parse the diff text to get structured representation
         let git_diff = super::core::parse_git_diff(diff_text)?;
 
@@ -486,143 +665,74 @@ impl TreeSitterAnalyzer {
         let mut other_changes = 0;
         
         // 记录语言特定变更
-        let mut java_changes = 0;
-        let mut rust_changes = 0;
-        let mut _python_changes = 0;
-        let mut _go_changes = 0;
-
-        for file_diff_info in &git_diff.changed_files {
-            match file_diff_info.change_type {
-                ChangeType::Added | ChangeType::Modified => {
-                    // For added/modified files, parse them
-                    // The path in FileDiff should be the new path
-                    let file_path = self.project_root.join(&file_diff_info.path);
-                    if !file_path.exists() {
-                        warn!("File {:?} mentioned in diff does not exist in project. Skipping.", file_path);
-                        continue;
-                    }
+        let mut language_changes = LanguageChangeCounts::default();
 
-                    // 首先检查文件类型是否支持 tree-sitter 分析
-                    match self.detect_language(&file_path) {
-                        Ok(Some(lang_id)) => {
-                            // 统计语言分布
-                            *language_counts.entry(lang_id.clone()).or_insert(0) += 1;
-                            
-                            // 记录各语言变更
-                            match lang_id.as_str() {
-                                "java" => java_changes += 1,
-                                "rust" => rust_changes += 1,
-                                "python" => _python_changes += 1,
-                                "go" => _go_changes += 1,
-                                _ => {}
-                            }
-                            
-                            // 支持的编程语言，继续 tree-sitter 分析
-                            match self.parse_file(&file_path) {
-                                Ok(file_ast) => {
-                                    // Analyze changes within this file based on hunks
-                                    let affected_nodes = self.analyze_file_changes(&file_ast, &file_diff_info.hunks)?;
-                                    total_affected_nodes += affected_nodes.len();
-                                    
-                                    // 统计不同类型的变更
-                                    for node in &affected_nodes {
-                                        match node.node_type.as_str() {
-                                            "function" | "test_function" => function_changes += 1,
-                                            "class" | "struct" | "enum" | "interface" | "type" | "class_structure" | "debuggable_struct" => type_changes += 1,
-                                            "method" | "overridden_method" | "api_endpoint" => method_changes += 1,
-                                            "trait" => interface_changes += 1,
-                                            _ => other_changes += 1,
-                                        }
-                                        
-                                        if let Some(change_type) = &node.change_type {
-                                            match change_type.as_str() {
-                                                "added" | "added_content" => total_additions += 1,
-                                                "deleted" => total_deletions += 1,
-                                                "modified" | "modified_with_deletion" => total_modifications += 1,
-                                                _ => {}
-                                            }
-                                        }
-                                    }
-                                    
-                                    // 根据文件语言和变更类型生成更有意义的摘要
-                                    let summary = match file_ast.language_id.as_str() {
-                                        "java" => self.generate_java_file_summary(&file_ast, &affected_nodes),
-                                        "rust" => self.generate_rust_file_summary(&file_ast, &affected_nodes),
-                                        _ => format!("文件 {} 被{}。影响了 {} 个代码结构。", 
-                                                file_ast.path.display(), 
-                                                match file_diff_info.change_type {
-                                                    ChangeType::Added => "新增",
-                                                    ChangeType::Modified => "修改",
-                                                    _ => "变更"
-                                                }, 
-                                                affected_nodes.len()),
-                                    };
-
-                                    file_analyses.push(FileAnalysis {
-                                        path: file_ast.path.clone(),
-                                        language: file_ast.language_id.clone(),
-                                        change_type: file_diff_info.change_type.clone(),
-                                        affected_nodes: affected_nodes.clone(),
-                                        summary: Some(summary),
-                                    });
-                                    
-                                    // 输出更详细的变更日志
-                                    for node in &affected_nodes {
-                                        if let Some(change_type) = &node.change_type {
-                                            debug!("变更: {} {} - {}", change_type, node.node_type, node.name);
-                                        }
-                                    }
-                                },
-                                Err(e) => {
-                                    error!("Failed to parse file {:?}: {:?}", file_path, e);
-                                    file_analyses.push(FileAnalysis {
-                                        path: file_path.clone(),
-                                        language: lang_id,
-                                        change_type: file_diff_info.change_type.clone(),
-                                        affected_nodes: Vec::new(),
-                                        summary: Some(format!("无法解析文件: {}", e)),
-                                    });
-                                }
-                            }
-                        },
-                        Ok(None) => {
-                            debug!("File {:?} has unsupported language. Using simple analysis.", file_path);
-                            // For unsupported languages, add a placeholder analysis
-                            file_analyses.push(FileAnalysis {
-                                path: file_path.clone(),
-                                language: "unknown".to_string(),
-                                change_type: file_diff_info.change_type.clone(),
-                                affected_nodes: Vec::new(),
-                                summary: Some(format!("不支持的文件类型，无法进行 tree-sitter 分析")),
-                            });
-                        },
-                        Err(e) => {
-                            error!("Error detecting language for file {:?}: {:?}", file_path, e);
-                            file_analyses.push(FileAnalysis {
-                                path: file_path.clone(),
-                                language: "error".to_string(),
-                                change_type: file_diff_info.change_type.clone(),
-                                affected_nodes: Vec::new(),
-                                summary: Some(format!("检测文件语言时出错: {}", e)),
-                            });
-                        }
+        // 整体删除和整体新增的定义，用于识别移动的代码
+        let mut removed_definitions = Vec::new();
+        let mut added_definitions = Vec::new();
+
+        // 各文件在工作线程中并行解析和分析，结果按 diff 中的顺序合并
+        for outcome in self.analyze_changed_files(&git_diff.changed_files, read_source) {
+            removed_definitions.extend(outcome.removed_definitions);
+            added_definitions.extend(outcome.added_definitions);
+            if let Some(ast) = outcome.parsed {
+                self.cache_ast(ast);
+            }
+            if let Some(lang_id) = outcome.language {
+                // 记录各语言变更
+                language_changes.record(&lang_id);
+                // 统计语言分布
+                *language_counts.entry(lang_id).or_insert(0) += 1;
+            }
+
+            let Some(analysis) = outcome.analysis else {
+                continue;
+            };
+            let affected_nodes = &analysis.affected_nodes;
+            total_affected_nodes += affected_nodes.len();
+            // 统计不同类型的变更
+            for node in affected_nodes {
+                match node.node_type.as_str() {
+                    "function" | "test_function" | "async_function" => function_changes += 1,
+                    "class" | "struct" | "enum" | "interface" | "type" | "class_structure" | "debuggable_struct" => type_changes += 1,
+                    "method" | "overridden_method" | "api_endpoint" | "property" => method_changes += 1,
+                    "trait" => interface_changes += 1,
+                    _ => other_changes += 1,
+                }
+                
+                if let Some(change_type) = &node.change_type {
+                    match change_type.as_str() {
+                        "added" | "added_content" => total_additions += 1,
+                        "deleted" => total_deletions += 1,
+                        "modified" | "modified_with_deletion" => total_modifications += 1,
+                        _ => {}
                     }
-                },
-                // For other change types like deletions, just add an analysis entry
-                _ => {
-                    file_analyses.push(FileAnalysis {
-                        path: file_diff_info.path.clone(),
-                        language: "unknown".to_string(), // We might not be able to determine language for deleted files
-                        change_type: file_diff_info.change_type.clone(),
-                        affected_nodes: Vec::new(),
-                        summary: Some(format!("文件被{}", match file_diff_info.change_type {
-                            ChangeType::Deleted => "删除",
-                            ChangeType::Renamed => "重命名",
-                            _ => "其他操作"
-                        })),
-                    });
+                    // 输出更详细的变更日志
+                    debug!("变更: {} {} - {}", change_type, node.node_type, node.name);
                 }
             }
+            file_analyses.push(analysis);
+        }
+
+        // 移动的代码不算作新增：将移动目标处的节点标记为 moved
+        let moved_code = detect_moved_code(&removed_definitions, &added_definitions);
+        for moved in &moved_code {
+            let Some(target) = &moved.new else {
+                continue;
+            };
+            let target_path = self.project_root.join(&target.path);
+            let nodes = file_analyses
+                .iter_mut()
+                .filter(|f| f.path == target_path)
+                .flat_map(|f| f.affected_nodes.iter_mut())
+                .filter(|n| n.line_range.0 + 1 == target.start_line);
+            for node in nodes {
+                if matches!(node.change_type.as_deref(), Some("added" | "added_content")) {
+                    total_additions -= 1;
+                }
+                node.change_type = Some("moved".to_string());
+            }
+            debug!("移动: {} {:?} -> {:?}", moved.node_type, moved.old, moved.new);
         }
 
         // 确定变更模式
@@ -632,8 +742,7 @@ impl TreeSitterAnalyzer {
             type_changes, 
             method_changes, 
             interface_changes,
-            java_changes,
-            rust_changes,
+            &language_changes,
             total_additions,
             total_deletions
         );
@@ -655,18 +764,23 @@ impl TreeSitterAnalyzer {
             for (lang, count) in &language_counts {
                 lang_parts.push(format!("{} ({}个文件)", lang, count));
             }
+            // HashMap 的遍历顺序不固定，排序后摘要才是确定的
+            lang_parts.sort();
             format!("涉及语言: {}", lang_parts.join(", "))
         } else {
             "未检测到支持的编程语言".to_string()
         };
         
-        let changes_summary = format!(
+        let mut changes_summary = format!(
             "变更统计: {}个新增, {}个删除, {}个修改，共影响{}个代码结构",
             total_additions, 
             total_deletions, 
             total_modifications,
             total_affected_nodes
         );
+        if !moved_code.is_empty() {
+            changes_summary.push_str(&format!("，{}个代码块被移动", moved_code.len()));
+        }
         
         let structure_summary = format!(
             "结构变更: {}个函数, {}个类型, {}个方法, {}个接口, {}个其他",
@@ -690,6 +804,10 @@ impl TreeSitterAnalyzer {
                     "Java特定变更"
                 } else if lang_change.starts_with("Rust") {
                     "Rust特定变更"
+                } else if lang_change.starts_with("Python") {
+                    "Python特定变更"
+                } else if lang_change.starts_with("Go") {
+                    "Go特定变更"
                 } else {
                     "特定语言变更"
                 }
@@ -726,21 +844,397 @@ impl TreeSitterAnalyzer {
             file_analyses,
             overall_summary,
             change_analysis,
+            moved_code,
+        })
+    }
+
+    /// 在工作线程池中分析各个变更文件，返回的结果与 `files` 的顺序一致
+    ///
+    /// 每个线程使用自己的 `Parser`，分析器的其余状态只读共享。线程数由 `analysis_threads`
+    /// 配置决定，为 0 时使用可用的 CPU 核数。
+    fn analyze_changed_files(
+        &self,
+        files: &[ChangedFile],
+        read_source: &(dyn Fn(&Path) -> Option<String> + Sync),
+    ) -> Vec<FileOutcome> {
+        let threads = match self.config.analysis_threads {
+            0 => std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            n => n,
+        }
+        .min(files.len());
+        if threads <= 1 {
+            let mut parser = Parser::new();
+            return files.iter().map(|file| self.analyze_changed_file(&mut parser, file, read_source)).collect();
+        }
+
+        debug!("使用 {} 个线程分析 {} 个变更文件", threads, files.len());
+        let next = AtomicUsize::new(0);
+        let mut outcomes: Vec<(usize, FileOutcome)> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut parser = Parser::new();
+                        let mut outcomes = Vec::new();
+                        loop {
+                            let index = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(file) = files.get(index) else {
+                                break;
+                            };
+                            outcomes.push((index, self.analyze_changed_file(&mut parser, file, read_source)));
+                        }
+                        outcomes
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+                .collect()
+        });
+        outcomes.sort_by_key(|(index, _)| *index);
+        outcomes.into_iter().map(|(_, outcome)| outcome).collect()
+    }
+
+    /// 分析单个变更文件，在工作线程中调用
+    ///
+    /// 每个文件有独立的时间预算（`file_timeout_ms`），超时的文件记录为无法分析，不影响其他文件。
+    fn analyze_changed_file(
+        &self,
+        parser: &mut Parser,
+        file_diff_info: &ChangedFile,
+        read_source: &(dyn Fn(&Path) -> Option<String> + Sync),
+    ) -> FileOutcome {
+        let deadline = self.file_deadline();
+        let mut analysis = FileAnalysis {
+            path: file_diff_info.path.clone(),
+            language: "unknown".to_string(),
+            change_type: file_diff_info.change_type.clone(),
+            affected_nodes: Vec::new(),
+            summary: None,
+        };
+        let mut outcome = FileOutcome {
+            analysis: None,
+            language: None,
+            removed_definitions: self.removed_definitions(parser, file_diff_info, deadline),
+            added_definitions: Vec::new(),
+            parsed: None,
+        };
+
+        match file_diff_info.change_type {
+            ChangeType::Added | ChangeType::Modified => {
+                // For added/modified files, parse them
+                // The path in FileDiff should be the new path
+                let file_path = self.project_root.join(&file_diff_info.path);
+                analysis.path = file_path.clone();
+                let Some(source) = read_source(&file_diff_info.path) else {
+                    warn!("File {:?} mentioned in diff does not exist in project. Skipping.", file_path);
+                    return outcome;
+                };
+
+                // 首先检查文件类型是否支持 tree-sitter 分析
+                match self.detect_language(&file_path) {
+                    Ok(Some(lang_id)) => {
+                        outcome.language = Some(lang_id.clone());
+                        analysis.language = lang_id;
+
+                        // 支持的编程语言，继续 tree-sitter 分析
+                        match self.load_file_structure(parser, &file_path, source, deadline) {
+                            Ok((structure, parsed)) => {
+                                // Analyze changes within this file based on hunks
+                                let affected_nodes = Self::nodes_in_hunks(&structure.nodes, &structure.source, &file_diff_info.hunks);
+                                outcome.added_definitions = Self::added_definitions(&structure.definitions, file_diff_info);
+
+                                // 根据文件语言和变更类型生成更有意义的摘要
+                                let summary = match structure.language_id.as_str() {
+                                    "java" => self.generate_java_file_summary(&structure.path, &affected_nodes),
+                                    "rust" => self.generate_rust_file_summary(&structure.path, &affected_nodes),
+                                    "python" => self.generate_python_file_summary(&structure.path, &affected_nodes),
+                                    "go" => self.generate_go_file_summary(&structure.path, &affected_nodes),
+                                    "c" | "cpp" => self.generate_c_family_file_summary(&structure.path, &affected_nodes),
+                                    _ => format!("文件 {} 被{}。影响了 {} 个代码结构。", 
+                                            structure.path.display(), 
+                                            match file_diff_info.change_type {
+                                                ChangeType::Added => "新增",
+                                                ChangeType::Modified => "修改",
+                                                _ => "变更"
+                                            }, 
+                                            affected_nodes.len()),
+                                };
+
+                                analysis.affected_nodes = affected_nodes;
+                                analysis.summary = Some(summary);
+                                outcome.parsed = parsed;
+                            },
+                            Err(e @ TreeSitterError::AnalysisTimeout(_)) => {
+                                warn!("{}", e);
+                                analysis.summary = Some(format!("分析超时，已跳过: {}", e));
+                            },
+                            Err(e) => {
+                                error!("Failed to parse file {:?}: {:?}", file_path, e);
+                                analysis.summary = Some(format!("无法解析文件: {}", e));
+                            }
+                        }
+                    },
+                    Ok(None) => {
+                        debug!("File {:?} has unsupported language. Using simple analysis.", file_path);
+                        // For unsupported languages, add a placeholder analysis
+                        analysis.summary = Some("不支持的文件类型，无法进行 tree-sitter 分析".to_string());
+                    },
+                    Err(e) => {
+                        error!("Error detecting language for file {:?}: {:?}", file_path, e);
+                        analysis.language = "error".to_string();
+                        analysis.summary = Some(format!("检测文件语言时出错: {}", e));
+                    }
+                }
+            },
+            // For other change types like deletions, just add an analysis entry
+            // We might not be able to determine language for deleted files
+            _ => {
+                analysis.summary = Some(format!("文件被{}", match file_diff_info.change_type {
+                    ChangeType::Deleted => "删除",
+                    ChangeType::Renamed => "重命名",
+                    _ => "其他操作"
+                }));
+            }
+        }
+        outcome.analysis = Some(analysis);
+        outcome
+    }
+
+    /// 从 diff 中被删除的行解析出整体删除的定义
+    ///
+    /// 每个 hunk 的上下文行和删除行组成变更前的片段，单独解析后只保留完全由删除行组成的定义。
+    fn removed_definitions(&self, parser: &mut Parser, file: &ChangedFile, deadline: Option<Instant>) -> Vec<Definition> {
+        let path = file.old_path.clone().unwrap_or_else(|| file.path.clone());
+        if !matches!(self.detect_language(&path), Ok(Some(_))) {
+            return Vec::new();
+        }
+        let mut removed = Vec::new();
+        for hunk in &file.hunks {
+            let (fragment, removed_lines) = hunk.old_fragment();
+            if removed_lines.is_empty() {
+                continue;
+            }
+            let definitions = match self.parse_definitions(parser, &path, fragment, deadline) {
+                Ok(definitions) => definitions,
+                Err(e) => {
+                    debug!("无法解析 {} 中删除的代码: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let offset = hunk.old_range.start.saturating_sub(1);
+            let lines: HashSet<usize> = removed_lines.into_iter().collect();
+            for mut definition in definitions_within_lines(definitions, &lines) {
+                definition.start_line += offset;
+                definition.end_line += offset;
+                removed.push(definition);
+            }
+        }
+        removed
+    }
+
+    /// 变更后文件中完全由新增行组成的定义，路径为 diff 中的相对路径
+    fn added_definitions(definitions: &[Definition], file: &ChangedFile) -> Vec<Definition> {
+        let lines: HashSet<usize> = file.hunks.iter().flat_map(|hunk| hunk.added_line_numbers()).collect();
+        let mut added = definitions_within_lines(definitions.to_vec(), &lines);
+        for definition in &mut added {
+            definition.path = file.path.clone();
+        }
+        added
+    }
+
+    /// 提取变更后文件内容中的结构节点和定义；内容未变的文件直接使用持久化缓存
+    ///
+    /// 同时返回本次解析的语法树，使用缓存时为 None。
+    fn load_file_structure(&self, parser: &mut Parser, file_path: &Path, source: String, deadline: Option<Instant>) -> Result<(FileStructure, Option<FileAst>), TreeSitterError> {
+        let cached = self.cache_entry(file_path, &source);
+        if let Some((_, entry)) = &cached
+            && let (Some(nodes), Some(definitions)) = (&entry.structure, &entry.definitions)
+            && let Ok(Some(language_id)) = self.detect_language(file_path)
+        {
+            debug!("使用 {:?} 的分析缓存", file_path);
+            let structure = FileStructure {
+                path: file_path.to_path_buf(),
+                language_id,
+                source,
+                nodes: nodes.clone(),
+                definitions: definitions.clone(),
+            };
+            return Ok((structure, None));
+        }
+
+        let file_ast = self.parse_with(parser, file_path, source, deadline)?;
+        let nodes = self.analyze_file_structure(&file_ast)?;
+        Self::check_deadline(deadline, file_path)?;
+        let definitions = self.structure_query(&file_ast)
+            .map(|query| extract_definitions(&file_ast, query))
+            .unwrap_or_default();
+        Self::check_deadline(deadline, file_path)?;
+        if let Some((key, mut entry)) = cached {
+            entry.structure = Some(nodes.clone());
+            entry.definitions = Some(definitions.clone());
+            self.store_cache_entry(&key, &entry);
+        }
+        let structure = FileStructure {
+            path: file_ast.path.clone(),
+            language_id: file_ast.language_id.clone(),
+            source: file_ast.source.clone(),
+            nodes,
+            definitions,
+        };
+        Ok((structure, Some(file_ast)))
+    }
+
+    /// 文件内容对应的持久化缓存条目及其缓存键，条目中尚未缓存的部分为 None
+    ///
+    /// 未启用缓存或语言没有结构查询时返回 None。缓存的定义和 API 路径替换为 `path`。
+    fn cache_entry(&self, path: &Path, source: &str) -> Option<(String, CacheEntry)> {
+        let cache = self.disk_cache.as_ref()?;
+        let lang_id = self.detect_language(path).ok()??;
+        let fingerprint = self.query_fingerprints.get(&Self::grammar_id(path, &lang_id))?;
+        let relative_path = path.strip_prefix(&self.project_root).unwrap_or(path);
+        let key = content_key(source);
+        let mut entry = cache
+            .load(&key, fingerprint, relative_path, source)
+            .unwrap_or_else(|| CacheEntry::new(fingerprint, relative_path));
+        for definition in entry.definitions.iter_mut().flatten() {
+            definition.path = path.to_path_buf();
+        }
+        for item in entry.api.iter_mut().flatten() {
+            item.path = path.to_path_buf();
+        }
+        Some((key, entry))
+    }
+
+    fn store_cache_entry(&self, key: &str, entry: &CacheEntry) {
+        if let Some(cache) = &self.disk_cache {
+            cache.store(key, entry);
+        }
+    }
+
+    /// 比较文件变更前后两个版本的语法树，精确列出新增、删除、修改、重命名和移动的定义
+    ///
+    /// 与 [`Self::analyze_diff`] 不同，两个版本的内容都由调用方提供（例如 `git show <rev>:<path>`
+    /// 或暂存区中的内容），不读取工作区文件。不支持或无法解析的文件记录在 `skipped` 中。
+    pub fn diff_file_versions(&mut self, files: &[FileVersions]) -> AstDiff {
+        let mut diff = AstDiff::default();
+        let mut definitions = Vec::new();
+        for file in files {
+            let old = self.extract_version_definitions(file.old_path.as_deref(), file.old_source.as_ref());
+            let new = self.extract_version_definitions(file.new_path.as_deref(), file.new_source.as_ref());
+            match (old, new) {
+                (Ok(old), Ok(new)) => definitions.push(FileDefinitions { old, new }),
+                (Err(e), _) | (_, Err(e)) => {
+                    let path = file.new_path.clone().or_else(|| file.old_path.clone()).unwrap_or_default();
+                    debug!("跳过 {} 的语法树比较: {}", path.display(), e);
+                    diff.skipped.push(path);
+                }
+            }
+        }
+        diff.changes = diff_definitions(&definitions);
+        diff
+    }
+
+    /// 比较变更前后两个版本的公共 API，并把其余定义级变更计为内部变更
+    pub fn diff_public_api(&mut self, files: &[FileVersions]) -> ApiReport {
+        let mut definitions = Vec::new();
+        let mut apis = Vec::new();
+        let mut skipped = Vec::new();
+        for file in files {
+            let old = self.extract_version_api(file.old_path.as_deref(), file.old_source.as_ref());
+            let new = self.extract_version_api(file.new_path.as_deref(), file.new_source.as_ref());
+            match (old, new) {
+                (Ok((old_definitions, old_api)), Ok((new_definitions, new_api))) => {
+                    definitions.push(FileDefinitions { old: old_definitions, new: new_definitions });
+                    apis.push(FileApi { old: old_api, new: new_api });
+                }
+                (Err(e), _) | (_, Err(e)) => {
+                    let path = file.new_path.clone().or_else(|| file.old_path.clone()).unwrap_or_default();
+                    debug!("跳过 {} 的公共 API 比较: {}", path.display(), e);
+                    skipped.push(path);
+                }
+            }
+        }
+        let mut report = diff_api(&apis, &diff_definitions(&definitions));
+        report.skipped = skipped;
+        report
+    }
+
+    /// 解析文件的一个版本并提取其中的定义，文件在该版本中不存在时返回空列表
+    fn extract_version_definitions(&mut self, path: Option<&Path>, source: Option<&String>) -> Result<Vec<Definition>, TreeSitterError> {
+        let (Some(path), Some(source)) = (path, source) else {
+            return Ok(Vec::new());
+        };
+        Ok(self.extract_version(path, source, false)?.0)
+    }
+
+    /// 解析文件的一个版本，提取其中的定义和公共 API
+    fn extract_version_api(&mut self, path: Option<&Path>, source: Option<&String>) -> Result<(Vec<Definition>, Vec<ApiItem>), TreeSitterError> {
+        let (Some(path), Some(source)) = (path, source) else {
+            return Ok((Vec::new(), Vec::new()));
+        };
+        let (definitions, items) = self.extract_version(path, source, true)?;
+        Ok((definitions, items.unwrap_or_default()))
+    }
+
+    /// 提取文件一个版本中的定义，`with_api` 时同时提取公共 API；内容未变的版本直接使用持久化缓存
+    fn extract_version(&mut self, path: &Path, source: &str, with_api: bool) -> Result<(Vec<Definition>, Option<Vec<ApiItem>>), TreeSitterError> {
+        let cached = self.cache_entry(path, source);
+        if let Some((_, entry)) = &cached
+            && let Some(definitions) = &entry.definitions
+            && (!with_api || entry.api.is_some())
+        {
+            return Ok((definitions.clone(), entry.api.clone()));
+        }
+
+        let file_ast = self.parse_source(path, source.to_string())?;
+        let definitions = extract_definitions(&file_ast, self.structure_query(&file_ast)?);
+        let items = with_api.then(|| extract_api_items(&file_ast, &definitions, &|node| self.is_node_public(node, &file_ast)));
+        if let Some((key, mut entry)) = cached {
+            entry.definitions = Some(definitions.clone());
+            if items.is_some() {
+                entry.api = items.clone();
+            }
+            self.store_cache_entry(&key, &entry);
+        }
+        Ok((definitions, items))
+    }
+
+    /// 解析代码片段并提取其中的定义，不使用持久化缓存
+    fn parse_definitions(&self, parser: &mut Parser, path: &Path, source: String, deadline: Option<Instant>) -> Result<Vec<Definition>, TreeSitterError> {
+        let file_ast = self.parse_with(parser, path, source, deadline)?;
+        Ok(extract_definitions(&file_ast, self.structure_query(&file_ast)?))
+    }
+
+    /// 文件语言对应的结构查询
+    fn structure_query(&self, file_ast: &FileAst) -> Result<&Query, TreeSitterError> {
+        self.queries.get(&Self::grammar_id(&file_ast.path, &file_ast.language_id)).ok_or_else(|| {
+            TreeSitterError::UnsupportedLanguage(format!("No structure query for '{}'", file_ast.language_id))
         })
     }
 
-    fn analyze_file_changes(&self, file_ast: &FileAst, hunks: &[DiffHunk]) -> Result<Vec<AffectedNode>, TreeSitterError> {
+    /// 文件的结构节点（未关联具体变更），按语言细分节点类型
+    fn analyze_file_structure(&self, file_ast: &FileAst) -> Result<Vec<AffectedNode>, TreeSitterError> {
         // 根据文件语言调用相应的分析方法
         match file_ast.language_id.as_str() {
-            "java" => self.analyze_java_file_changes(file_ast, hunks),
-            "rust" => self.analyze_rust_file_changes(file_ast, hunks),
+            "java" => self.analyze_java_file_structure(file_ast),
+            "rust" => self.analyze_rust_file_structure(file_ast),
+            "python" => self.analyze_python_file_structure(file_ast),
+            "go" => self.analyze_go_file_structure(file_ast),
             // 其他语言可以在这里添加
-            _ => self.analyze_generic_file_changes(file_ast, hunks),
+            _ => {
+                // 动态库语法没有内置查询，需要在查询目录中提供 .scm 文件
+                if !self.queries.contains_key(&Self::grammar_id(&file_ast.path, &file_ast.language_id)) {
+                    warn!("语言 {} 没有可用的结构查询，请在 queries/{}/ 目录中添加 .scm 文件", file_ast.language_id, file_ast.language_id);
+                    return Ok(Vec::new());
+                }
+                self.analyze_generic_file_structure(file_ast)
+            }
         }
     }
     
-    fn analyze_java_file_changes(&self, file_ast: &FileAst, hunks: &[DiffHunk]) -> Result<Vec<AffectedNode>, TreeSitterError> {
-        let mut affected_nodes = self.analyze_generic_file_changes(file_ast, hunks)?;
+    fn analyze_java_file_structure(&self, file_ast: &FileAst) -> Result<Vec<AffectedNode>, TreeSitterError> {
+        let mut affected_nodes = self.analyze_generic_file_structure(file_ast)?;
         
         // Java 特定分析逻辑
         let source_bytes = file_ast.source.as_bytes();
@@ -786,8 +1280,8 @@ impl TreeSitterAnalyzer {
         Ok(affected_nodes)
     }
     
-    fn analyze_rust_file_changes(&self, file_ast: &FileAst, hunks: &[DiffHunk]) -> Result<Vec<AffectedNode>, TreeSitterError> {
-        let mut affected_nodes = self.analyze_generic_file_changes(file_ast, hunks)?;
+    fn analyze_rust_file_structure(&self, file_ast: &FileAst) -> Result<Vec<AffectedNode>, TreeSitterError> {
+        let mut affected_nodes = self.analyze_generic_file_structure(file_ast)?;
         
         // Rust 特定分析逻辑
         let source_bytes = file_ast.source.as_bytes();
@@ -826,8 +1320,100 @@ impl TreeSitterAnalyzer {
         Ok(affected_nodes)
     }
     
-    fn generate_java_file_summary(&self, file_ast: &FileAst, affected_nodes: &[AffectedNode]) -> String {
-        let _source_bytes = file_ast.source.as_bytes(); // Keep but mark as unused
+    fn analyze_python_file_structure(&self, file_ast: &FileAst) -> Result<Vec<AffectedNode>, TreeSitterError> {
+        let mut affected_nodes = self.analyze_generic_file_structure(file_ast)?;
+
+        // 带装饰器的定义同时被 function/class 和 decorated 捕获，只保留包含装饰器行的节点
+        let decorated: Vec<(usize, usize, String)> = affected_nodes
+            .iter()
+            .filter(|n| n.node_type == "decorated")
+            .map(|n| (n.range.0, n.range.1, n.name.clone()))
+            .collect();
+        affected_nodes.retain(|n| {
+            !(matches!(n.node_type.as_str(), "function" | "class")
+                && decorated.iter().any(|(start, end, name)| *start <= n.range.0 && n.range.1 <= *end && *name == n.name))
+        });
+
+        let is_test_file = file_ast.path.file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with("test_") || n.ends_with("_test.py"));
+
+        // Python 特定分析逻辑
+        for node in &mut affected_nodes {
+            if !matches!(node.node_type.as_str(), "function" | "class" | "decorated") {
+                continue;
+            }
+            let Some(definition) = python_definition_at(file_ast, node.range) else {
+                continue;
+            };
+            if definition.kind() == "class_definition" {
+                node.node_type = "class".to_string();
+                continue;
+            }
+
+            let decorators = python_decorators(node);
+            let in_class = definition.parent()
+                .and_then(|body| body.parent())
+                .is_some_and(|p| p.kind() == "class_definition");
+            let is_async = definition.utf8_text(file_ast.source.as_bytes())
+                .is_ok_and(|text| text.starts_with("async"));
+
+            node.node_type = if is_test_file && node.name.starts_with("test") {
+                "test_function"
+            } else if decorators.iter().any(|d| is_python_route_decorator(d)) {
+                "api_endpoint"
+            } else if decorators.iter().any(|d| d == "property" || d.ends_with(".setter")) {
+                "property"
+            } else if is_async {
+                "async_function"
+            } else if in_class {
+                "method"
+            } else {
+                "function"
+            }
+            .to_string();
+        }
+
+        Ok(affected_nodes)
+    }
+
+    fn analyze_go_file_structure(&self, file_ast: &FileAst) -> Result<Vec<AffectedNode>, TreeSitterError> {
+        let mut affected_nodes = self.analyze_generic_file_structure(file_ast)?;
+
+        // Go 特定分析逻辑
+        let source_bytes = file_ast.source.as_bytes();
+        let root = file_ast.tree.root_node();
+        let is_test_file = file_ast.path.to_string_lossy().ends_with("_test.go");
+
+        for node in &mut affected_nodes {
+            let Some(tree_node) = root.descendant_for_byte_range(node.range.0, node.range.1) else {
+                continue;
+            };
+            match node.node_type.as_str() {
+                // 类型声明细分为结构体、接口和其他类型
+                "type" => node.node_type = go_type_kind(tree_node).to_string(),
+                // 方法名带上接收者类型，便于区分不同类型的同名方法
+                "method" => {
+                    if let Some(receiver) = go_receiver_type(tree_node, source_bytes) {
+                        node.name = format!("{}.{}", receiver, node.name);
+                    }
+                }
+                "function" => {
+                    let declaration = go_declaration_node(tree_node);
+                    if is_test_file && declaration.kind() == "function_declaration"
+                        && ["Test", "Benchmark", "Example", "Fuzz"].iter().any(|p| node.name.starts_with(p))
+                    {
+                        node.node_type = "test_function".to_string();
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(affected_nodes)
+    }
+
+    fn generate_java_file_summary(&self, path: &Path, affected_nodes: &[AffectedNode]) -> String {
         // 统计各类型节点数量
         let mut class_count = 0;
         let mut method_count = 0;
@@ -863,7 +1449,7 @@ impl TreeSitterAnalyzer {
         }
         
         // 生成摘要
-        let mut summary = format!("Java文件 {} 变更分析: ", file_ast.path.display());
+        let mut summary = format!("Java文件 {} 变更分析: ", path.display());
         
         if affected_nodes.is_empty() {
             return format!("{}未检测到结构性变更", summary);
@@ -910,8 +1496,141 @@ impl TreeSitterAnalyzer {
         summary
     }
     
-    fn generate_rust_file_summary(&self, file_ast: &FileAst, affected_nodes: &[AffectedNode]) -> String {
-        let _source_bytes = file_ast.source.as_bytes(); // Keep but mark as unused
+    fn generate_c_family_file_summary(&self, path: &Path, affected_nodes: &[AffectedNode]) -> String {
+        let is_header = Self::is_header_file(path);
+        let mut summary = format!(
+            "C/C++{} {} 变更分析: ",
+            if is_header { "头文件" } else { "源文件" },
+            path.display()
+        );
+
+        if affected_nodes.is_empty() {
+            return format!("{}未检测到结构性变更", summary);
+        }
+
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for node in affected_nodes {
+            let label = match node.node_type.as_str() {
+                "function" => "函数",
+                "method" => "方法",
+                "class" => "类",
+                "struct" => "结构体",
+                "enum" => "枚举",
+                "type" => "类型定义",
+                "namespace" => "命名空间",
+                "template" => "模板",
+                "macro" => "宏",
+                _ => continue,
+            };
+            match counts.iter_mut().find(|(l, _)| *l == label) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((label, 1)),
+            }
+        }
+        if !counts.is_empty() {
+            let parts: Vec<String> = counts.iter().map(|(label, count)| format!("{}个{}", count, label)).collect();
+            summary.push_str(&format!("影响了{}", parts.join("、")));
+        }
+
+        // 头文件中公开声明的变更即公共 API 变更
+        let mut api_names: Vec<&str> = affected_nodes
+            .iter()
+            .filter(|n| n.is_public && n.node_type != "include")
+            .map(|n| n.name.as_str())
+            .collect();
+        api_names.dedup();
+        if !api_names.is_empty() {
+            summary.push_str(&format!("。公共 API 变更: {}", api_names.join(", ")));
+        }
+
+        summary
+    }
+
+    /// 按显示标签统计受影响节点数量，保持标签首次出现的顺序
+    fn count_node_labels(affected_nodes: &[AffectedNode], label_of: impl Fn(&str) -> Option<&'static str>) -> String {
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for node in affected_nodes {
+            let Some(label) = label_of(node.node_type.as_str()) else {
+                continue;
+            };
+            match counts.iter_mut().find(|(l, _)| *l == label) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((label, 1)),
+            }
+        }
+        counts.iter().map(|(label, count)| format!("{}个{}", count, label)).collect::<Vec<_>>().join("、")
+    }
+
+    /// 返回受影响节点中公开项的名称（去重，保持顺序）
+    fn public_node_names(affected_nodes: &[AffectedNode]) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for node in affected_nodes.iter().filter(|n| n.is_public && n.node_type != "import") {
+            if !names.contains(&node.name.as_str()) {
+                names.push(node.name.as_str());
+            }
+        }
+        names
+    }
+
+    fn generate_python_file_summary(&self, path: &Path, affected_nodes: &[AffectedNode]) -> String {
+        let mut summary = format!("Python文件 {} 变更分析: ", path.display());
+        if affected_nodes.is_empty() {
+            return format!("{}未检测到结构性变更", summary);
+        }
+
+        let structure_summary = Self::count_node_labels(affected_nodes, |node_type| match node_type {
+            "function" => Some("函数"),
+            "async_function" => Some("异步函数"),
+            "method" => Some("方法"),
+            "property" => Some("属性"),
+            "api_endpoint" => Some("路由"),
+            "class" => Some("类"),
+            "test_function" => Some("测试函数"),
+            _ => None,
+        });
+        if !structure_summary.is_empty() {
+            summary.push_str(&format!("影响了{}", structure_summary));
+        }
+        if affected_nodes.iter().any(|n| n.node_type == "public_api") {
+            summary.push_str("。修改了 __all__ 导出列表");
+        }
+
+        let api_names = Self::public_node_names(affected_nodes);
+        if !api_names.is_empty() {
+            summary.push_str(&format!("。公共 API 变更: {}", api_names.join(", ")));
+        }
+        summary
+    }
+
+    fn generate_go_file_summary(&self, path: &Path, affected_nodes: &[AffectedNode]) -> String {
+        let mut summary = format!("Go文件 {} 变更分析: ", path.display());
+        if affected_nodes.is_empty() {
+            return format!("{}未检测到结构性变更", summary);
+        }
+
+        let structure_summary = Self::count_node_labels(affected_nodes, |node_type| match node_type {
+            "function" => Some("函数"),
+            "method" => Some("方法"),
+            "struct" => Some("结构体"),
+            "interface" => Some("接口"),
+            "type" => Some("类型"),
+            "const" => Some("常量"),
+            "var" => Some("变量"),
+            "test_function" => Some("测试函数"),
+            _ => None,
+        });
+        if !structure_summary.is_empty() {
+            summary.push_str(&format!("影响了{}", structure_summary));
+        }
+
+        let api_names = Self::public_node_names(affected_nodes);
+        if !api_names.is_empty() {
+            summary.push_str(&format!("。导出 API 变更: {}", api_names.join(", ")));
+        }
+        summary
+    }
+
+    fn generate_rust_file_summary(&self, path: &Path, affected_nodes: &[AffectedNode]) -> String {
         // 统计各类型节点数量
         let mut struct_count = 0;
         let mut enum_count = 0;
@@ -954,7 +1673,7 @@ impl TreeSitterAnalyzer {
         }
         
         // 生成摘要
-        let mut summary = format!("Rust文件 {} 变更分析: ", file_ast.path.display());
+        let mut summary = format!("Rust文件 {} 变更分析: ", path.display());
         
         if affected_nodes.is_empty() {
             return format!("{}未检测到结构性变更", summary);
@@ -1010,11 +1729,16 @@ impl TreeSitterAnalyzer {
         type_changes: usize,
         method_changes: usize,
         interface_changes: usize,
-        java_changes: usize,
-        rust_changes: usize,
+        language_changes: &LanguageChangeCounts,
         total_additions: usize,
         total_deletions: usize
     ) -> ChangePattern {
+        let LanguageChangeCounts {
+            java: java_changes,
+            rust: rust_changes,
+            python: python_changes,
+            go: go_changes,
+        } = *language_changes;
         // 如果变更主要是Java特定的
         if java_changes > 0 && java_changes > rust_changes {
             // 检查是否有SpringBoot或JPA相关的变更
@@ -1068,6 +1792,38 @@ impl TreeSitterAnalyzer {
                 return ChangePattern::LanguageSpecificChange("RustMacroChange".to_string());
             }
         }
+
+        let has_node = |language: &str, predicate: &dyn Fn(&AffectedNode) -> bool| {
+            file_analyses.iter().any(|analysis| {
+                analysis.language == language && analysis.affected_nodes.iter().any(predicate)
+            })
+        };
+
+        // 如果变更主要是Python特定的
+        if python_changes > 0 && python_changes > java_changes.max(rust_changes).max(go_changes) {
+            if has_node("python", &|node| node.node_type == "public_api") {
+                return python::to_generic_change_pattern(PythonChangePattern::PublicApi);
+            } else if has_node("python", &|node| matches!(node.node_type.as_str(), "decorated" | "api_endpoint" | "property")) {
+                return python::to_generic_change_pattern(PythonChangePattern::Decorator);
+            } else if has_node("python", &|node| node.node_type == "async_function") {
+                return python::to_generic_change_pattern(PythonChangePattern::Async);
+            } else if has_node("python", &|node| node.node_type == "class" && node.is_public) {
+                return python::to_generic_change_pattern(PythonChangePattern::ClassStructure);
+            }
+        }
+
+        // 如果变更主要是Go特定的
+        if go_changes > 0 && go_changes > java_changes.max(rust_changes).max(python_changes) {
+            if has_node("go", &|node| node.node_type == "interface") {
+                return go::to_generic_change_pattern(GoChangePattern::Interface);
+            } else if has_node("go", &|node| node.node_type == "method" && node.is_public) {
+                return go::to_generic_change_pattern(GoChangePattern::MethodSet);
+            } else if has_node("go", &|node| node.node_type == "struct") {
+                return go::to_generic_change_pattern(GoChangePattern::Struct);
+            } else if has_node("go", &|node| node.is_public && node.node_type != "import") {
+                return go::to_generic_change_pattern(GoChangePattern::ExportedApi);
+            }
+        }
         
         // 通用变更模式判断
         let config_changes = file_analyses.iter().any(|analysis| {
@@ -1174,14 +1930,60 @@ impl TreeSitterAnalyzer {
         ChangeScope::Minor
     }
     
-    fn analyze_generic_file_changes(&self, file_ast: &FileAst, hunks: &[DiffHunk]) -> Result<Vec<AffectedNode>, TreeSitterError> {
-        let mut affected_nodes = Vec::new();
-        let query = self.queries.get(&file_ast.language_id).ok_or_else(|| 
+    /// 文件中被结构查询捕获的全部节点，与具体变更无关，可以按文件内容缓存
+    fn analyze_generic_file_structure(&self, file_ast: &FileAst) -> Result<Vec<AffectedNode>, TreeSitterError> {
+        let mut nodes = Vec::new();
+        let query = self.queries.get(&Self::grammar_id(&file_ast.path, &file_ast.language_id)).ok_or_else(|| 
             TreeSitterError::QueryError(format!("No query found for language {}", file_ast.language_id))
         )?;
 
         let source_bytes = file_ast.source.as_bytes();
         let tree_root = file_ast.tree.root_node();
+        let mut cursor = tree_sitter::QueryCursor::new();
+        let matches = cursor.matches(query, tree_root, source_bytes);
+
+        for m in matches {
+            for capture in m.captures {
+                let node = capture.node;
+                let node_range = node.byte_range();
+                let node_name_capture = m.captures.iter().find(|c| 
+                    query.capture_names()[c.index as usize].ends_with(".name")
+                );
+                
+                let name: String = node_name_capture
+                    .map(|c| c.node.utf8_text(source_bytes).unwrap_or("").to_string())
+                    .unwrap_or_else(|| "unknown".to_string());
+
+                let kind_capture_index = query.capture_names()[capture.index as usize]
+                    .split('.')
+                    .next()
+                    .unwrap_or("unknown_type");
+
+                nodes.push(AffectedNode {
+                    node_type: kind_capture_index.to_string(),
+                    name,
+                    range: (node_range.start, node_range.end),
+                    is_public: self.is_node_public(&node, file_ast),
+                    content: Some(node.utf8_text(source_bytes).unwrap_or("").to_string()),
+                    line_range: (node.start_position().row, node.end_position().row),
+                    change_type: None,
+                    additions: None,
+                    deletions: None,
+                });
+            }
+        }
+        // Deduplicate nodes captured by several patterns (by node range and type)
+        nodes.sort_by_key(|n| (n.range.0, n.range.1, n.node_type.clone(), n.name.clone()));
+        nodes.dedup_by_key(|n| (n.range.0, n.range.1, n.node_type.clone(), n.name.clone()));
+        Ok(nodes)
+    }
+
+    /// 从文件的结构节点中找出与 hunk 重叠的节点，并记录每个节点的变更类型和增删的行
+    ///
+    /// 节点与多个 hunk 重叠时使用第一个 hunk 的变更信息。
+    fn nodes_in_hunks(structure: &[AffectedNode], source: &str, hunks: &[DiffHunk]) -> Vec<AffectedNode> {
+        let source_bytes = source.as_bytes();
+        let mut hunk_changes = Vec::new();
 
         for hunk in hunks {
             // Determine the byte range of the hunk in the new file content
@@ -1203,7 +2005,7 @@ impl TreeSitterAnalyzer {
             }
             if current_line < hunk_start_line && hunk_start_line > 0 { // If hunk_start_line is 0, hunk_start_byte remains 0
                  // Reached EOF before hunk start line, means hunk is likely beyond file end (should not happen in valid diff)
-                 warn!("Hunk start line {} is beyond file end for {:?}", hunk_start_line + 1, file_ast.path);
+                 warn!("Hunk start line {} is beyond file end", hunk_start_line + 1);
                  continue;
             }
 
@@ -1250,64 +2052,36 @@ impl TreeSitterAnalyzer {
             } else {
                 "unchanged"
             };
+            hunk_changes.push((hunk_start_byte, hunk_end_byte, additions, deletions, change_operation));
+        }
 
-            let mut cursor = tree_sitter::QueryCursor::new();
-            let matches = cursor.matches(query, tree_root, source_bytes);
+        let mut affected_nodes = Vec::new();
+        for node in structure {
+            // Check if the node overlaps with the hunk's byte range
+            let Some((_, _, additions, deletions, change_operation)) = hunk_changes
+                .iter()
+                .find(|(start, end, _, _, _)| node.range.0 < *end && node.range.1 > *start)
+            else {
+                continue;
+            };
+            let content = node.content.as_deref().unwrap_or("");
 
-            for m in matches {
-                for capture in m.captures {
-                    let node = capture.node;
-                    let node_range = node.byte_range();
+            // 确定变更的详细类型
+            let change_details = if content.contains(&additions.join("\n")) {
+                "added_content"
+            } else if !deletions.is_empty() && deletions.iter().any(|d| content.contains(d)) {
+                "modified_with_deletion"
+            } else {
+                change_operation
+            };
 
-                    // Check if the node overlaps with the hunk's byte range
-                    if node_range.start < hunk_end_byte && node_range.end > hunk_start_byte {
-                        let node_name_capture = m.captures.iter().find(|c| 
-                            query.capture_names()[c.index as usize].ends_with(".name")
-                        );
-                        
-                        let name: String = node_name_capture
-                            .map(|c| c.node.utf8_text(source_bytes).unwrap_or("").to_string())
-                            .unwrap_or_else(|| "unknown".to_string());
-
-                        let kind_capture_index = query.capture_names()[capture.index as usize]
-                            .split('.')
-                            .next()
-                            .unwrap_or("unknown_type");
-                        
-                        let start_line = node.start_position().row;
-                        let end_line = node.end_position().row;
-                        
-                        // 获取节点内容
-                        let content = node.utf8_text(source_bytes).unwrap_or("").to_string();
-                        
-                        // 确定变更的详细类型
-                        let change_details = if content.contains(&additions.join("\n")) {
-                            "added_content"
-                        } else if !deletions.is_empty() && deletions.iter().any(|d| content.contains(d)) {
-                            "modified_with_deletion"
-                        } else {
-                            change_operation
-                        };
-
-                        affected_nodes.push(AffectedNode {
-                            node_type: kind_capture_index.to_string(),
-                            name,
-                            range: (node_range.start, node_range.end),
-                            is_public: self.is_node_public(&node, file_ast),
-                            content: Some(content),
-                            line_range: (start_line, end_line),
-                            change_type: Some(change_details.to_string()),
-                            additions: if !additions.is_empty() { Some(additions.clone()) } else { None },
-                            deletions: if !deletions.is_empty() { Some(deletions.clone()) } else { None },
-                        });
-                    }
-                }
-            }
+            let mut affected = node.clone();
+            affected.change_type = Some(change_details.to_string());
+            affected.additions = if !additions.is_empty() { Some(additions.clone()) } else { None };
+            affected.deletions = if !deletions.is_empty() { Some(deletions.clone()) } else { None };
+            affected_nodes.push(affected);
         }
-        // Deduplicate affected_nodes if necessary (e.g. by node range and type)
-        affected_nodes.sort_by_key(|n| (n.range.0, n.range.1, n.node_type.clone(), n.name.clone()));
-        affected_nodes.dedup_by_key(|n| (n.range.0, n.range.1, n.node_type.clone(), n.name.clone()));
-        Ok(affected_nodes)
+        affected_nodes
     }
     
     #[allow(dead_code)]
@@ -1372,12 +2146,8 @@ impl TreeSitterAnalyzer {
         Ok(project_structure)
     }
     
-    // generate_commit_prompt would also go here, likely calling analyze_diff first.
-    pub async fn generate_commit_prompt(&mut self, diff_text: &str, config: &crate::config_management::settings::AppConfig) -> Result<String, AppError> {
-        // 1. Analyze the diff
-        let diff_analysis = self.analyze_diff(diff_text)
-            .map_err(|e| AppError::TreeSitter(e))?;
-
+    /// 根据已完成的 diff 分析生成提交消息提示，调用方负责选择变更后文件内容的来源
+    pub async fn generate_commit_prompt(&self, diff_analysis: &DiffAnalysis, config: &crate::config_management::settings::AppConfig) -> Result<String, AppError> {
         // 2. Construct a prompt based on the analysis
         // This is a simplified example. You'd want to be more sophisticated.
         let mut prompt_parts = Vec::new();
@@ -0,0 +1,413 @@
+// src/tree_sitter_analyzer/api_surface.rs
+//! 公共 API 变更检测
+//!
+//! 从文件变更前后的两个版本中提取公共 API（由 `is_node_public` 判断的公开定义及其签名），
+//! 比较两个版本的 API 快照，把每个变更归类为破坏性、新增或内部变更，并给出建议的语义化版本升级。
+//!
+//! 签名取定义去掉实现体、注释和注解/属性后的文本；结构体保留公开字段，枚举保留变体，
+//! 因此只修改函数体不会被视为签名变更。
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+use super::ast_diff::{Definition, DefinitionChange};
+use super::core::FileAst;
+use super::java::java_modifier_keywords;
+
+/// 签名中忽略的节点类型：注释、注解、属性和装饰器
+const SIGNATURE_NOISE_KINDS: &[&str] = &[
+    "attribute_item", "inner_attribute_item", "marker_annotation", "annotation", "decorator",
+];
+
+/// 公共 API 中的一个条目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiItem {
+    pub path: PathBuf,
+    /// 定义类型，如 `function`、`class`
+    pub kind: String,
+    /// 限定名称，如 `Parser::parse`
+    pub name: String,
+    /// 规范化的签名
+    pub signature: String,
+    /// 实现方必须提供的成员（trait 中没有默认实现的方法、接口或抽象方法）
+    pub required: bool,
+    /// 起始行（从 1 开始）
+    pub line: usize,
+}
+
+/// 变更对公共 API 的影响
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiImpact {
+    /// 删除公开条目、修改签名或新增必须实现的成员
+    Breaking,
+    /// 新增公开条目
+    Additive,
+    /// 不影响公共 API 的变更
+    Internal,
+}
+
+impl ApiImpact {
+    /// 报告中该类变更的标题
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ApiImpact::Breaking => "破坏性变更",
+            ApiImpact::Additive => "新增 API",
+            ApiImpact::Internal => "内部变更",
+        }
+    }
+}
+
+/// 建议的语义化版本升级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SemverBump {
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+impl SemverBump {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SemverBump::None => "none",
+            SemverBump::Patch => "patch",
+            SemverBump::Minor => "minor",
+            SemverBump::Major => "major",
+        }
+    }
+}
+
+/// 一个公共 API 变更
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApiChange {
+    pub impact: ApiImpact,
+    pub kind: String,
+    pub name: String,
+    pub path: PathBuf,
+    pub line: usize,
+    /// 变更说明，如「删除」「签名变更」
+    pub description: String,
+    pub old_signature: Option<String>,
+    pub new_signature: Option<String>,
+}
+
+/// 一个变更文件中两个版本的公共 API
+#[derive(Debug, Clone, Default)]
+pub struct FileApi {
+    pub old: Vec<ApiItem>,
+    pub new: Vec<ApiItem>,
+}
+
+/// 公共 API 变更报告
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiReport {
+    pub changes: Vec<ApiChange>,
+    pub bump: SemverBump,
+    /// 不支持或无法解析的文件
+    pub skipped: Vec<PathBuf>,
+}
+
+impl Default for ApiReport {
+    fn default() -> Self {
+        Self { changes: Vec::new(), bump: SemverBump::None, skipped: Vec::new() }
+    }
+}
+
+/// 压缩空白并去掉结尾的分隔符
+fn normalize_signature(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    text.trim_end_matches([';', ',', '=', ':', ' ']).to_string()
+}
+
+fn is_signature_noise(node: &Node) -> bool {
+    node.kind().contains("comment") || SIGNATURE_NOISE_KINDS.contains(&node.kind())
+}
+
+/// 收集 `end` 之前需要从签名中去掉的子树范围
+fn collect_noise(node: Node, end: usize, ranges: &mut Vec<(usize, usize)>) {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.start_byte() >= end {
+            break;
+        }
+        if is_signature_noise(&child) {
+            ranges.push((child.start_byte(), child.end_byte()));
+        } else {
+            collect_noise(child, end, ranges);
+        }
+    }
+}
+
+/// 节点从起始位置到 `end` 的文本，去掉注释、注解和属性
+fn text_until(node: Node, source: &str, end: usize) -> String {
+    let mut noise = Vec::new();
+    collect_noise(node, end, &mut noise);
+    let mut text = String::new();
+    let mut offset = node.start_byte();
+    for (start, stop) in noise {
+        if start > offset {
+            text.push_str(&source[offset..start.min(end)]);
+        }
+        offset = offset.max(stop);
+    }
+    if offset < end {
+        text.push_str(&source[offset..end]);
+    }
+    normalize_signature(&text)
+}
+
+/// 定义的实现体：函数体、类体等；Python 装饰器定义取内部定义的实现体
+fn body_of<'a>(node: &Node<'a>) -> Option<Node<'a>> {
+    node.child_by_field_name("body")
+        .or_else(|| node.child_by_field_name("definition").and_then(|d| d.child_by_field_name("body")))
+}
+
+/// 常量、静态变量和字段的初始值，不属于签名
+fn value_of<'a>(node: &Node<'a>) -> Option<Node<'a>> {
+    if let Some(value) = node.child_by_field_name("value") {
+        return Some(value);
+    }
+    node.child_by_field_name("declarator").and_then(|d| d.child_by_field_name("value"))
+}
+
+/// 签名中保留的成员：Rust 结构体的公开字段、枚举变体和 Java 枚举常量
+fn member_signatures(body: &Node, source: &str, is_public: &dyn Fn(&Node) -> bool) -> Option<Vec<String>> {
+    let members: Vec<Node> = {
+        let mut cursor = body.walk();
+        body.named_children(&mut cursor).filter(|c| !is_signature_noise(c)).collect()
+    };
+    let signatures = match body.kind() {
+        "field_declaration_list" => members
+            .iter()
+            .filter(|m| m.kind() == "field_declaration" && is_public(m))
+            .map(|m| text_until(*m, source, m.end_byte()))
+            .collect(),
+        "enum_variant_list" => members
+            .iter()
+            .filter(|m| m.kind() == "enum_variant")
+            .map(|m| text_until(*m, source, m.end_byte()))
+            .collect(),
+        "enum_body" => members
+            .iter()
+            .filter(|m| m.kind() == "enum_constant")
+            .filter_map(|m| m.child_by_field_name("name"))
+            .map(|name| source[name.start_byte()..name.end_byte()].to_string())
+            .collect(),
+        _ => return None,
+    };
+    Some(signatures)
+}
+
+/// 计算定义的规范化签名
+fn api_signature(node: Node, source: &str, is_public: &dyn Fn(&Node) -> bool) -> String {
+    // 元组结构体的字段保留在签名中，宏只比较名称
+    let end = match node.kind() {
+        "macro_definition" => node.child_by_field_name("name").map(|n| n.end_byte()),
+        _ => body_of(&node)
+            .filter(|body| body.kind() != "ordered_field_declaration_list")
+            .or_else(|| value_of(&node))
+            .map(|n| n.start_byte()),
+    }
+    .unwrap_or(node.end_byte());
+    let header = text_until(node, source, end);
+    match body_of(&node).and_then(|body| member_signatures(&body, source, is_public)) {
+        Some(members) => format!("{} {{ {} }}", header, members.join(", ")),
+        None => header,
+    }
+}
+
+/// 是否为实现方必须提供的成员
+fn is_required_member(node: &Node) -> bool {
+    match node.kind() {
+        "function_signature_item" => node
+            .parent()
+            .and_then(|p| p.parent())
+            .is_some_and(|owner| owner.kind() == "trait_item"),
+        "method_declaration" => {
+            if node.child_by_field_name("body").is_some() {
+                return false;
+            }
+            let modifiers = java_modifier_keywords(node);
+            let in_interface = node.parent().is_some_and(|p| p.kind() == "interface_body");
+            modifiers.contains(&"abstract")
+                || (in_interface && !modifiers.iter().any(|m| matches!(*m, "default" | "static" | "private")))
+        }
+        _ => false,
+    }
+}
+
+/// 从文件的定义中提取公共 API
+///
+/// `is_public` 判断定义节点是否公开，由分析器按语言规则提供。
+pub fn extract_api_items(file_ast: &FileAst, definitions: &[Definition], is_public: &dyn Fn(&Node) -> bool) -> Vec<ApiItem> {
+    let root = file_ast.tree.root_node();
+    definitions
+        .iter()
+        .filter_map(|definition| {
+            let (start, end) = definition.byte_range();
+            let node = root.descendant_for_byte_range(start, end)?;
+            if !is_public(&node) {
+                return None;
+            }
+            Some(ApiItem {
+                path: definition.path.clone(),
+                kind: definition.node_type.clone(),
+                name: definition.qualified_name.clone(),
+                signature: api_signature(node, &file_ast.source, is_public),
+                required: is_required_member(&node),
+                line: definition.start_line,
+            })
+        })
+        .collect()
+}
+
+fn api_change(impact: ApiImpact, item: &ApiItem, description: &str, old: Option<&ApiItem>, new: Option<&ApiItem>) -> ApiChange {
+    ApiChange {
+        impact,
+        kind: item.kind.clone(),
+        name: item.name.clone(),
+        path: item.path.clone(),
+        line: item.line,
+        description: description.to_string(),
+        old_signature: old.map(|i| i.signature.clone()),
+        new_signature: new.map(|i| i.signature.clone()),
+    }
+}
+
+/// 比较变更前后的公共 API
+///
+/// 每个文件内先按 (类型, 限定名称, 签名) 配对未变化的条目，剩余同名条目视为签名变更，
+/// 其余为删除或新增。删除和签名变更是破坏性的；新增是兼容的，但向已有的 trait、接口或
+/// 抽象类新增必须实现的成员是破坏性的。`definition_changes` 中与公共 API 无关的定义变更计为内部变更。
+pub fn diff_api(files: &[FileApi], definition_changes: &[DefinitionChange]) -> ApiReport {
+    let mut changes = Vec::new();
+    for file in files {
+        let mut old_left: Vec<&ApiItem> = file.old.iter().collect();
+        let mut new_left: Vec<&ApiItem> = Vec::new();
+        for item in &file.new {
+            let unchanged = old_left
+                .iter()
+                .position(|old| old.kind == item.kind && old.name == item.name && old.signature == item.signature);
+            match unchanged {
+                Some(index) => {
+                    old_left.remove(index);
+                }
+                None => new_left.push(item),
+            }
+        }
+
+        let old_names: HashSet<&str> = file.old.iter().map(|i| i.name.as_str()).collect();
+        for item in new_left {
+            match old_left.iter().position(|old| old.kind == item.kind && old.name == item.name) {
+                Some(index) => {
+                    let old = old_left.remove(index);
+                    changes.push(api_change(ApiImpact::Breaking, item, "签名变更", Some(old), Some(item)));
+                }
+                None => {
+                    let container_existed = item
+                        .name
+                        .rsplit_once("::")
+                        .is_some_and(|(container, _)| old_names.contains(container));
+                    if item.required && container_existed {
+                        changes.push(api_change(ApiImpact::Breaking, item, "新增必须实现的成员", None, Some(item)));
+                    } else {
+                        changes.push(api_change(ApiImpact::Additive, item, "新增", None, Some(item)));
+                    }
+                }
+            }
+        }
+        for old in old_left {
+            changes.push(api_change(ApiImpact::Breaking, old, "删除或不再公开", Some(old), None));
+        }
+    }
+
+    let api_names: HashSet<(PathBuf, String)> = changes.iter().map(|c| (c.path.clone(), c.name.clone())).collect();
+    for change in definition_changes {
+        let touches_api = [change.old.as_ref(), change.new.as_ref()]
+            .into_iter()
+            .flatten()
+            .any(|l| api_names.contains(&(l.path.clone(), l.qualified_name.clone())));
+        if touches_api {
+            continue;
+        }
+        let location = change.new.as_ref().or(change.old.as_ref());
+        changes.push(ApiChange {
+            impact: ApiImpact::Internal,
+            kind: change.node_type.clone(),
+            name: change.name().to_string(),
+            path: location.map(|l| l.path.clone()).unwrap_or_default(),
+            line: location.map(|l| l.start_line).unwrap_or_default(),
+            description: change.kind.display_name().to_string(),
+            old_signature: None,
+            new_signature: None,
+        });
+    }
+
+    let bump = changes
+        .iter()
+        .map(|c| match c.impact {
+            ApiImpact::Breaking => SemverBump::Major,
+            ApiImpact::Additive => SemverBump::Minor,
+            ApiImpact::Internal => SemverBump::Patch,
+        })
+        .max()
+        .unwrap_or(SemverBump::None);
+    changes.sort_by(|a, b| (a.impact as u8, &a.path, a.line).cmp(&(b.impact as u8, &b.path, b.line)));
+    ApiReport { changes, bump, skipped: Vec::new() }
+}
+
+impl ApiReport {
+    /// 是否包含破坏性变更
+    pub fn has_breaking_changes(&self) -> bool {
+        self.changes.iter().any(|c| c.impact == ApiImpact::Breaking)
+    }
+
+    /// 按影响筛选变更
+    pub fn changes_with(&self, impact: ApiImpact) -> impl Iterator<Item = &ApiChange> {
+        self.changes.iter().filter(move |c| c.impact == impact)
+    }
+
+    /// 破坏性变更的简短描述，用于提交消息中的 `BREAKING CHANGE` 页脚
+    pub fn breaking_summary(&self) -> Vec<String> {
+        self.changes_with(ApiImpact::Breaking)
+            .map(|c| format!("{} {} `{}`", c.description, c.kind, c.name))
+            .collect()
+    }
+
+    /// 格式化为 Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut text = format!("### 公共 API 变更（建议版本升级：{}）\n", self.bump.as_str());
+        if self.changes.is_empty() {
+            text.push_str("\n公共 API 没有变化。\n");
+        }
+        for impact in [ApiImpact::Breaking, ApiImpact::Additive, ApiImpact::Internal] {
+            let changes: Vec<&ApiChange> = self.changes_with(impact).collect();
+            if changes.is_empty() {
+                continue;
+            }
+            text.push_str(&format!("\n#### {}\n\n", impact.display_name()));
+            for change in changes {
+                text.push_str(&format!(
+                    "- {}: {} `{}` ({}:{})\n",
+                    change.description, change.kind, change.name, change.path.display(), change.line
+                ));
+                match (&change.old_signature, &change.new_signature) {
+                    (Some(old), Some(new)) => text.push_str(&format!("  - `{}` → `{}`\n", old, new)),
+                    (Some(signature), None) | (None, Some(signature)) if impact == ApiImpact::Breaking => {
+                        text.push_str(&format!("  - `{}`\n", signature));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if !self.skipped.is_empty() {
+            let skipped: Vec<String> = self.skipped.iter().map(|p| p.display().to_string()).collect();
+            text.push_str(&format!("\n未分析的文件：{}\n", skipped.join(", ")));
+        }
+        text
+    }
+}
@@ -0,0 +1,672 @@
+// src/tree_sitter_analyzer/ast_diff.rs
+//! 语法树级别的变更比较
+//!
+//! 分别解析文件变更前后的两个版本，提取其中的具名定义（函数、方法、类型等），
+//! 按定义的限定名称配对，精确判断每个定义是新增、删除、修改、重命名还是移动，
+//! 而不是从 diff 的 `-` 行推测。
+//!
+//! 移动和重命名通过规范化语法树哈希识别：哈希忽略空白、注释和定义自身的名称，
+//! 每个定义还记录其子树的哈希，内容略有修改时按子树哈希的重合程度判断相似度。
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Node, Query, QueryCursor};
+
+use super::core::{FileAst, StableHasher};
+use super::go::go_receiver_type;
+
+/// 不作为定义比较的捕获类型（导入、注解等没有独立语义的结构）
+const SKIPPED_KINDS: &[&str] = &[
+    "import", "use", "include", "export", "attribute", "annotation", "package", "public_api",
+];
+
+/// 判断重命名时，两个定义的语法树需要达到的相似度
+const RENAME_SIMILARITY: f64 = 0.8;
+
+/// 同名定义被删除后在其他位置新增时，视为移动需要达到的语法树相似度
+const MOVE_SIMILARITY: f64 = 0.6;
+
+/// 名称不同的定义视为移动时至少需要的子树数量，避免把结构相同的简短定义误判为移动
+const MIN_MOVE_SUBTREES: usize = 4;
+
+/// 参与相似度比较的子树至少包含的词法单元数
+const MIN_SUBTREE_TOKENS: usize = 3;
+
+/// 文件中的一个具名定义
+///
+/// 可以写入分析缓存，文本字段不序列化，读取后由 [`restore_definition_texts`] 从源码恢复。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Definition {
+    pub path: PathBuf,
+    /// 定义类型，取自查询捕获名称，如 `function`、`class`
+    pub node_type: String,
+    pub name: String,
+    /// 包含外层定义的限定名称，如 `Parser::parse`
+    pub qualified_name: String,
+    /// 起始行（从 1 开始）
+    pub start_line: usize,
+    /// 结束行（包含）
+    pub end_line: usize,
+    #[serde(skip)]
+    pub text: String,
+    /// 去掉内层定义后的文本，用于判断定义本身是否被修改
+    #[serde(skip)]
+    own_text: String,
+    /// 外层定义在同一文件定义列表中的下标
+    parent: Option<usize>,
+    /// 规范化语法树哈希，忽略空白、注释和定义名称；使用固定算法，可以写入分析缓存
+    ast_hash: u64,
+    /// 子树哈希（已排序），用于计算相似度
+    subtrees: Vec<u64>,
+    /// 定义节点在源码中的字节范围
+    byte_range: (usize, usize),
+}
+
+impl Definition {
+    /// 定义节点在源码中的字节范围，可用于在语法树中找回定义节点
+    pub fn byte_range(&self) -> (usize, usize) {
+        self.byte_range
+    }
+}
+
+/// 查询匹配到的定义节点
+struct RawDefinition {
+    start: usize,
+    end: usize,
+    kind: String,
+    name: String,
+    ast_hash: u64,
+    subtrees: Vec<u64>,
+}
+
+/// 定义级变更的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefinitionChangeKind {
+    Added,
+    Removed,
+    Modified,
+    Renamed,
+    Moved,
+}
+
+impl DefinitionChangeKind {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            DefinitionChangeKind::Added => "新增",
+            DefinitionChangeKind::Removed => "删除",
+            DefinitionChangeKind::Modified => "修改",
+            DefinitionChangeKind::Renamed => "重命名",
+            DefinitionChangeKind::Moved => "移动",
+        }
+    }
+}
+
+/// 定义在某个版本中的位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefinitionLocation {
+    pub path: PathBuf,
+    pub qualified_name: String,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+impl DefinitionLocation {
+    fn of(definition: &Definition) -> Self {
+        Self {
+            path: definition.path.clone(),
+            qualified_name: definition.qualified_name.clone(),
+            start_line: definition.start_line,
+            end_line: definition.end_line,
+        }
+    }
+}
+
+/// 一个定义的变更
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefinitionChange {
+    pub kind: DefinitionChangeKind,
+    pub node_type: String,
+    /// 变更前的位置，新增的定义为 None
+    pub old: Option<DefinitionLocation>,
+    /// 变更后的位置，删除的定义为 None
+    pub new: Option<DefinitionLocation>,
+    /// 移动或重命名的同时内容也有修改
+    pub content_changed: bool,
+}
+
+impl DefinitionChange {
+    /// 变更后（删除时为变更前）的限定名称
+    pub fn name(&self) -> &str {
+        self.new.as_ref().or(self.old.as_ref()).map(|l| l.qualified_name.as_str()).unwrap_or_default()
+    }
+
+    /// 变更的简短描述，如 ``function `parse` src/a.rs:3 → src/b.rs:10``
+    pub fn describe(&self) -> String {
+        let location = |l: &DefinitionLocation| format!("{}:{}", l.path.display(), l.start_line);
+        let text = match (self.kind, &self.old, &self.new) {
+            (DefinitionChangeKind::Renamed, Some(old), Some(new)) => format!(
+                "{} `{}` → `{}` ({})",
+                self.node_type, old.qualified_name, new.qualified_name, location(new)
+            ),
+            (DefinitionChangeKind::Moved, Some(old), Some(new)) if old.qualified_name != new.qualified_name => format!(
+                "{} `{}` → `{}` {} → {}",
+                self.node_type, old.qualified_name, new.qualified_name, location(old), location(new)
+            ),
+            (DefinitionChangeKind::Moved, Some(old), Some(new)) => format!(
+                "{} `{}` {} → {}",
+                self.node_type, new.qualified_name, location(old), location(new)
+            ),
+            (_, _, Some(new)) => format!("{} `{}` ({})", self.node_type, new.qualified_name, location(new)),
+            (_, Some(old), None) => format!("{} `{}` (原 {})", self.node_type, old.qualified_name, location(old)),
+            (_, None, None) => return String::new(),
+        };
+        if self.content_changed && matches!(self.kind, DefinitionChangeKind::Renamed | DefinitionChangeKind::Moved) {
+            format!("{}，内容有修改", text)
+        } else {
+            text
+        }
+    }
+}
+
+/// 一个文件变更前后的两个版本，新增或删除的文件缺少其中一个版本
+#[derive(Debug, Clone, Default)]
+pub struct FileVersions {
+    pub old_path: Option<PathBuf>,
+    pub new_path: Option<PathBuf>,
+    pub old_source: Option<String>,
+    pub new_source: Option<String>,
+}
+
+/// 变更前后的语法树比较结果
+#[derive(Debug, Clone, Default)]
+pub struct AstDiff {
+    pub changes: Vec<DefinitionChange>,
+    /// 不支持或无法解析的文件
+    pub skipped: Vec<PathBuf>,
+}
+
+fn text_of(node: &Node, source: &[u8]) -> String {
+    node.utf8_text(source).unwrap_or_default().to_string()
+}
+
+/// 没有 `.name` 捕获时从节点字段中取名称节点，impl 块使用实现的类型（和 trait）
+fn fallback_name<'a>(node: &Node<'a>, source: &[u8]) -> Option<(String, Node<'a>)> {
+    if let Some(name) = node.child_by_field_name("name") {
+        return Some((text_of(&name, source), name));
+    }
+    let type_node = node.child_by_field_name("type")?;
+    let type_name = text_of(&type_node, source);
+    Some(match node.child_by_field_name("trait") {
+        Some(trait_node) => (format!("{} for {}", text_of(&trait_node, source), type_name), type_node),
+        None => (type_name, type_node),
+    })
+}
+
+/// 计算子树的规范化哈希，返回 (哈希, 词法单元数)；注释返回 None
+///
+/// 叶子节点按类型和文本计算，定义名称节点只按类型计算，因此重命名不影响哈希。
+/// 词法单元数不少于 [`MIN_SUBTREE_TOKENS`] 的具名子树哈希收集到 `subtrees` 中。
+fn normalized_hash(node: Node, source: &[u8], name_range: Option<(usize, usize)>, subtrees: &mut Vec<u64>) -> Option<(u64, usize)> {
+    if node.kind().contains("comment") {
+        return None;
+    }
+    let mut hasher = StableHasher::default();
+    hasher.write_str(node.kind());
+    if name_range == Some((node.start_byte(), node.end_byte())) {
+        return Some((hasher.finish(), 1));
+    }
+    if node.child_count() == 0 {
+        hasher.write_str(node.utf8_text(source).unwrap_or_default());
+        return Some((hasher.finish(), 1));
+    }
+    let mut tokens = 0;
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if let Some((child_hash, child_tokens)) = normalized_hash(child, source, name_range, subtrees) {
+            hasher.write_u64(child_hash);
+            tokens += child_tokens;
+        }
+    }
+    let hash = hasher.finish();
+    if node.is_named() && tokens >= MIN_SUBTREE_TOKENS {
+        subtrees.push(hash);
+    }
+    Some((hash, tokens))
+}
+
+/// 使用结构查询提取文件中的具名定义，按在文件中出现的顺序返回
+pub fn extract_definitions(file_ast: &FileAst, query: &Query) -> Vec<Definition> {
+    let source = file_ast.source.as_bytes();
+    let capture_names = query.capture_names();
+    let mut cursor = QueryCursor::new();
+    let mut raw: Vec<RawDefinition> = Vec::new();
+
+    for m in cursor.matches(query, file_ast.tree.root_node(), source) {
+        let Some(declaration) = m.captures.iter().find(|c| capture_names[c.index as usize].ends_with(".declaration")) else {
+            continue;
+        };
+        let node = declaration.node;
+        let mut kind = capture_names[declaration.index as usize].split('.').next().unwrap_or_default().to_string();
+        if SKIPPED_KINDS.contains(&kind.as_str()) {
+            continue;
+        }
+        let name = m.captures
+            .iter()
+            .find(|c| capture_names[c.index as usize].ends_with(".name"))
+            .map(|c| (text_of(&c.node, source), c.node))
+            .or_else(|| fallback_name(&node, source));
+        let Some((mut name, name_node)) = name else {
+            continue;
+        };
+
+        // 语言相关的调整：Python 装饰器定义取内部定义的类型，Go 方法带上接收者类型
+        if kind == "decorated" {
+            let inner = node.child_by_field_name("definition").map(|d| d.kind()).unwrap_or_default();
+            kind = if inner == "class_definition" { "class" } else { "function" }.to_string();
+        }
+        if file_ast.language_id == "go"
+            && kind == "method"
+            && let Some(receiver) = go_receiver_type(node, source)
+        {
+            name = format!("{}.{}", receiver, name);
+        }
+        let mut subtrees = Vec::new();
+        let name_range = Some((name_node.start_byte(), name_node.end_byte()));
+        let ast_hash = normalized_hash(node, source, name_range, &mut subtrees).map(|(h, _)| h).unwrap_or_default();
+        subtrees.sort_unstable();
+        raw.push(RawDefinition { start: node.start_byte(), end: node.end_byte(), kind, name, ast_hash, subtrees });
+    }
+
+    // 同一节点被多个模式捕获时只保留第一个；与外层定义同名且同时结束的内层节点（如装饰器内的函数）去掉，
+    // 构造函数等与外层同名的成员保留
+    raw.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+    raw.dedup_by(|b, a| a.start == b.start && a.end == b.end);
+    let outer: Vec<(usize, usize, String)> = raw.iter().map(|r| (r.start, r.end, r.name.clone())).collect();
+    raw.retain(|r| !outer.iter().any(|(s, e, n)| *n == r.name && *s < r.start && r.end == *e));
+
+    let mut definitions: Vec<Definition> = Vec::with_capacity(raw.len());
+    let mut stack: Vec<usize> = Vec::new();
+    for RawDefinition { start, end, kind, name, ast_hash, subtrees } in raw {
+        while stack.last().is_some_and(|&top| definitions[top].byte_range.1 <= start) {
+            stack.pop();
+        }
+        let parent = stack.last().copied();
+        let qualified_name = match parent {
+            Some(p) => format!("{}::{}", definitions[p].qualified_name, name),
+            None => name.clone(),
+        };
+        let start_line = file_ast.source[..start].matches('\n').count() + 1;
+        let end_line = start_line + file_ast.source[start..end].matches('\n').count();
+        definitions.push(Definition {
+            path: file_ast.path.clone(),
+            node_type: kind,
+            name,
+            qualified_name,
+            start_line,
+            end_line,
+            text: String::new(),
+            own_text: String::new(),
+            parent,
+            ast_hash,
+            subtrees,
+            byte_range: (start, end),
+        });
+        stack.push(definitions.len() - 1);
+    }
+
+    restore_definition_texts(&mut definitions, &file_ast.source);
+    definitions
+}
+
+/// 根据字节范围从源码中填充定义的文本，以及去掉直接包含的内层定义后的文本
+pub fn restore_definition_texts(definitions: &mut [Definition], source: &str) {
+    for i in 0..definitions.len() {
+        let (start, end) = definitions[i].byte_range;
+        let mut own_text = String::new();
+        let mut offset = start;
+        for child in (i + 1..definitions.len()).filter(|&c| definitions[c].parent == Some(i)) {
+            own_text.push_str(&source[offset..definitions[child].byte_range.0]);
+            offset = definitions[child].byte_range.1;
+        }
+        own_text.push_str(&source[offset..end]);
+        definitions[i].text = source[start..end].to_string();
+        definitions[i].own_text = own_text;
+    }
+}
+
+/// 压缩空白后的文本，格式调整不视为内容变更
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 两个定义的语法树相似度（0 到 1）
+///
+/// 规范化哈希相同为 1，否则为 `2S / (2S + L + R)`：S 为共有的子树数，L、R 为各自独有的子树数。
+/// 一处修改会改变其所有外层子树的哈希，因此不使用对差异更敏感的 Jaccard 系数。
+fn ast_similarity(old: &Definition, new: &Definition) -> f64 {
+    if old.ast_hash == new.ast_hash {
+        return 1.0;
+    }
+    let (mut i, mut j, mut common) = (0, 0, 0);
+    while i < old.subtrees.len() && j < new.subtrees.len() {
+        match old.subtrees[i].cmp(&new.subtrees[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                common += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    let total = old.subtrees.len() + new.subtrees.len();
+    if total == 0 {
+        return 0.0;
+    }
+    2.0 * common as f64 / total as f64
+}
+
+/// 在删除和新增的定义之间寻找移动，返回 (删除下标, 新增下标, 内容是否有修改)
+///
+/// 移动到其他文件或其他外层定义中的定义：规范化语法树相同（名称不同时要求定义足够大），
+/// 或名称相同且语法树相似度达到 [`MOVE_SIMILARITY`]。同一文件中名称不同的定义留给重命名判断。
+fn find_moves(removed: &[&Definition], added: &[&Definition]) -> Vec<(usize, usize, bool)> {
+    let mut moves: Vec<(usize, usize, bool)> = Vec::new();
+    // 先匹配完全相同的定义，再匹配略有修改的定义
+    for exact in [true, false] {
+        for (added_index, new_def) in added.iter().enumerate() {
+            if moves.iter().any(|(_, a, _)| *a == added_index) {
+                continue;
+            }
+            let best = removed
+                .iter()
+                .enumerate()
+                .filter(|(removed_index, old_def)| {
+                    !moves.iter().any(|(r, _, _)| r == removed_index)
+                        && old_def.node_type == new_def.node_type
+                        && (old_def.path != new_def.path || old_def.name == new_def.name)
+                })
+                .filter_map(|(removed_index, old_def)| {
+                    let same_name = old_def.name == new_def.name;
+                    let similarity = if exact {
+                        let matched = old_def.ast_hash == new_def.ast_hash
+                            && (same_name || old_def.subtrees.len() >= MIN_MOVE_SUBTREES);
+                        if matched { 1.0 } else { 0.0 }
+                    } else if same_name {
+                        ast_similarity(old_def, new_def)
+                    } else {
+                        0.0
+                    };
+                    let threshold = if exact { 1.0 } else { MOVE_SIMILARITY };
+                    (similarity >= threshold).then_some((removed_index, similarity))
+                })
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            if let Some((removed_index, _)) = best {
+                moves.push((removed_index, added_index, removed[removed_index].ast_hash != new_def.ast_hash));
+            }
+        }
+    }
+    moves
+}
+
+/// 返回完全由给定行组成的最外层定义，用于找出整体新增或整体删除的定义
+pub fn definitions_within_lines(definitions: Vec<Definition>, lines: &HashSet<usize>) -> Vec<Definition> {
+    let within: Vec<Definition> = definitions
+        .into_iter()
+        .filter(|d| (d.start_line..=d.end_line).all(|line| lines.contains(&line)))
+        .collect();
+    within
+        .iter()
+        .filter(|d| {
+            !within.iter().any(|outer| {
+                outer.start_line <= d.start_line
+                    && d.end_line <= outer.end_line
+                    && (outer.start_line, outer.end_line) != (d.start_line, d.end_line)
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+/// 在整体删除和整体新增的定义之间识别移动的代码
+///
+/// 用于只有 diff 片段的场景：`removed` 取自 diff 中被删除的行，`added` 取自新增的行。
+pub fn detect_moved_code(removed: &[Definition], added: &[Definition]) -> Vec<DefinitionChange> {
+    let removed_refs: Vec<&Definition> = removed.iter().collect();
+    let added_refs: Vec<&Definition> = added.iter().collect();
+    find_moves(&removed_refs, &added_refs)
+        .into_iter()
+        .map(|(r, a, content_changed)| DefinitionChange {
+            kind: DefinitionChangeKind::Moved,
+            node_type: added[a].node_type.clone(),
+            old: Some(DefinitionLocation::of(&removed[r])),
+            new: Some(DefinitionLocation::of(&added[a])),
+            content_changed,
+        })
+        .collect()
+}
+
+/// 返回最长递增子序列中元素的下标
+fn longest_increasing_subsequence(values: &[usize]) -> HashSet<usize> {
+    let n = values.len();
+    let mut lengths = vec![1usize; n];
+    let mut previous: Vec<Option<usize>> = vec![None; n];
+    for i in 0..n {
+        for j in 0..i {
+            if values[j] < values[i] && lengths[j] + 1 > lengths[i] {
+                lengths[i] = lengths[j] + 1;
+                previous[i] = Some(j);
+            }
+        }
+    }
+    let mut result = HashSet::new();
+    let mut current = (0..n).max_by_key(|&i| (lengths[i], std::cmp::Reverse(i)));
+    while let Some(i) = current {
+        result.insert(i);
+        current = previous[i];
+    }
+    result
+}
+
+/// 定义的位置：(文件下标, 定义下标)
+type DefinitionIndex = (usize, usize);
+
+/// 一个变更文件中两个版本的定义
+pub struct FileDefinitions {
+    pub old: Vec<Definition>,
+    pub new: Vec<Definition>,
+}
+
+/// 比较变更前后的定义
+///
+/// 先在每个文件内按 (类型, 限定名称) 配对，内容不同的为修改，同级定义顺序改变的为移动；
+/// 剩余未配对的定义跨文件匹配：名称和内容相同的为移动，同一文件中内容相似而名称不同的为重命名，
+/// 其余为新增或删除。外层定义整体新增、删除、移动或重命名时，不再单独列出其内层定义。
+pub fn diff_definitions(files: &[FileDefinitions]) -> Vec<DefinitionChange> {
+    // (文件下标, 定义下标, 变更) ；用于之后省略内层定义
+    let mut changes: Vec<(usize, Option<usize>, Option<usize>, DefinitionChange)> = Vec::new();
+    let mut unmatched_old: Vec<DefinitionIndex> = Vec::new();
+    let mut unmatched_new: Vec<DefinitionIndex> = Vec::new();
+
+    for (file_index, file) in files.iter().enumerate() {
+        let mut used_old = vec![false; file.old.len()];
+        let mut pairs: Vec<(usize, usize)> = Vec::new();
+        for (new_index, new_def) in file.new.iter().enumerate() {
+            let matched = file.old.iter().enumerate().position(|(old_index, old_def)| {
+                !used_old[old_index]
+                    && old_def.node_type == new_def.node_type
+                    && old_def.qualified_name == new_def.qualified_name
+            });
+            match matched {
+                Some(old_index) => {
+                    used_old[old_index] = true;
+                    pairs.push((old_index, new_index));
+                }
+                None => unmatched_new.push((file_index, new_index)),
+            }
+        }
+        unmatched_old.extend((0..file.old.len()).filter(|&i| !used_old[i]).map(|i| (file_index, i)));
+
+        // 同一外层定义中的定义按新版本顺序排列，不在最长递增子序列中的即被移动过
+        let mut moved: HashSet<usize> = HashSet::new();
+        let mut containers: Vec<Option<String>> = pairs
+            .iter()
+            .map(|&(_, n)| file.new[n].parent.map(|p| file.new[p].qualified_name.clone()))
+            .collect();
+        containers.dedup();
+        for container in containers.iter().collect::<HashSet<_>>() {
+            let group: Vec<(usize, usize)> = pairs
+                .iter()
+                .copied()
+                .filter(|&(_, n)| file.new[n].parent.map(|p| file.new[p].qualified_name.clone()) == *container)
+                .collect();
+            let old_order: Vec<usize> = group.iter().map(|&(o, _)| o).collect();
+            let kept = longest_increasing_subsequence(&old_order);
+            moved.extend((0..group.len()).filter(|i| !kept.contains(i)).map(|i| group[i].1));
+        }
+
+        for &(old_index, new_index) in &pairs {
+            let (old_def, new_def) = (&file.old[old_index], &file.new[new_index]);
+            let content_changed = normalize(&old_def.own_text) != normalize(&new_def.own_text);
+            let kind = if moved.contains(&new_index) {
+                DefinitionChangeKind::Moved
+            } else if content_changed {
+                DefinitionChangeKind::Modified
+            } else {
+                continue;
+            };
+            changes.push((file_index, Some(old_index), Some(new_index), DefinitionChange {
+                kind,
+                node_type: new_def.node_type.clone(),
+                old: Some(DefinitionLocation::of(old_def)),
+                new: Some(DefinitionLocation::of(new_def)),
+                content_changed,
+            }));
+        }
+    }
+
+    // 跨文件或跨外层定义的移动
+    let removed: Vec<&Definition> = unmatched_old.iter().map(|&(f, i)| &files[f].old[i]).collect();
+    let added: Vec<&Definition> = unmatched_new.iter().map(|&(f, i)| &files[f].new[i]).collect();
+    let mut paired: Vec<(DefinitionIndex, DefinitionIndex, DefinitionChangeKind, bool)> = find_moves(&removed, &added)
+        .into_iter()
+        .map(|(r, a, content_changed)| (unmatched_old[r], unmatched_new[a], DefinitionChangeKind::Moved, content_changed))
+        .collect();
+    // 同一文件内的重命名：类型相同，语法树（不含名称）相同或高度相似
+    for &(nf, ni) in &unmatched_new {
+        if paired.iter().any(|(_, n, _, _)| *n == (nf, ni)) {
+            continue;
+        }
+        let new_def = &files[nf].new[ni];
+        let best = unmatched_old
+            .iter()
+            .copied()
+            .filter(|&(of, oi)| {
+                of == nf
+                    && !paired.iter().any(|(o, _, _, _)| *o == (of, oi))
+                    && files[of].old[oi].node_type == new_def.node_type
+                    && files[of].old[oi].name != new_def.name
+            })
+            .map(|(of, oi)| ((of, oi), ast_similarity(&files[of].old[oi], new_def)))
+            .filter(|(_, similarity)| *similarity >= RENAME_SIMILARITY)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some(((of, oi), _)) = best {
+            paired.push(((of, oi), (nf, ni), DefinitionChangeKind::Renamed, files[of].old[oi].ast_hash != new_def.ast_hash));
+        }
+    }
+
+    for &((of, oi), (nf, ni), kind, content_changed) in &paired {
+        let (old_def, new_def) = (&files[of].old[oi], &files[nf].new[ni]);
+        changes.push((nf, Some(oi), Some(ni), DefinitionChange {
+            kind,
+            node_type: new_def.node_type.clone(),
+            old: Some(DefinitionLocation::of(old_def)),
+            new: Some(DefinitionLocation::of(new_def)),
+            content_changed,
+        }));
+    }
+    for &(nf, ni) in unmatched_new.iter().filter(|n| !paired.iter().any(|(_, p, _, _)| p == *n)) {
+        let new_def = &files[nf].new[ni];
+        changes.push((nf, None, Some(ni), DefinitionChange {
+            kind: DefinitionChangeKind::Added,
+            node_type: new_def.node_type.clone(),
+            old: None,
+            new: Some(DefinitionLocation::of(new_def)),
+            content_changed: true,
+        }));
+    }
+    for &(of, oi) in unmatched_old.iter().filter(|o| !paired.iter().any(|(p, _, _, _)| p == *o)) {
+        let old_def = &files[of].old[oi];
+        changes.push((of, Some(oi), None, DefinitionChange {
+            kind: DefinitionChangeKind::Removed,
+            node_type: old_def.node_type.clone(),
+            old: Some(DefinitionLocation::of(old_def)),
+            new: None,
+            content_changed: true,
+        }));
+    }
+
+    // 外层定义整体变更时省略未修改内容的内层定义
+    let is_whole = |kind: DefinitionChangeKind| kind != DefinitionChangeKind::Modified;
+    let new_parents: HashSet<(usize, usize)> = changes
+        .iter()
+        .filter(|(_, _, n, c)| n.is_some() && is_whole(c.kind))
+        .map(|(f, _, n, _)| (*f, n.unwrap()))
+        .collect();
+    let old_parents: HashSet<(PathBuf, String)> = changes
+        .iter()
+        .filter(|(_, _, _, c)| is_whole(c.kind))
+        .filter_map(|(_, _, _, c)| c.old.as_ref().map(|o| (o.path.clone(), o.qualified_name.clone())))
+        .collect();
+    let mut result: Vec<DefinitionChange> = changes
+        .iter()
+        .filter(|(file_index, _, new_index, change)| {
+            if change.kind == DefinitionChangeKind::Modified || (change.content_changed && change.kind != DefinitionChangeKind::Added && change.kind != DefinitionChangeKind::Removed) {
+                return true;
+            }
+            let new_parent_changed = new_index
+                .and_then(|n| files[*file_index].new[n].parent)
+                .is_some_and(|p| new_parents.contains(&(*file_index, p)));
+            let old_parent_changed = change.old.as_ref().is_some_and(|o| {
+                o.qualified_name
+                    .rsplit_once("::")
+                    .is_some_and(|(parent, _)| old_parents.contains(&(o.path.clone(), parent.to_string())))
+            });
+            match change.kind {
+                DefinitionChangeKind::Added => !new_parent_changed,
+                DefinitionChangeKind::Removed => !old_parent_changed,
+                _ => !(new_parent_changed || old_parent_changed),
+            }
+        })
+        .map(|(_, _, _, change)| change.clone())
+        .collect();
+
+    result.sort_by(|a, b| {
+        let key = |c: &DefinitionChange| {
+            let location = c.new.as_ref().or(c.old.as_ref()).unwrap();
+            (location.path.clone(), location.start_line)
+        };
+        key(a).cmp(&key(b))
+    });
+    result
+}
+
+impl AstDiff {
+    /// 按变更类型统计数量
+    pub fn count(&self, kind: DefinitionChangeKind) -> usize {
+        self.changes.iter().filter(|c| c.kind == kind).count()
+    }
+
+    /// 格式化为分析报告中的 Markdown 章节
+    pub fn to_markdown(&self) -> String {
+        if self.changes.is_empty() {
+            return String::new();
+        }
+        let mut text = String::from("#### 定义级变更（语法树比较）\n\n");
+        for change in &self.changes {
+            text.push_str(&format!("- {}: {}\n", change.kind.display_name(), change.describe()));
+        }
+        text
+    }
+}
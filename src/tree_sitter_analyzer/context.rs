@@ -0,0 +1,372 @@
+// src/tree_sitter_analyzer/context.rs
+//! 评审上下文构建
+//!
+//! 根据变更后文件的语法树，为每个 hunk 提取完整的所在函数或类，
+//! 再补充这些代码中引用到的、定义在变更文件内的其他函数和类型，
+//! 在 token 预算内组成提供给 AI 评审的代码上下文。
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use tree_sitter::Node;
+
+use super::core::FileAst;
+
+/// 超出预算的代码片段保留的 hunk 前后行数
+const WINDOW_LINES: usize = 5;
+
+/// 一个变更文件及其变更的行范围
+#[derive(Debug, Clone)]
+pub struct ChangedFileAst {
+    /// 变更后文件的语法树
+    pub ast: FileAst,
+    /// 变更的行范围（从 1 开始，包含两端）
+    pub changed_lines: Vec<(usize, usize)>,
+}
+
+/// 上下文中的一段代码
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextSnippet {
+    pub path: PathBuf,
+    pub language: String,
+    /// 语法节点类型，例如 `function_item`
+    pub kind: String,
+    pub name: String,
+    /// 起始行（从 1 开始）
+    pub start_line: usize,
+    /// 结束行（包含）
+    pub end_line: usize,
+    pub text: String,
+    /// 是否为被变更代码引用的定义（而不是变更所在的函数或类）
+    pub referenced: bool,
+}
+
+/// 提供给 AI 评审的代码上下文
+#[derive(Debug, Clone, Default)]
+pub struct ReviewContext {
+    pub snippets: Vec<ContextSnippet>,
+    /// 估算的 token 数
+    pub estimated_tokens: usize,
+    /// 因超出预算而省略的片段数量
+    pub omitted: usize,
+}
+
+/// 粗略估算文本的 token 数
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+/// 函数级定义的节点类型
+fn function_kinds(language: &str) -> &'static [&'static str] {
+    match language {
+        "rust" => &["function_item", "macro_definition"],
+        "java" => &["method_declaration", "constructor_declaration"],
+        "python" => &["function_definition"],
+        "go" => &["function_declaration", "method_declaration"],
+        // TypeScript 文件的 language_id 也是 "javascript"，两种语法的节点类型都在这里列出
+        "javascript" => &["function_declaration", "generator_function_declaration", "method_definition"],
+        "c" | "cpp" => &["function_definition"],
+        _ => &[],
+    }
+}
+
+/// 类级定义（包含其他定义的容器）的节点类型
+fn container_kinds(language: &str) -> &'static [&'static str] {
+    match language {
+        "rust" => &["impl_item", "trait_item", "mod_item", "struct_item", "enum_item", "union_item"],
+        "java" => &["class_declaration", "interface_declaration", "enum_declaration", "record_declaration"],
+        "python" => &["class_definition"],
+        "go" => &["type_declaration"],
+        "javascript" => &["class_declaration", "abstract_class_declaration", "interface_declaration"],
+        "cpp" => &["class_specifier", "struct_specifier", "namespace_definition"],
+        "c" => &["struct_specifier"],
+        _ => &[],
+    }
+}
+
+/// 可以被引用的其他定义的节点类型
+fn extra_definition_kinds(language: &str) -> &'static [&'static str] {
+    match language {
+        "rust" => &["const_item", "static_item", "type_item"],
+        "go" => &["type_spec", "const_spec"],
+        "javascript" => &["type_alias_declaration", "enum_declaration"],
+        _ => &[],
+    }
+}
+
+fn node_lines(node: &Node) -> (usize, usize) {
+    (node.start_position().row + 1, node.end_position().row + 1)
+}
+
+fn overlaps(node: &Node, (start, end): (usize, usize)) -> bool {
+    let (node_start, node_end) = node_lines(node);
+    node_start <= end && start <= node_end
+}
+
+/// 查找与行范围重叠的最内层函数；不在函数中时使用所在的类
+fn collect_enclosing<'a>(node: Node<'a>, lines: (usize, usize), language: &str, out: &mut Vec<Node<'a>>) {
+    let mut cursor = node.walk();
+    let children: Vec<Node<'a>> = node.named_children(&mut cursor).collect();
+    for child in children {
+        if !overlaps(&child, lines) {
+            continue;
+        }
+        if function_kinds(language).contains(&child.kind()) {
+            out.push(child);
+        } else if container_kinds(language).contains(&child.kind()) {
+            let before = out.len();
+            collect_enclosing(child, lines, language, out);
+            if out.len() == before {
+                out.push(child);
+            }
+        } else {
+            collect_enclosing(child, lines, language, out);
+        }
+    }
+}
+
+/// 获取定义的名称
+fn definition_name(node: &Node, source: &str) -> Option<String> {
+    let name = node
+        .child_by_field_name("name")
+        .or_else(|| node.child_by_field_name("declarator").and_then(|d| d.child_by_field_name("declarator")))?;
+    name.utf8_text(source.as_bytes()).ok().map(|s| s.to_string())
+}
+
+/// 收集节点中使用的全部标识符
+fn collect_identifiers(node: Node, source: &str, out: &mut HashSet<String>) {
+    if node.kind().ends_with("identifier") {
+        if let Ok(text) = node.utf8_text(source.as_bytes()) {
+            out.insert(text.to_string());
+        }
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        collect_identifiers(child, source, out);
+    }
+}
+
+/// 收集文件中全部可被引用的定义
+fn collect_definitions<'a>(node: Node<'a>, language: &str, out: &mut Vec<Node<'a>>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        let kind = child.kind();
+        if function_kinds(language).contains(&kind)
+            || container_kinds(language).contains(&kind)
+            || extra_definition_kinds(language).contains(&kind)
+        {
+            out.push(child);
+        }
+        collect_definitions(child, language, out);
+    }
+}
+
+fn make_snippet(file: &FileAst, node: &Node, referenced: bool) -> ContextSnippet {
+    let (start_line, end_line) = node_lines(node);
+    let text = file.source.lines().skip(start_line - 1).take(end_line - start_line + 1).collect::<Vec<_>>().join("\n");
+    ContextSnippet {
+        path: file.path.clone(),
+        language: file.language_id.clone(),
+        kind: node.kind().to_string(),
+        name: definition_name(node, &file.source).unwrap_or_default(),
+        start_line,
+        end_line,
+        text,
+        referenced,
+    }
+}
+
+/// 将过大的片段缩减为首行（签名）加上变更附近的若干行
+fn shrink_snippet(snippet: &ContextSnippet, lines: (usize, usize)) -> ContextSnippet {
+    let from = lines.0.saturating_sub(WINDOW_LINES).max(snippet.start_line + 1);
+    let to = (lines.1 + WINDOW_LINES).min(snippet.end_line);
+    let body: Vec<&str> = snippet.text.lines().collect();
+    let ellipsis = if snippet.language == "python" { "\n    # ..." } else { "\n    // ..." };
+
+    let mut text = body.first().copied().unwrap_or_default().to_string();
+    if from > snippet.start_line + 1 {
+        text.push_str(ellipsis);
+    }
+    for line in body.iter().take(to + 1 - snippet.start_line).skip(from - snippet.start_line) {
+        text.push('\n');
+        text.push_str(line);
+    }
+    if to < snippet.end_line {
+        text.push_str(ellipsis);
+    }
+
+    ContextSnippet { text, ..snippet.clone() }
+}
+
+/// 构建评审上下文
+///
+/// 先加入每个 hunk 所在的完整函数或类（超出预算时缩减为变更附近的片段），
+/// 再按出现顺序加入这些代码引用的、定义在变更文件中的其他定义，直到用完 token 预算。
+///
+/// # Arguments
+///
+/// * `files` - 变更后的文件及其变更行范围
+/// * `token_budget` - 上下文的 token 预算
+///
+/// # Returns
+///
+/// * `ReviewContext` - 代码上下文
+pub fn build_review_context(files: &[ChangedFileAst], token_budget: usize) -> ReviewContext {
+    let mut context = ReviewContext::default();
+    if token_budget == 0 {
+        return context;
+    }
+
+    let mut included: Vec<(PathBuf, usize, usize)> = Vec::new();
+    let mut referenced_names = HashSet::new();
+    let mut defined_names = HashSet::new();
+
+    // 变更所在的函数或类
+    for file in files {
+        let language = file.ast.language_id.as_str();
+        let root = file.ast.tree.root_node();
+        for &lines in &file.changed_lines {
+            let mut enclosing = Vec::new();
+            collect_enclosing(root, lines, language, &mut enclosing);
+
+            for node in enclosing {
+                let (start, end) = node_lines(&node);
+                if included.iter().any(|(p, s, e)| *p == file.ast.path && *s <= start && end <= *e) {
+                    continue;
+                }
+
+                let mut snippet = make_snippet(&file.ast, &node, false);
+                let remaining = token_budget.saturating_sub(context.estimated_tokens);
+                if estimate_tokens(&snippet.text) > remaining {
+                    snippet = shrink_snippet(&snippet, lines);
+                }
+                let tokens = estimate_tokens(&snippet.text);
+                if tokens > remaining {
+                    context.omitted += 1;
+                    continue;
+                }
+
+                collect_identifiers(node, &file.ast.source, &mut referenced_names);
+                defined_names.insert(snippet.name.clone());
+                included.push((file.ast.path.clone(), start, end));
+                context.estimated_tokens += tokens;
+                context.snippets.push(snippet);
+            }
+        }
+    }
+
+    // 被引用的定义
+    for file in files {
+        let language = file.ast.language_id.as_str();
+        let mut definitions = Vec::new();
+        collect_definitions(file.ast.tree.root_node(), language, &mut definitions);
+
+        for node in definitions {
+            let Some(name) = definition_name(&node, &file.ast.source) else {
+                continue;
+            };
+            if !referenced_names.contains(&name) || defined_names.contains(&name) {
+                continue;
+            }
+            let (start, end) = node_lines(&node);
+            if included.iter().any(|(p, s, e)| *p == file.ast.path && start <= *e && *s <= end) {
+                continue;
+            }
+
+            let snippet = make_snippet(&file.ast, &node, true);
+            let tokens = estimate_tokens(&snippet.text);
+            if context.estimated_tokens + tokens > token_budget {
+                context.omitted += 1;
+                continue;
+            }
+            defined_names.insert(name);
+            included.push((file.ast.path.clone(), start, end));
+            context.estimated_tokens += tokens;
+            context.snippets.push(snippet);
+        }
+    }
+
+    context
+}
+
+impl ReviewContext {
+    /// 将上下文格式化为评审提示中的 Markdown 章节
+    pub fn to_markdown(&self) -> String {
+        if self.snippets.is_empty() {
+            return String::new();
+        }
+
+        let mut text = String::from(
+            "## 完整代码上下文\n\n以下是变更后代码中变更所在的完整函数或类，以及它们引用的定义。\
+            评审时请结合这些上下文判断，不要将上下文中已定义的变量、函数或类型视为未定义。\n",
+        );
+        for (referenced, title) in [(false, "变更所在的函数或类"), (true, "引用的定义")] {
+            let snippets: Vec<&ContextSnippet> = self.snippets.iter().filter(|s| s.referenced == referenced).collect();
+            if snippets.is_empty() {
+                continue;
+            }
+            text.push_str(&format!("\n### {}\n", title));
+            for snippet in snippets {
+                text.push_str(&format!(
+                    "\n**{}** `{}` ({}，第 {}-{} 行)\n\n```{}\n{}\n```\n",
+                    snippet.path.display(),
+                    if snippet.name.is_empty() { snippet.kind.as_str() } else { snippet.name.as_str() },
+                    snippet.kind,
+                    snippet.start_line,
+                    snippet.end_line,
+                    snippet.language,
+                    snippet.text
+                ));
+            }
+        }
+        if self.omitted > 0 {
+            text.push_str(&format!("\n（受上下文预算限制，省略了 {} 个代码片段）\n", self.omitted));
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_management::settings::TreeSitterConfig;
+    use crate::tree_sitter_analyzer::TreeSitterAnalyzer;
+    use std::path::Path;
+
+    const SOURCE: &str = "struct Limits {\n    max: usize,\n}\n\nfn helper(limits: &Limits) -> usize {\n    limits.max\n}\n\nfn unrelated() {}\n\nfn changed(limits: &Limits) -> usize {\n    let value = helper(limits);\n    value + 1\n}\n";
+
+    fn changed_file(lines: (usize, usize)) -> ChangedFileAst {
+        let mut analyzer = TreeSitterAnalyzer::new(TreeSitterConfig::default()).unwrap();
+        let ast = analyzer.parse_source(Path::new("src/lib.rs"), SOURCE.to_string()).unwrap();
+        ChangedFileAst { ast, changed_lines: vec![lines] }
+    }
+
+    #[test]
+    fn test_enclosing_function_and_references() {
+        let context = build_review_context(&[changed_file((13, 13))], 1000);
+        let names: Vec<(&str, bool)> = context.snippets.iter().map(|s| (s.name.as_str(), s.referenced)).collect();
+        assert_eq!(names, vec![("changed", false), ("Limits", true), ("helper", true)]);
+        assert_eq!(context.snippets[0].start_line, 11);
+        assert_eq!(context.snippets[0].end_line, 14);
+        assert!(context.to_markdown().contains("fn changed(limits: &Limits) -> usize {"));
+    }
+
+    #[test]
+    fn test_typescript_definitions_are_referenced() {
+        let source = "type Limits = { max: number };\n\ninterface Options {\n    strict: boolean;\n}\n\nexport function changed(limits: Limits, options: Options): number {\n    return limits.max;\n}\n";
+        let mut analyzer = TreeSitterAnalyzer::new(TreeSitterConfig::default()).unwrap();
+        let ast = analyzer.parse_source(Path::new("src/limits.ts"), source.to_string()).unwrap();
+        let context = build_review_context(&[ChangedFileAst { ast, changed_lines: vec![(8, 8)] }], 1000);
+        let names: Vec<(&str, bool)> = context.snippets.iter().map(|s| (s.name.as_str(), s.referenced)).collect();
+        assert_eq!(names, vec![("changed", false), ("Limits", true), ("Options", true)]);
+    }
+
+    #[test]
+    fn test_token_budget_limits_references() {
+        let context = build_review_context(&[changed_file((13, 13))], 25);
+        assert_eq!(context.snippets.len(), 1);
+        assert_eq!(context.omitted, 2);
+        assert!(context.estimated_tokens <= 25);
+        assert!(build_review_context(&[changed_file((13, 13))], 0).snippets.is_empty());
+    }
+}
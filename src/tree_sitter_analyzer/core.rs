@@ -1,7 +1,11 @@
 use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::sync::Mutex;
 use std::time::SystemTime;
 use tree_sitter::{Language, Tree};
+use crate::config_management::settings::GrammarConfig;
+use super::ast_diff::DefinitionChange;
 pub use crate::core::errors::TreeSitterError; // Re-export for use in mod.rs
 
 // Add missing variants to TreeSitterError if needed
@@ -32,6 +36,85 @@ pub fn get_tree_sitter_go() -> Language {
     tree_sitter_go::language()
 }
 
+// JavaScript语言解析器（包含 JSX）
+pub fn get_tree_sitter_javascript() -> Language {
+    tree_sitter_javascript::language()
+}
+
+// TypeScript语言解析器
+pub fn get_tree_sitter_typescript() -> Language {
+    tree_sitter_typescript::language_typescript()
+}
+
+// TSX语言解析器
+pub fn get_tree_sitter_tsx() -> Language {
+    tree_sitter_typescript::language_tsx()
+}
+
+// C语言解析器
+pub fn get_tree_sitter_c() -> Language {
+    tree_sitter_c::language()
+}
+
+// C++语言解析器
+pub fn get_tree_sitter_cpp() -> Language {
+    tree_sitter_cpp::language()
+}
+
+lazy_static::lazy_static! {
+    // 已加载的动态库语法，按 (库路径, 符号) 缓存。
+    // 语法表数据位于动态库中，解析出的语法树和查询都引用它，因此动态库加载后不再卸载。
+    static ref LOADED_GRAMMARS: Mutex<HashMap<(PathBuf, String), Language>> = Mutex::new(HashMap::new());
+}
+
+/// 从动态库加载 tree-sitter 语法
+///
+/// 动态库需导出 `const TSLanguage *<symbol>(void)`，未配置 `symbol` 时使用 `tree_sitter_<language>`。
+/// 语法的 ABI 版本必须在当前 tree-sitter 支持的范围内。
+pub fn load_grammar_library(language: &str, grammar: &GrammarConfig) -> Result<Language, TreeSitterError> {
+    let path = match grammar.path.strip_prefix("~") {
+        Ok(rest) => dirs::home_dir().map(|home| home.join(rest)).unwrap_or_else(|| grammar.path.clone()),
+        Err(_) => grammar.path.clone(),
+    };
+    let symbol = grammar.symbol.clone().unwrap_or_else(|| format!("tree_sitter_{}", language.replace('-', "_")));
+
+    let mut loaded = LOADED_GRAMMARS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(language) = loaded.get(&(path.clone(), symbol.clone())) {
+        return Ok(*language);
+    }
+
+    // SAFETY: 加载用户配置的语法动态库并调用其导出的语言函数，这与链接语法 crate 时调用的
+    // `tree_sitter_<lang>()` 相同。ABI 版本兼容时动态库被有意泄漏，保证返回的 Language 始终有效；
+    // 不兼容时 Language 不会被使用，动态库随即卸载。
+    let loaded_language = unsafe {
+        let library = libloading::Library::new(&path).map_err(|e| {
+            TreeSitterError::LanguageError(format!("无法加载语法动态库 {}: {}", path.display(), e))
+        })?;
+        let constructor: libloading::Symbol<unsafe extern "C" fn() -> Language> =
+            library.get(symbol.as_bytes()).map_err(|e| {
+                TreeSitterError::LanguageError(format!("语法动态库 {} 中没有符号 {}: {}", path.display(), symbol, e))
+            })?;
+        let loaded_language = constructor();
+
+        let version = loaded_language.version();
+        if !(tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION..=tree_sitter::LANGUAGE_VERSION).contains(&version) {
+            drop(library);
+            return Err(TreeSitterError::LanguageError(format!(
+                "语法 {} 的 ABI 版本 {} 不受支持，需要 {} 到 {}",
+                language,
+                version,
+                tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION,
+                tree_sitter::LANGUAGE_VERSION
+            )));
+        }
+        std::mem::forget(library);
+        loaded_language
+    };
+
+    loaded.insert((path, symbol), loaded_language);
+    Ok(loaded_language)
+}
+
 // 文件AST结构
 // 这个结构体代表一个文件的语法分析树(AST)
 // 使用tree-sitter提供的实际Tree类型
@@ -113,6 +196,38 @@ pub struct DiffHunk {
     pub lines: Vec<String>,
 }
 
+impl DiffHunk {
+    /// 新增行在变更后文件中的行号（从 1 开始）
+    pub fn added_line_numbers(&self) -> Vec<usize> {
+        let mut line_number = self.new_range.start;
+        let mut added = Vec::new();
+        for line in &self.lines {
+            if line.starts_with('+') {
+                added.push(line_number);
+                line_number += 1;
+            } else if line.starts_with(' ') {
+                line_number += 1;
+            }
+        }
+        added
+    }
+
+    /// 变更前的内容片段（上下文行和删除行），以及删除行在片段中的行号（从 1 开始）
+    pub fn old_fragment(&self) -> (String, Vec<usize>) {
+        let mut fragment = String::new();
+        let mut removed = Vec::new();
+        let old_lines = self.lines.iter().filter(|l| l.starts_with('-') || l.starts_with(' '));
+        for (index, line) in old_lines.enumerate() {
+            if line.starts_with('-') {
+                removed.push(index + 1);
+            }
+            fragment.push_str(&line[1..]);
+            fragment.push('\n');
+        }
+        (fragment, removed)
+    }
+}
+
 // Legacy structure, keeping this for backward compatibility,
 // but we're migrating to ChangedFile
 #[derive(Debug, Clone)]
@@ -128,6 +243,7 @@ impl From<FileDiff> for ChangedFile {
     fn from(file_diff: FileDiff) -> Self {
         ChangedFile {
             path: file_diff.path,
+            old_path: file_diff.old_path,
             change_type: file_diff.change_type,
             hunks: file_diff.hunks,
             file_mode_change: None,
@@ -139,7 +255,7 @@ impl From<ChangedFile> for FileDiff {
     fn from(changed_file: ChangedFile) -> Self {
         FileDiff {
             path: changed_file.path,
-            old_path: None,
+            old_path: changed_file.old_path,
             change_type: changed_file.change_type,
             hunks: changed_file.hunks,
         }
@@ -150,6 +266,8 @@ impl From<ChangedFile> for FileDiff {
 #[derive(Debug, Clone)]
 pub struct ChangedFile {
     pub path: PathBuf,
+    /// 变更前的路径（`--- a/` 或 `rename from`），新增的文件为 None
+    pub old_path: Option<PathBuf>,
     pub change_type: ChangeType,
     pub hunks: Vec<DiffHunk>,
     pub file_mode_change: Option<String>,
@@ -194,12 +312,14 @@ impl GitDiff {
 }
 
 // Represents a node in the AST affected by changes
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AffectedNode {
     pub node_type: String,
     pub name: String,
     pub range: (usize, usize),
     pub is_public: bool,
+    /// 节点文本，可由 `range` 从源码恢复，不写入分析缓存
+    #[serde(skip)]
     pub content: Option<String>,
     pub line_range: (usize, usize),
     pub change_type: Option<String>,    // 新增：变更类型（added, deleted, modified）
@@ -262,6 +382,8 @@ pub struct DiffAnalysis {
     pub overall_summary: String,
     #[allow(dead_code)]
     pub change_analysis: ChangeAnalysis,
+    /// 从一处删除、在另一处新增（可能略有修改）的定义，按移动而非删除加新增处理
+    pub moved_code: Vec<DefinitionChange>,
 }
 
 // Mapping between diff and AST
@@ -350,6 +472,40 @@ pub fn calculate_hash(content: &str) -> String {
     format!("{:x}", hash)
 }
 
+/// 固定算法的 64 位 FNV-1a 哈希
+///
+/// 与 `DefaultHasher` 不同，结果不随 Rust 版本和进程变化，可以写入持久化的分析缓存。
+#[derive(Debug, Clone, Copy)]
+pub struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl StableHasher {
+    pub fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    /// 写入字符串并追加分隔符，使相邻字段的边界参与哈希
+    pub fn write_str(&mut self, text: &str) {
+        self.write(text.as_bytes());
+        self.write(&[0xff]);
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
 fn parse_line_range(range_str: &str) -> LineRange {
     let cleaned_range_str = range_str.trim_start_matches(|c| c == '-' || c == '+');
     let parts: Vec<&str> = cleaned_range_str.split(',').collect();
@@ -0,0 +1,269 @@
+// src/tree_sitter_analyzer/disk_cache.rs
+//! 持久化的分析缓存
+//!
+//! 每次运行 gitie 都是新进程，内存中的 AST 缓存无法跨调用复用。这里把从文件内容中提取的
+//! 结构信息（受影响节点的候选列表、定义和公共 API 签名）保存在仓库的 `.git/gitie/analysis-cache/`
+//! 目录中，以文件内容的哈希为键。内容不变的文件再次分析时无需重新解析。
+//!
+//! 每个条目记录生成它的 gitie 版本和结构查询的指纹，查询或版本变化后的条目视为过期，
+//! 读取时删除并重新生成。缓存只保存与文件内容相关的信息，节点和定义的文本在读取时从源码恢复。
+//!
+//! 条目的修改时间记录最近一次使用的时间。每天最多清理一次，删除长期未使用的条目，
+//! 并将缓存目录的总大小限制在 [`MAX_CACHE_BYTES`] 以内。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::api_surface::ApiItem;
+use super::ast_diff::{Definition, restore_definition_texts};
+use super::core::{AffectedNode, StableHasher, calculate_hash};
+
+/// 缓存目录，相对于仓库的 Git 目录
+const CACHE_DIR: &str = "gitie/analysis-cache";
+
+/// 超过此时间未使用的条目在清理时删除
+pub const MAX_ENTRY_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// 缓存目录的大小上限，超过时从最久未使用的条目开始删除
+pub const MAX_CACHE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// 两次清理之间的最短间隔，上次清理的时间记录在缓存目录中的标记文件上
+const PRUNE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+const PRUNE_MARKER: &str = "last-prune";
+
+/// 文件内容的缓存键：内容的 64 位 FNV-1a 哈希和长度
+///
+/// 键只用于命名缓存文件，不需要与 Git 的 blob OID 一致，也不需要抗碰撞。
+pub fn content_key(content: &str) -> String {
+    let mut hasher = StableHasher::default();
+    hasher.write(content.as_bytes());
+    format!("{:016x}{:x}", hasher.finish(), content.len())
+}
+
+/// 结构查询的指纹：gitie 版本、语法和查询源码变化后缓存条目失效
+pub fn query_fingerprint(grammar: &str, query_source: &str) -> String {
+    calculate_hash(&format!("{}\0{}\0{}", env!("CARGO_PKG_VERSION"), grammar, query_source))
+}
+
+/// 一个文件内容的缓存条目，各部分在首次需要时计算
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub fingerprint: String,
+    /// 生成条目时的文件路径；测试文件、头文件等判断依赖路径，路径不同时不复用
+    pub path: PathBuf,
+    /// 文件中所有结构节点（未关联具体 hunk）
+    #[serde(default)]
+    pub structure: Option<Vec<AffectedNode>>,
+    #[serde(default)]
+    pub definitions: Option<Vec<Definition>>,
+    #[serde(default)]
+    pub api: Option<Vec<ApiItem>>,
+}
+
+impl CacheEntry {
+    pub fn new(fingerprint: &str, path: &Path) -> Self {
+        Self { fingerprint: fingerprint.to_string(), path: path.to_path_buf(), ..Default::default() }
+    }
+
+    /// 从源码恢复缓存中省略的节点内容和定义文本
+    fn restore_texts(&mut self, source: &str) {
+        for node in self.structure.iter_mut().flatten() {
+            node.content = source.get(node.range.0..node.range.1).map(str::to_string);
+        }
+        if let Some(definitions) = self.definitions.as_mut() {
+            restore_definition_texts(definitions, source);
+        }
+    }
+}
+
+/// 保存在 `.git/gitie/analysis-cache/` 中的分析缓存
+#[derive(Debug, Clone)]
+pub struct AnalysisCache {
+    dir: PathBuf,
+}
+
+impl AnalysisCache {
+    /// 打开仓库的分析缓存；项目根目录不是 Git 仓库时返回 None
+    pub fn open(project_root: &Path) -> Option<Self> {
+        let dot_git = project_root.join(".git");
+        let git_dir = if dot_git.is_dir() {
+            dot_git
+        } else {
+            // 工作树和子模块中的 .git 是指向实际 Git 目录的文件
+            let content = fs::read_to_string(&dot_git).ok()?;
+            let target = PathBuf::from(content.strip_prefix("gitdir:")?.trim());
+            if target.is_absolute() { target } else { project_root.join(target) }
+        };
+        Some(Self { dir: git_dir.join(CACHE_DIR) })
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        let (prefix, rest) = key.split_at(2.min(key.len()));
+        self.dir.join(prefix).join(format!("{}.json", rest))
+    }
+
+    /// 读取内容对应的缓存条目；指纹不一致或无法读取的过期条目会被删除，路径不同时视为未命中
+    pub fn load(&self, key: &str, fingerprint: &str, path: &Path, source: &str) -> Option<CacheEntry> {
+        let entry_path = self.entry_path(key);
+        let data = fs::read_to_string(&entry_path).ok()?;
+        let mut entry = match serde_json::from_str::<CacheEntry>(&data) {
+            Ok(entry) if entry.fingerprint == fingerprint => entry,
+            _ => {
+                debug!("删除过期的分析缓存 {}", entry_path.display());
+                let _ = fs::remove_file(&entry_path);
+                return None;
+            }
+        };
+        if entry.path != path {
+            return None;
+        }
+        // 更新修改时间，清理时按最近使用的时间保留条目
+        let _ = fs::File::options()
+            .write(true)
+            .open(&entry_path)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+        entry.restore_texts(source);
+        Some(entry)
+    }
+
+    /// 写入缓存条目；先写临时文件再重命名，避免并发的 gitie 进程读到不完整的条目
+    pub fn store(&self, key: &str, entry: &CacheEntry) {
+        let entry_path = self.entry_path(key);
+        let result = (|| -> std::io::Result<()> {
+            fs::create_dir_all(entry_path.parent().unwrap_or(&self.dir))?;
+            let temp_path = entry_path.with_extension(format!("tmp{}", std::process::id()));
+            fs::write(&temp_path, serde_json::to_vec(entry)?)?;
+            fs::rename(&temp_path, &entry_path)
+        })();
+        if let Err(e) = result {
+            debug!("无法写入分析缓存 {}: {}", entry_path.display(), e);
+        }
+    }
+
+    /// 距上次清理超过一天时按默认的时间和大小限制清理缓存
+    pub fn prune_if_due(&self) {
+        let marker = self.dir.join(PRUNE_MARKER);
+        let due = fs::metadata(&marker)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_none_or(|elapsed| elapsed >= PRUNE_INTERVAL);
+        if !due || !self.dir.is_dir() {
+            return;
+        }
+        let _ = fs::write(&marker, b"");
+        let removed = self.prune(MAX_ENTRY_AGE, MAX_CACHE_BYTES);
+        if removed > 0 {
+            debug!("清理了 {} 个分析缓存条目", removed);
+        }
+    }
+
+    /// 删除超过 `max_age` 未使用的条目；剩余条目总大小超过 `max_bytes` 时，
+    /// 从最久未使用的条目开始删除。返回删除的条目数
+    pub fn prune(&self, max_age: Duration, max_bytes: u64) -> usize {
+        let now = SystemTime::now();
+        let mut entries = Vec::new();
+        let prefixes: Vec<PathBuf> = fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+            .map(|entry| entry.path())
+            .collect();
+        for prefix in &prefixes {
+            for entry in fs::read_dir(prefix).into_iter().flatten().flatten() {
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                entries.push((modified, metadata.len(), entry.path()));
+            }
+        }
+
+        // 从最近使用的条目开始保留
+        entries.sort_by_key(|(modified, ..)| std::cmp::Reverse(*modified));
+        let mut kept_bytes = 0;
+        let mut removed = 0;
+        for (modified, size, path) in entries {
+            let expired = now.duration_since(modified).is_ok_and(|age| age > max_age);
+            if expired || kept_bytes + size > max_bytes {
+                if fs::remove_file(&path).is_ok() {
+                    removed += 1;
+                }
+            } else {
+                kept_bytes += size;
+            }
+        }
+        // 非空目录删除失败，只会删除已经清空的前缀目录
+        for prefix in &prefixes {
+            let _ = fs::remove_dir(prefix);
+        }
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_key_is_stable() {
+        assert_eq!(content_key(""), "cbf29ce4842223250");
+        assert_eq!(content_key("hello\n"), "a9bc80cca21f28b36");
+        assert_ne!(content_key("a"), content_key("b"));
+    }
+
+    #[test]
+    fn test_cache_entries_are_invalidated() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join(".git")).unwrap();
+        let cache = AnalysisCache::open(root.path()).unwrap();
+        let source = "fn main() {}\n";
+        let key = content_key(source);
+        let path = Path::new("src/main.rs");
+
+        let mut entry = CacheEntry::new("v1", path);
+        entry.structure = Some(vec![AffectedNode::new("function".to_string(), "main".to_string(), (0, 12), false)]);
+        cache.store(&key, &entry);
+
+        let loaded = cache.load(&key, "v1", path, source).unwrap();
+        let nodes = loaded.structure.unwrap();
+        assert_eq!(nodes[0].content.as_deref(), Some("fn main() {}"));
+        assert!(cache.load(&key, "v1", Path::new("src/other.rs"), source).is_none());
+        assert!(cache.load(&key, "v1", path, source).is_some());
+        // 查询或版本变化后条目过期并被删除
+        assert!(cache.load(&key, "v2", path, source).is_none());
+        assert!(cache.load(&key, "v1", path, source).is_none());
+    }
+
+    #[test]
+    fn test_prune_removes_old_and_excess_entries() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join(".git")).unwrap();
+        let cache = AnalysisCache::open(root.path()).unwrap();
+        let now = SystemTime::now();
+        let keys: Vec<String> = ["a", "b", "c"].iter().map(|source| content_key(source)).collect();
+        for (i, key) in keys.iter().enumerate() {
+            cache.store(key, &CacheEntry::new("v1", Path::new("src/lib.rs")));
+            // 第一个条目最久未使用，第三个最近使用
+            let age = Duration::from_secs(60 * 60 * (3 - i as u64));
+            fs::File::options().write(true).open(cache.entry_path(key)).unwrap().set_modified(now - age).unwrap();
+        }
+        let size = fs::metadata(cache.entry_path(&keys[0])).unwrap().len();
+
+        // 超过保留时间的条目被删除
+        assert_eq!(cache.prune(Duration::from_secs(150 * 60), u64::MAX), 1);
+        assert!(!cache.entry_path(&keys[0]).exists());
+        // 超过大小上限时保留最近使用的条目
+        assert_eq!(cache.prune(MAX_ENTRY_AGE, size), 1);
+        assert!(!cache.entry_path(&keys[1]).exists());
+        assert!(cache.entry_path(&keys[2]).exists());
+
+        // 读取条目会刷新使用时间，使其不再过期
+        assert!(cache.load(&keys[2], "v1", Path::new("src/lib.rs"), "c").is_some());
+        assert_eq!(cache.prune(Duration::from_secs(60), u64::MAX), 0);
+    }
+}
@@ -0,0 +1,90 @@
+// src/tree_sitter_analyzer/go.rs
+use tree_sitter::Node;
+
+use crate::tree_sitter_analyzer::core::{ChangePattern, FileAst};
+
+// Go-specific change patterns
+pub enum GoChangePattern {
+    Interface,
+    MethodSet,
+    ExportedApi,
+    Struct,
+}
+
+// Function to convert Go-specific change patterns to generic ChangePattern
+pub fn to_generic_change_pattern(go_pattern: GoChangePattern) -> ChangePattern {
+    ChangePattern::LanguageSpecificChange(match go_pattern {
+        GoChangePattern::Interface => "GoInterfaceChange".to_string(),
+        GoChangePattern::MethodSet => "GoMethodSetChange".to_string(),
+        GoChangePattern::ExportedApi => "GoExportedApiChange".to_string(),
+        GoChangePattern::Struct => "GoStructChange".to_string(),
+    })
+}
+
+/// 返回节点所在的声明节点：名称标识符返回其父节点，其余原样返回
+pub fn go_declaration_node<'a>(node: Node<'a>) -> Node<'a> {
+    match node.kind() {
+        "identifier" | "field_identifier" | "type_identifier" => node.parent().unwrap_or(node),
+        _ => node,
+    }
+}
+
+/// 根据类型声明的具体类型细分为 `struct`、`interface` 或 `type`
+pub fn go_type_kind(node: Node) -> &'static str {
+    let declaration = go_declaration_node(node);
+    match declaration.child_by_field_name("type").map(|t| t.kind()) {
+        Some("struct_type") if declaration.kind() == "type_spec" => "struct",
+        Some("interface_type") if declaration.kind() == "type_spec" => "interface",
+        _ => "type",
+    }
+}
+
+/// 返回方法接收者的类型名称（去掉指针），如 `func (s *Server) Run()` 返回 `Server`
+pub fn go_receiver_type(node: Node, source: &[u8]) -> Option<String> {
+    let declaration = go_declaration_node(node);
+    if declaration.kind() != "method_declaration" {
+        return None;
+    }
+    let receiver = declaration.child_by_field_name("receiver")?;
+    let parameter = receiver.named_child(0)?;
+    let receiver_type = parameter.child_by_field_name("type")?;
+    let text = receiver_type.utf8_text(source).ok()?;
+    Some(text.trim_start_matches('*').trim().to_string())
+}
+
+/// 标识符是否导出（首字母大写）
+pub fn is_go_exported_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_uppercase())
+}
+
+/// 判断 Go 声明是否属于公共 API
+///
+/// 首字母大写的包级声明是导出的；方法还要求接收者类型导出，
+/// 函数体内的局部声明以及 `internal` 包中的声明不属于公共 API。
+pub fn is_go_node_public_impl(node: &Node, file_ast: &FileAst) -> bool {
+    let source = file_ast.source.as_bytes();
+    if file_ast.path.components().any(|c| c.as_os_str() == "internal") {
+        return false;
+    }
+    let declaration = go_declaration_node(*node);
+
+    let mut parent = declaration.parent();
+    while let Some(p) = parent {
+        if matches!(p.kind(), "block" | "func_literal") {
+            return false;
+        }
+        parent = p.parent();
+    }
+
+    let name = match declaration.child_by_field_name("name") {
+        Some(name) => name.utf8_text(source).unwrap_or(""),
+        None => return false,
+    };
+    if !is_go_exported_name(name) {
+        return false;
+    }
+    match go_receiver_type(declaration, source) {
+        Some(receiver) => is_go_exported_name(&receiver),
+        None => true,
+    }
+}
@@ -8,6 +8,7 @@ use crate::{
     core::errors::TreeSitterError,
     tree_sitter_analyzer::core::{AffectedNode, FileAst, ChangePattern},
     tree_sitter_analyzer::analyzer::TreeSitterAnalyzer, // To access is_node_public
+    tree_sitter_analyzer::queries::builtin_query,
 };
 
 // Types of relationships between Java classes
@@ -289,78 +290,71 @@ fn extract_java_class_name_from_node(class_node: Node, file_ast: &FileAst) -> Op
 }
 
 
-// This function is now specific to Java and can be called by the analyzer.
-#[allow(dead_code)]
-pub fn is_java_node_public_impl(node: &tree_sitter::Node, file_ast: &FileAst) -> bool {
-    // Check for a "modifiers" child node first
+/// 可以独立判断可见性的 Java 声明节点类型
+const JAVA_DECLARATION_KINDS: &[&str] = &[
+    "class_declaration", "interface_declaration", "enum_declaration", "record_declaration",
+    "annotation_type_declaration", "method_declaration", "constructor_declaration", "field_declaration",
+    "constant_declaration", "enum_constant", "annotation_type_element_declaration",
+];
+
+/// 声明的修饰符关键字（不含注解），如 `public`、`static`、`default`
+pub fn java_modifier_keywords(node: &Node) -> Vec<&'static str> {
     let mut cursor = node.walk();
-    if node.child_count() > 0 && cursor.goto_first_child() {
-        loop {
-            let child_node = cursor.node();
-            if child_node.kind() == "modifiers" {
-                if let Ok(modifier_text) = child_node.utf8_text(file_ast.source.as_bytes()) {
-                    if modifier_text.contains("public") {
-                        return true;
-                    }
-                    if modifier_text.contains("private") || modifier_text.contains("protected") {
-                        return false; // Explicitly private or protected
-                    }
-                }
-                // If modifiers node exists but no explicit public, private, or protected, it might be package-private
-                // For top-level types, this means not public. For members, it depends.
-                // However, if we found a `modifiers` node and it didn't say `public`, we assume not explicitly public.
-                return false; 
-            }
-            if !cursor.goto_next_sibling() {
-                break;
-            }
+    let Some(modifiers) = node.children(&mut cursor).find(|c| c.kind() == "modifiers") else {
+        return Vec::new();
+    };
+    let mut modifier_cursor = modifiers.walk();
+    let keywords = modifiers.children(&mut modifier_cursor).filter(|c| !c.is_named()).map(|c| c.kind()).collect();
+    keywords
+}
+
+/// 判断 Java 声明是否属于公共 API
+///
+/// 接口和注解类型中的成员默认公开（`private` 除外），枚举常量随枚举公开，
+/// 其余声明需要 `public` 修饰符。外层类型也必须公开，局部类和匿名类不公开。
+pub fn is_java_node_public_impl(node: &tree_sitter::Node, _file_ast: &FileAst) -> bool {
+    let mut declaration = *node;
+    while !JAVA_DECLARATION_KINDS.contains(&declaration.kind()) {
+        match declaration.parent() {
+            Some(parent) => declaration = parent,
+            None => return false,
         }
     }
+    if !is_java_declaration_visible(&declaration) {
+        return false;
+    }
 
-    // Fallback for top-level types or members where `modifiers` might not be a direct child
-    // or for interface methods (implicitly public).
-    // This part requires careful consideration of Java's visibility rules.
-
-    // Interface methods are implicitly public if not marked private (Java 9+) or static.
-    if node.kind() == "method_declaration" && node.parent().map_or(false, |p| p.kind() == "interface_body") {
-        // Check if it has `private` or `static` modifiers, which would make it not implicitly public in all contexts.
-        // This check is simplified; a full modifier check might be needed.
-        if let Ok(node_text) = node.utf8_text(file_ast.source.as_bytes()) {
-            if !node_text.contains("private") && !node_text.contains("static") {
-                 // Could also check for `default` modifier if needed.
-                return true; 
+    let mut current = declaration.parent();
+    while let Some(parent) = current {
+        match parent.kind() {
+            "block" | "constructor_body" | "lambda_expression" | "object_creation_expression" => return false,
+            "class_declaration" | "interface_declaration" | "enum_declaration" | "record_declaration"
+            | "annotation_type_declaration" => {
+                if !is_java_declaration_visible(&parent) {
+                    return false;
+                }
             }
+            _ => {}
         }
+        current = parent.parent();
     }
+    true
+}
 
-    // Default for top-level classes/interfaces without explicit modifiers is package-private (not public).
-    if node.kind() == "class_declaration" || node.kind() == "interface_declaration" {
-        if node.parent().map_or(false, |p| p.kind() == "program" || p.kind() == "compilation_unit") {
-             // If it's a top-level class/interface and we haven't found `public` in a `modifiers` node,
-             // it's package-private, hence not public.
-            return false;
-        }
+/// 声明本身在其所在的类型中是否公开，不考虑外层类型
+fn is_java_declaration_visible(declaration: &Node) -> bool {
+    let modifiers = java_modifier_keywords(declaration);
+    match declaration.parent().map(|c| c.kind()) {
+        Some("interface_body") | Some("annotation_type_body") => !modifiers.contains(&"private"),
+        _ if declaration.kind() == "enum_constant" => true,
+        _ => modifiers.contains(&"public"),
     }
-    
-    // Default to false if no explicit "public" modifier is found through the AST.
-    false
 }
 
-
-// It's better to have query strings directly in the language-specific files.
+/// Java 结构查询，与分析器使用同一份内置查询文件
 #[allow(dead_code)]
 pub fn get_java_query_pattern_str() -> &'static str {
-    r#"
-    (class_declaration) @class.declaration
-    (interface_declaration) @interface.declaration
-    (enum_declaration) @enum.declaration
-    (annotation_type_declaration) @annotation_type.declaration
-    (method_declaration) @method.declaration
-    (constructor_declaration) @constructor.declaration
-    (field_declaration) @field.declaration
-    (package_declaration) @package.declaration
-    (import_declaration) @import.declaration
-    "#
+    builtin_query("java").unwrap_or_default()
 }
 
 /// Extract Java package name
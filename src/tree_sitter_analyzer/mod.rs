@@ -1,12 +1,17 @@
 // src/tree_sitter_analyzer/mod.rs
 pub mod core;
 pub mod analyzer;
+pub mod context;
 pub mod java;
 pub mod rust;
+pub mod python;
+pub mod go;
 pub mod simple_diff;
 pub mod parse_utils;
-// Future: pub mod python;
-// Future: pub mod go;
+pub mod queries;
+pub mod ast_diff;
+pub mod api_surface;
+pub mod disk_cache;
 // Future: pub mod javascript;
 
 // Re-export key items for easier access from outside this module.
@@ -16,6 +21,8 @@ pub use self::parse_utils::{parse_git_diff_text, detect_language};
 pub use self::java::JavaProjectStructure; // Moved back to java.rs
 pub use self::java::{JavaClass, JavaMethod, JavaMethodParam, JavaClassRelation, JavaRelationType, JavaPackage, JavaChangePattern};
 pub use self::rust::RustChangePattern;
+pub use self::ast_diff::FileVersions;
+pub use self::api_surface::{ApiReport, SemverBump};
 // Re-export language-specific functions if they are meant to be part of the public API of this module
 // For example, if you want to allow direct access to Java-specific parsing outside of the TreeSitterAnalyzer facade:
 // pub use self::java::{extract_java_package_name, extract_java_class_name};
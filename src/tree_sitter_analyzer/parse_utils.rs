@@ -27,13 +27,15 @@ pub fn parse_git_diff_text(diff_text: &str) -> Result<GitDiff, String> {
         // Parse file headers
         if line.starts_with("diff --git ") {
             // Add previous file if exists
-            if let Some(file) = current_file.take() {
+            if let Some(mut file) = current_file.take() {
+                file.hunks = std::mem::take(&mut current_hunks);
                 git_diff.changed_files.push(file);
             }
             
             // Create new file entry
             current_file = Some(ChangedFile {
                 path: PathBuf::new(),
+                old_path: None,
                 change_type: ChangeType::Modified,
                 hunks: Vec::new(),
                 file_mode_change: None,
@@ -47,6 +49,23 @@ pub fn parse_git_diff_text(diff_text: &str) -> Result<GitDiff, String> {
                 file.path = PathBuf::from(&line[6..]);
             }
         }
+        else if line.starts_with("--- a/") && line.len() > 6 && current_hunks.is_empty() {
+            if let Some(ref mut file) = current_file {
+                file.old_path = Some(PathBuf::from(&line[6..]));
+            }
+        }
+        // Parse renames; pure renames have no ---/+++ lines
+        else if let Some(from) = line.strip_prefix("rename from ") {
+            if let Some(ref mut file) = current_file {
+                file.old_path = Some(PathBuf::from(from));
+                file.change_type = ChangeType::Renamed;
+            }
+        }
+        else if let Some(to) = line.strip_prefix("rename to ") {
+            if let Some(ref mut file) = current_file {
+                file.path = PathBuf::from(to);
+            }
+        }
         // Parse file change type
         else if line.starts_with("new file mode ") {
             if let Some(ref mut file) = current_file {
@@ -108,6 +127,15 @@ pub fn parse_git_diff_text(diff_text: &str) -> Result<GitDiff, String> {
         git_diff.changed_files.push(file);
     }
     
+    // Deleted files only have a `--- a/` path
+    for file in &mut git_diff.changed_files {
+        if file.path.as_os_str().is_empty()
+            && let Some(old_path) = &file.old_path
+        {
+            file.path = old_path.clone();
+        }
+    }
+    
     Ok(git_diff)
 }
 
@@ -0,0 +1,157 @@
+// src/tree_sitter_analyzer/python.rs
+use tree_sitter::Node;
+
+use crate::tree_sitter_analyzer::core::{AffectedNode, ChangePattern, FileAst};
+
+// Python-specific change patterns
+pub enum PythonChangePattern {
+    Decorator,
+    Async,
+    PublicApi,
+    ClassStructure,
+}
+
+// Function to convert Python-specific change patterns to generic ChangePattern
+pub fn to_generic_change_pattern(python_pattern: PythonChangePattern) -> ChangePattern {
+    ChangePattern::LanguageSpecificChange(match python_pattern {
+        PythonChangePattern::Decorator => "PythonDecoratorChange".to_string(),
+        PythonChangePattern::Async => "PythonAsyncChange".to_string(),
+        PythonChangePattern::PublicApi => "PythonPublicApiChange".to_string(),
+        PythonChangePattern::ClassStructure => "PythonClassStructureChange".to_string(),
+    })
+}
+
+/// 解析模块顶层的 `__all__` 列表，未定义时返回 None
+pub fn extract_python_all_exports(file_ast: &FileAst) -> Option<Vec<String>> {
+    let source = file_ast.source.as_bytes();
+    let root = file_ast.tree.root_node();
+    let mut cursor = root.walk();
+    let mut exports: Option<Vec<String>> = None;
+
+    for statement in root.children(&mut cursor) {
+        if statement.kind() != "expression_statement" {
+            continue;
+        }
+        let Some(assignment) = statement.named_child(0) else {
+            continue;
+        };
+        let is_all = |n: Node| n.utf8_text(source).is_ok_and(|t| t == "__all__");
+        let (target, value) = match assignment.kind() {
+            "assignment" | "augmented_assignment" => (
+                assignment.child_by_field_name("left"),
+                assignment.child_by_field_name("right"),
+            ),
+            _ => continue,
+        };
+        if !target.is_some_and(is_all) {
+            continue;
+        }
+        let Some(value) = value else {
+            continue;
+        };
+        if assignment.kind() == "assignment" {
+            exports = Some(Vec::new());
+        }
+        let names = exports.get_or_insert_with(Vec::new);
+        let mut list_cursor = value.walk();
+        for item in value.named_children(&mut list_cursor) {
+            if item.kind() == "string" {
+                let text = item.utf8_text(source).unwrap_or("");
+                names.push(text.trim_matches(|c| c == '"' || c == '\'').to_string());
+            }
+        }
+    }
+
+    exports
+}
+
+/// 名称按 Python 约定是否公开：不以下划线开头，或为 `__dunder__` 方法
+pub fn is_public_python_name(name: &str) -> bool {
+    !name.starts_with('_') || (name.starts_with("__") && name.ends_with("__") && name.len() > 4)
+}
+
+/// 判断 Python 定义是否属于公共 API
+///
+/// 顶层定义在定义了 `__all__` 时以其为准，否则按名称约定判断；
+/// 类成员需要所在类和成员名都公开；函数内部的定义不公开。
+pub fn is_python_node_public_impl(node: &Node, file_ast: &FileAst, all_exports: Option<&[String]>) -> bool {
+    let source = file_ast.source.as_bytes();
+    let mut definition = *node;
+    if definition.kind() == "identifier" {
+        match definition.parent() {
+            Some(parent) => definition = parent,
+            None => return false,
+        }
+    }
+    if definition.kind() == "decorated_definition" {
+        match definition.child_by_field_name("definition") {
+            Some(inner) => definition = inner,
+            None => return false,
+        }
+    }
+    if matches!(definition.kind(), "expression_statement" | "assignment") {
+        // `__all__` 本身就是公开 API 的声明
+        return true;
+    }
+    if !matches!(definition.kind(), "function_definition" | "class_definition") {
+        return false;
+    }
+
+    let name = definition
+        .child_by_field_name("name")
+        .and_then(|name| name.utf8_text(source).ok())
+        .unwrap_or("")
+        .to_string();
+    let mut parent = definition.parent();
+    while let Some(p) = parent {
+        match p.kind() {
+            "function_definition" => return false,
+            "class_definition" => {
+                return is_public_python_name(&name) && is_python_node_public_impl(&p, file_ast, all_exports);
+            }
+            "module" => break,
+            _ => parent = p.parent(),
+        }
+    }
+
+    match all_exports {
+        Some(exports) => exports.contains(&name),
+        None => is_public_python_name(&name),
+    }
+}
+
+/// 返回装饰器文本（不含 `@`），用于识别路由、属性等特殊定义
+pub fn python_decorators(node: &AffectedNode) -> Vec<String> {
+    node.content
+        .as_deref()
+        .unwrap_or("")
+        .lines()
+        .map(|l| l.trim())
+        .take_while(|l| l.starts_with('@'))
+        .map(|l| l.trim_start_matches('@').to_string())
+        .collect()
+}
+
+/// 装饰器是否声明了 Web 路由（Flask、FastAPI、Django REST 等）
+pub fn is_python_route_decorator(decorator: &str) -> bool {
+    let name = decorator.split('(').next().unwrap_or("");
+    name.ends_with(".route")
+        || name.ends_with(".get")
+        || name.ends_with(".post")
+        || name.ends_with(".put")
+        || name.ends_with(".delete")
+        || name.ends_with(".patch")
+        || name == "api_view"
+}
+
+/// 返回受影响节点对应的函数或类定义节点，装饰器定义返回其内部定义
+pub fn python_definition_at<'a>(file_ast: &'a FileAst, range: (usize, usize)) -> Option<Node<'a>> {
+    let mut node = file_ast.tree.root_node().descendant_for_byte_range(range.0, range.1)?;
+    if node.kind() == "identifier" {
+        node = node.parent()?;
+    }
+    if node.kind() == "decorated_definition" {
+        node = node.child_by_field_name("definition")?;
+    }
+    matches!(node.kind(), "function_definition" | "class_definition").then_some(node)
+}
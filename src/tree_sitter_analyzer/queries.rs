@@ -0,0 +1,133 @@
+//! tree-sitter 结构查询文件
+//!
+//! 每种语法的结构查询保存在 `assets/queries/<lang>/structure.scm` 中并内置到程序里。
+//! 用户可以在 `~/.config/gitie/queries/<lang>/` 或仓库内的 `.gitie/queries/<lang>/` 中放置 `.scm` 文件：
+//! 名为 `structure.scm` 的文件替换内置查询，其他 `.scm` 文件按文件名顺序追加在查询之后，
+//! 用于识别团队内部的注解、宏等框架写法。两个目录都存在 `structure.scm` 时以仓库内的为准。
+
+use crate::config_management::settings::AppConfig;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 替换内置查询的文件名
+pub const STRUCTURE_QUERY_FILE: &str = "structure.scm";
+
+/// 仓库内查询目录，相对于项目根目录
+pub const PROJECT_QUERIES_DIR: &str = ".gitie/queries";
+
+/// 内置查询：(语法标识, 查询内容)
+const BUILTIN_QUERIES: &[(&str, &str)] = &[
+    ("rust", include_str!("../../assets/queries/rust/structure.scm")),
+    ("java", include_str!("../../assets/queries/java/structure.scm")),
+    ("python", include_str!("../../assets/queries/python/structure.scm")),
+    ("go", include_str!("../../assets/queries/go/structure.scm")),
+    ("javascript", include_str!("../../assets/queries/javascript/structure.scm")),
+    ("typescript", include_str!("../../assets/queries/typescript/structure.scm")),
+    ("tsx", include_str!("../../assets/queries/tsx/structure.scm")),
+    ("c", include_str!("../../assets/queries/c/structure.scm")),
+    ("cpp", include_str!("../../assets/queries/cpp/structure.scm")),
+];
+
+/// 返回语法的内置结构查询
+pub fn builtin_query(grammar: &str) -> Option<&'static str> {
+    BUILTIN_QUERIES.iter().find(|(g, _)| *g == grammar).map(|(_, query)| *query)
+}
+
+/// 返回语法的查询目录，按优先级从低到高排列：用户配置目录、仓库目录
+pub fn query_dirs(grammar: &str, project_root: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Ok(config_dir) = AppConfig::get_user_config_dir() {
+        dirs.push(config_dir.join("queries").join(grammar));
+    }
+    if !project_root.as_os_str().is_empty() {
+        dirs.push(project_root.join(PROJECT_QUERIES_DIR).join(grammar));
+    }
+    dirs
+}
+
+/// 组合后的查询内容及其来源文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuerySource {
+    /// 查询内容
+    pub source: String,
+    /// 参与组合的用户查询文件，为空表示只使用内置查询
+    pub files: Vec<PathBuf>,
+}
+
+/// 加载语法的结构查询，组合内置查询和查询目录中的 `.scm` 文件
+///
+/// 既没有内置查询也没有用户查询文件时返回 None。
+pub fn load_query_source(grammar: &str, dirs: &[PathBuf]) -> Option<QuerySource> {
+    let mut base = builtin_query(grammar).map(|q| q.to_string());
+    let mut files = Vec::new();
+    let mut extensions = Vec::new();
+
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "scm"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) => {
+                    tracing::warn!("无法读取查询文件 {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            if path.file_name().is_some_and(|name| name == STRUCTURE_QUERY_FILE) {
+                // 后出现的目录优先级更高，替换之前的基础查询
+                files.retain(|f: &PathBuf| f.file_name().is_none_or(|name| name != STRUCTURE_QUERY_FILE));
+                base = Some(content);
+            } else {
+                extensions.push(content);
+            }
+            files.push(path);
+        }
+    }
+
+    if base.is_none() && extensions.is_empty() {
+        return None;
+    }
+    let mut source = base.unwrap_or_default();
+    for extension in extensions {
+        if !source.is_empty() && !source.ends_with('\n') {
+            source.push('\n');
+        }
+        source.push_str(&extension);
+    }
+    Some(QuerySource { source, files })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_query_source_overrides_and_extends() {
+        let user = tempfile::tempdir().unwrap();
+        let project = tempfile::tempdir().unwrap();
+        let dirs = vec![user.path().to_path_buf(), project.path().to_path_buf()];
+
+        let builtin = load_query_source("rust", &dirs).unwrap();
+        assert_eq!(builtin.source, builtin_query("rust").unwrap());
+        assert!(builtin.files.is_empty());
+        assert!(load_query_source("kotlin", &dirs).is_none());
+
+        fs::write(user.path().join("macros.scm"), "(macro_invocation) @macro_call.declaration\n").unwrap();
+        let extended = load_query_source("rust", &dirs).unwrap();
+        assert!(extended.source.starts_with(builtin_query("rust").unwrap()));
+        assert!(extended.source.ends_with("(macro_invocation) @macro_call.declaration\n"));
+
+        fs::write(user.path().join(STRUCTURE_QUERY_FILE), "(struct_item) @struct.declaration\n").unwrap();
+        fs::write(project.path().join(STRUCTURE_QUERY_FILE), "(enum_item) @enum.declaration\n").unwrap();
+        let overridden = load_query_source("rust", &dirs).unwrap();
+        assert_eq!(overridden.source, "(enum_item) @enum.declaration\n(macro_invocation) @macro_call.declaration\n");
+        assert_eq!(overridden.files, vec![user.path().join("macros.scm"), project.path().join(STRUCTURE_QUERY_FILE)]);
+    }
+}
@@ -5,6 +5,7 @@ use crate::{
     core::errors::TreeSitterError,
     tree_sitter_analyzer::core::{AffectedNode, FileAst, ChangePattern},
     tree_sitter_analyzer::analyzer::TreeSitterAnalyzer, // To access is_node_public
+    tree_sitter_analyzer::queries::builtin_query,
 };
 
 // Rust-specific change patterns
@@ -57,11 +58,11 @@ pub fn analyze_rust_file_structure_impl(file_ast: &FileAst, analyzer: &TreeSitte
             };
 
             let (node_type, name_opt) = match capture_name.as_str() {
-                "function" => ("function".to_string(), extract_name(node, "name")),
-                "struct" => ("struct".to_string(), extract_name(node, "name")),
-                "enum" => ("enum".to_string(), extract_name(node, "name")),
-                "trait" => ("trait".to_string(), extract_name(node, "name")),
-                "impl_item" => {
+                "function.declaration" => ("function".to_string(), extract_name(node, "name")),
+                "struct.declaration" => ("struct".to_string(), extract_name(node, "name")),
+                "enum.declaration" => ("enum".to_string(), extract_name(node, "name")),
+                "trait.declaration" => ("trait".to_string(), extract_name(node, "name")),
+                "impl.declaration" => {
                     // For impl blocks, the "name" is often the type being implemented or the trait for a type.
                     // This might need more sophisticated extraction, e.g., getting the text of the type node.
                     let type_node = node.child_by_field_name("type");
@@ -77,14 +78,14 @@ pub fn analyze_rust_file_structure_impl(file_ast: &FileAst, analyzer: &TreeSitte
                     };
                     ("impl".to_string(), Some(name))
                 }
-                "module" => ("module".to_string(), extract_name(node, "name")),
-                "const" => ("const".to_string(), extract_name(node, "name")),
-                "static" => ("static".to_string(), extract_name(node, "name")),
-                "type_alias" => ("type_alias".to_string(), extract_name(node, "name")),
-                "macro_definition" => ("macro".to_string(), extract_name(node, "name")),
+                "module.declaration" => ("module".to_string(), extract_name(node, "name")),
+                "const.declaration" => ("const".to_string(), extract_name(node, "name")),
+                "static.declaration" => ("static".to_string(), extract_name(node, "name")),
+                "type_alias.declaration" => ("type_alias".to_string(), extract_name(node, "name")),
+                "macro.declaration" => ("macro".to_string(), extract_name(node, "name")),
                 // "use_declaration" and "attribute" might not need a specific "name" in the same way,
                 // or their "name" is the full path/text.
-                "use" => ("use_declaration".to_string(), Some(node.utf8_text(file_ast.source.as_bytes()).unwrap_or_default().to_string())),
+                "use.declaration" => ("use_declaration".to_string(), Some(node.utf8_text(file_ast.source.as_bytes()).unwrap_or_default().to_string())),
                 _ => continue, // Skip other captures or non-primary captures
             };
 
@@ -106,64 +107,96 @@ pub fn analyze_rust_file_structure_impl(file_ast: &FileAst, analyzer: &TreeSitte
     Ok(nodes)
 }
 
-#[allow(dead_code)]
-pub fn is_rust_node_public_impl(node: &tree_sitter::Node, file_ast: &FileAst) -> bool {
-    // Check for a `visibility_modifier` child node or `pub` keyword directly.
+/// 可以独立判断可见性的 Rust 条目节点类型
+const RUST_ITEM_KINDS: &[&str] = &[
+    "function_item", "function_signature_item", "struct_item", "union_item", "enum_item", "enum_variant",
+    "field_declaration", "trait_item", "impl_item", "mod_item", "const_item", "static_item", "type_item",
+    "associated_type", "macro_definition",
+];
+
+/// 从名称等子节点向上找到所属的条目节点
+fn rust_item_node(node: Node) -> Node {
+    let mut current = node;
+    while !RUST_ITEM_KINDS.contains(&current.kind()) {
+        match current.parent() {
+            Some(parent) => current = parent,
+            None => return node,
+        }
+    }
+    current
+}
+
+/// 条目的可见性修饰符是否为不受限的 `pub`；`pub(crate)`、`pub(super)` 和 `pub(in path)` 不算公开
+fn has_pub_visibility(node: &Node, source: &[u8]) -> bool {
     let mut cursor = node.walk();
-    if node.child_count() > 0 && cursor.goto_first_child() {
-        loop {
-            let child = cursor.node();
-            if child.kind() == "visibility_modifier" {
-                // The text of visibility_modifier itself will be like "pub", "pub(crate)", etc.
-                if let Ok(vis_text) = child.utf8_text(file_ast.source.as_bytes()) {
-                    if vis_text.starts_with("pub") {
-                        return true;
-                    }
-                }
-            }
-            // Sometimes `pub` can be a direct keyword child without being wrapped in `visibility_modifier`
-            // (though less common for items that `visibility_modifier` applies to).
-            // This check might be redundant if tree-sitter-rust always uses `visibility_modifier`.
-            if child.kind() == "pub" { // Check if there's a node kind named "pub"
-                 if let Ok(text) = child.utf8_text(file_ast.source.as_bytes()) {
-                    if text == "pub" {
-                        return true;
-                    }
-                }
-            }
-            if !cursor.goto_next_sibling() {
-                break;
-            }
+    let visibility = node.children(&mut cursor).find(|c| c.kind() == "visibility_modifier");
+    visibility.is_some_and(|v| v.utf8_text(source).is_ok_and(|text| text.trim() == "pub"))
+}
+
+/// 宏定义前是否带有 `#[macro_export]` 属性
+fn has_macro_export(node: &Node, source: &[u8]) -> bool {
+    let mut sibling = node.prev_sibling();
+    while let Some(s) = sibling {
+        if s.kind() != "attribute_item" {
+            break;
+        }
+        if s.utf8_text(source).is_ok_and(|text| text.contains("macro_export")) {
+            return true;
         }
+        sibling = s.prev_sibling();
     }
-    // If the node itself is a `visibility_modifier` starting with `pub` (e.g. in some macro outputs or unusual ASTs)
-    if node.kind() == "visibility_modifier" {
-        if let Ok(vis_text) = node.utf8_text(file_ast.source.as_bytes()) {
-            if vis_text.starts_with("pub") {
-                return true;
+    false
+}
+
+/// 条目所在的 trait 或 impl 块
+fn enclosing_block_owner<'a>(node: &Node<'a>) -> Option<Node<'a>> {
+    node.parent()
+        .filter(|p| p.kind() == "declaration_list")
+        .and_then(|p| p.parent())
+        .filter(|p| matches!(p.kind(), "trait_item" | "impl_item"))
+}
+
+/// 判断 Rust 条目是否属于 crate 的公共 API
+///
+/// 只有不受限的 `pub` 才算公开；trait 中的条目随 trait 公开，trait 实现块本身公开但其中的方法
+/// 不单独计入，枚举变体随枚举公开。所有外层模块、结构体和 trait 也必须公开，
+/// 函数体内的条目不公开。`#[macro_export]` 的宏始终公开。
+pub fn is_rust_node_public_impl(node: &tree_sitter::Node, file_ast: &FileAst) -> bool {
+    let source = file_ast.source.as_bytes();
+    let item = rust_item_node(*node);
+    let owner = enclosing_block_owner(&item);
+    let own_public = match item.kind() {
+        "macro_definition" => return has_macro_export(&item, source),
+        "impl_item" => item.child_by_field_name("trait").is_some(),
+        "enum_variant" => true,
+        _ => match owner {
+            Some(owner) if owner.kind() == "trait_item" => true,
+            Some(owner) if owner.child_by_field_name("trait").is_some() => false,
+            _ => has_pub_visibility(&item, source),
+        },
+    };
+    if !own_public {
+        return false;
+    }
+
+    let mut current = item;
+    while let Some(parent) = current.parent() {
+        match parent.kind() {
+            "block" | "function_item" | "closure_expression" => return false,
+            "trait_item" | "enum_item" | "struct_item" | "union_item" | "mod_item" => {
+                if !has_pub_visibility(&parent, source) {
+                    return false;
+                }
             }
+            _ => {}
         }
+        current = parent;
     }
-
-    // Default to not public if no explicit `pub` modifier found.
-    // In Rust, items are private by default.
-    false
+    true
 }
 
+/// Rust 结构查询，与分析器使用同一份内置查询文件
 #[allow(dead_code)]
 pub fn get_rust_query_pattern_str() -> &'static str {
-    r#"
-    (function_item) @function
-    (struct_item) @struct
-    (enum_item) @enum
-    (trait_item) @trait
-    (impl_item) @impl_item
-    (mod_item) @module
-    (const_item) @const
-    (static_item) @static
-    (type_item) @type_alias  ; type alias
-    (macro_definition) @macro_definition
-    (use_declaration) @use
-    ; (attribute_item) @attribute ; Attributes might be too noisy if captured this way
-    "#
+    builtin_query("rust").unwrap_or_default()
 }
@@ -37,6 +37,7 @@ pub fn parse_simple_diff(diff_text: &str) -> GitDiff {
             // Create a new file entry with empty hunks
             current_file = Some(ChangedFile {
                 path: PathBuf::new(),
+                old_path: None,
                 change_type: ChangeType::Modified, // Default to modified
                 hunks: Vec::new(),
                 file_mode_change: None,
@@ -1,4 +1,5 @@
-use gitie::cli_interface::args::{args_contain_help, args_contain_ai, args_contain_noai, should_use_ai};
+use clap::Parser;
+use gitie::cli_interface::args::{args_contain_help, args_contain_ai, args_contain_noai, should_use_ai, GitieArgs, GitieSubCommand};
 
 #[test]
 fn test_args_contain_help() {
@@ -90,4 +91,75 @@ fn test_should_use_ai() {
     // With multiple instances of --noai: AI should be disabled
     let args = vec!["commit".to_string(), "--noai".to_string(), "--noai".to_string()];
     assert!(!should_use_ai(&args));
-}
\ No newline at end of file
+}
+#[test]
+fn test_parse_amend_and_reword() {
+    // commit --amend is parsed as a gitie flag instead of a passthrough argument
+    let parsed = GitieArgs::try_parse_from(["gitie", "commit", "--amend"]).unwrap();
+    match parsed.command {
+        GitieSubCommand::Commit(args) => {
+            assert!(args.amend);
+            assert!(args.passthrough_args.is_empty());
+        }
+        other => panic!("unexpected subcommand: {:?}", other),
+    }
+
+    // reword defaults to HEAD
+    let parsed = GitieArgs::try_parse_from(["gitie", "reword"]).unwrap();
+    match parsed.command {
+        GitieSubCommand::Reword(args) => {
+            assert_eq!(args.rev, "HEAD");
+            assert!(!args.yes);
+        }
+        other => panic!("unexpected subcommand: {:?}", other),
+    }
+
+    let parsed = GitieArgs::try_parse_from(["gitie", "reword", "HEAD~2", "-y"]).unwrap();
+    match parsed.command {
+        GitieSubCommand::Reword(args) => {
+            assert_eq!(args.rev, "HEAD~2");
+            assert!(args.yes);
+        }
+        other => panic!("unexpected subcommand: {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_signing_and_trailer_flags() {
+    let parsed = GitieArgs::try_parse_from([
+        "gitie", "commit", "-s", "-S", "--trailer", "Reviewed-by: Bob <bob@example.com>",
+    ])
+    .unwrap();
+    match parsed.command {
+        GitieSubCommand::Commit(args) => {
+            assert!(args.signoff);
+            assert_eq!(args.gpg_sign.as_deref(), Some(""));
+            assert_eq!(args.trailers, vec!["Reviewed-by: Bob <bob@example.com>"]);
+        }
+        other => panic!("unexpected subcommand: {:?}", other),
+    }
+
+    let parsed = GitieArgs::try_parse_from(["gitie", "commit", "--gpg-sign=ABCD1234"]).unwrap();
+    match parsed.command {
+        GitieSubCommand::Commit(args) => {
+            assert!(!args.signoff);
+            assert_eq!(args.gpg_sign.as_deref(), Some("ABCD1234"));
+        }
+        other => panic!("unexpected subcommand: {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_review_policy() {
+    let parsed =
+        GitieArgs::try_parse_from(["gitie", "commit", "--review", "--review-policy", "block-on-error"]).unwrap();
+    match parsed.command {
+        GitieSubCommand::Commit(args) => {
+            assert!(args.review);
+            assert_eq!(args.review_policy.as_deref(), Some("block-on-error"));
+        }
+        other => panic!("unexpected subcommand: {:?}", other),
+    }
+
+    assert!(GitieArgs::try_parse_from(["gitie", "commit", "--review-policy", "sometimes"]).is_err());
+}
@@ -0,0 +1,134 @@
+use gitie::command_processing::commit_options::{
+    contains_commit_option, filter_commit_args, gpg_sign_option, parse_commit_args,
+};
+
+fn to_args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|a| a.to_string()).collect()
+}
+
+/// Filters the way `gitie commit` does: gitie options always, -a/--all only with auto-stage
+fn filter(args: &[&str], auto_stage: bool) -> Vec<String> {
+    filter_commit_args(&to_args(args), |option| {
+        option.is_gitie_option() || (auto_stage && option.is("all"))
+    })
+}
+
+#[test]
+fn test_filter_commit_args_table() {
+    // (description, input, auto_stage, expected)
+    let cases: &[(&str, &[&str], bool, &[&str])] = &[
+        ("empty", &[], false, &[]),
+        ("plain flags untouched", &["--amend", "-v"], false, &["--amend", "-v"]),
+        ("tree-sitter long flag removed", &["--tree-sitter", "--amend"], false, &["--amend"]),
+        ("tree-sitter with value removed", &["--tree-sitter", "deep", "-v"], false, &["-v"]),
+        ("tree-sitter attached value removed", &["--tree-sitter=deep", "-v"], false, &["-v"]),
+        ("short tree-sitter with value removed", &["-t", "shallow", "--amend"], false, &["--amend"]),
+        ("short tree-sitter before option keeps option", &["-t", "--amend"], false, &["--amend"]),
+        ("-a kept without auto-stage", &["-a"], false, &["-a"]),
+        ("-a removed with auto-stage", &["-a", "-v"], true, &["-v"]),
+        ("--all removed with auto-stage", &["--all"], true, &[]),
+        ("-at splits into gitie options", &["-at"], true, &[]),
+        ("-at keeps -a without auto-stage", &["-at"], false, &["-a"]),
+        ("-av keeps -v with auto-stage", &["-av"], true, &["-v"]),
+        ("-av untouched without auto-stage", &["-av"], false, &["-av"]),
+        ("gpg key id with letters untouched", &["-Sdeadbeat"], true, &["-Sdeadbeat"]),
+        ("gpg key id after -a in cluster", &["-aSkeyat"], true, &["-Skeyat"]),
+        ("long gpg key id untouched", &["--gpg-sign=ABCDtaT"], true, &["--gpg-sign=ABCDtaT"]),
+        ("attached message untouched", &["-mfix that"], true, &["-mfix that"]),
+        ("message value not parsed as options", &["-m", "-at"], true, &["-m", "-at"]),
+        ("cluster ending in -m takes next value", &["-am", "-t"], true, &["-m", "-t"]),
+        ("template value kept after -C", &["-C", "HEAD~1", "-t", "deep"], false, &["-C", "HEAD~1"]),
+        ("author value with spaces", &["--author", "A T <a@t>", "-a"], true, &["--author", "A T <a@t>"]),
+        ("untracked-files value is attached only", &["-uno", "-a"], true, &["-uno"]),
+        ("trailer value preserved", &["--trailer", "Acked-by: a", "--all"], true, &["--trailer", "Acked-by: a"]),
+        ("pathspec after separator untouched", &["-a", "--", "-t", "src/a.rs"], true, &["--", "-t", "src/a.rs"]),
+        ("pathspec before separator untouched", &["src/at.rs"], true, &["src/at.rs"]),
+        ("unknown long option untouched", &["--no-such-thing"], true, &["--no-such-thing"]),
+        ("negated flag untouched", &["--no-verify", "--no-edit"], true, &["--no-verify", "--no-edit"]),
+        ("ambiguous abbreviation untouched", &["--al"], true, &["--al"]),
+        ("abbreviated --tree removed", &["--tree", "deep"], false, &[]),
+        ("gitie-only flags removed", &["--noai", "-m", "x", "--no-verify"], false, &["-m", "x", "--no-verify"]),
+        ("ai/review/split removed", &["--ai", "--review", "--split", "-v"], false, &["-v"]),
+        ("review-policy value removed", &["--review-policy", "never", "--amend"], false, &["--amend"]),
+        ("review-policy attached value removed", &["--review-policy=block", "-s"], false, &["-s"]),
+    ];
+
+    for (description, input, auto_stage, expected) in cases {
+        assert_eq!(
+            filter(input, *auto_stage),
+            to_args(expected),
+            "case failed: {}",
+            description
+        );
+    }
+}
+
+#[test]
+fn test_contains_commit_option_table() {
+    // (input, option, expected)
+    let cases: &[(&[&str], &str, bool)] = &[
+        (&["--amend"], "amend", true),
+        (&["--amen"], "amend", true),
+        (&["--no-amend"], "amend", false),
+        (&["-m", "--amend"], "amend", false),
+        (&["--", "--amend"], "amend", false),
+        (&["--allow-empty"], "allow-empty", true),
+        (&["--allow-empty-message"], "allow-empty", false),
+        (&["-as"], "signoff", true),
+        (&["-Ss"], "signoff", false),
+        (&["-vS"], "gpg-sign", true),
+    ];
+
+    for (input, option, expected) in cases {
+        assert_eq!(
+            contains_commit_option(&to_args(input), option),
+            *expected,
+            "case failed: {:?} contains {}",
+            input,
+            option
+        );
+    }
+}
+
+#[test]
+fn test_gpg_sign_option_table() {
+    // (input, expected)
+    let cases: &[(&[&str], Option<bool>)] = &[
+        (&[], None),
+        (&["-m", "x"], None),
+        (&["-S"], Some(true)),
+        (&["-SKEYID"], Some(true)),
+        (&["--gpg-sign=KEY"], Some(true)),
+        (&["-vS"], Some(true)),
+        (&["--no-gpg-sign"], Some(false)),
+        (&["-S", "--no-gpg-sign"], Some(false)),
+        (&["--no-gpg-sign", "-S"], Some(true)),
+        (&["-m", "-S"], None),
+        (&["--", "-S"], None),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(gpg_sign_option(&to_args(input)), *expected, "case failed: {:?}", input);
+    }
+}
+
+#[test]
+fn test_parse_commit_args_values() {
+    let groups = parse_commit_args(&to_args(&["-am", "msg", "--gpg-sign=KEY", "-S", "--author=A"]));
+    assert_eq!(groups.len(), 4);
+
+    assert_eq!(groups[0].tokens, to_args(&["-am", "msg"]));
+    assert_eq!(groups[0].options.len(), 2);
+    assert!(groups[0].options[0].is("all"));
+    assert!(groups[0].options[1].is("message"));
+    assert_eq!(groups[0].options[1].value.as_deref(), Some("msg"));
+
+    assert!(groups[1].options[0].is("gpg-sign"));
+    assert_eq!(groups[1].options[0].value.as_deref(), Some("KEY"));
+
+    assert!(groups[2].options[0].is("gpg-sign"));
+    assert_eq!(groups[2].options[0].value, None);
+
+    assert!(groups[3].options[0].is("author"));
+    assert_eq!(groups[3].options[0].value.as_deref(), Some("A"));
+}
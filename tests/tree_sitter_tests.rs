@@ -125,4 +125,498 @@ index 83db48f..0000000
         assert!(change_types.contains(&ChangeType::Added));
         assert!(change_types.contains(&ChangeType::Deleted));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_typescript_and_tsx_analysis() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("types.ts"),
+            "export interface User {\n  id: number;\n}\nexport type Id = number;\n",
+        ).unwrap();
+        std::fs::write(
+            dir.path().join("Card.tsx"),
+            "export const Card = (props: { title: string }) => (\n  <div>{props.title}</div>\n);\n",
+        ).unwrap();
+
+        let diff_text = r#"diff --git a/types.ts b/types.ts
+new file mode 100644
+--- /dev/null
++++ b/types.ts
+@@ -0,0 +1,4 @@
++export interface User {
++  id: number;
++}
++export type Id = number;
+diff --git a/Card.tsx b/Card.tsx
+new file mode 100644
+--- /dev/null
++++ b/Card.tsx
+@@ -0,0 +1,3 @@
++export const Card = (props: { title: string }) => (
++  <div>{props.title}</div>
++);
+"#;
+
+        let mut analyzer = TreeSitterAnalyzer::new(TreeSitterConfig::default()).unwrap();
+        analyzer.set_project_root(dir.path().to_path_buf());
+        let analysis = analyzer.analyze_diff(diff_text).unwrap();
+
+        let node_types = |name: &str| -> Vec<String> {
+            analysis.file_analyses
+                .iter()
+                .find(|a| a.path.ends_with(name))
+                .unwrap()
+                .affected_nodes
+                .iter()
+                .map(|n| format!("{}:{}", n.node_type, n.name))
+                .collect()
+        };
+        let ts_nodes = node_types("types.ts");
+        assert!(ts_nodes.contains(&"interface:User".to_string()), "{:?}", ts_nodes);
+        assert!(ts_nodes.contains(&"type:Id".to_string()), "{:?}", ts_nodes);
+        let tsx_nodes = node_types("Card.tsx");
+        assert!(tsx_nodes.contains(&"component:Card".to_string()), "{:?}", tsx_nodes);
+        assert!(tsx_nodes.contains(&"function:Card".to_string()), "{:?}", tsx_nodes);
+    }
+
+    #[test]
+    fn test_cpp_header_declarations_are_public_api() {
+        let dir = tempfile::tempdir().unwrap();
+        let header = "namespace geo {\nclass Shape {\npublic:\n    double area() const;\nprivate:\n    void recalc();\n};\nnamespace detail { int helper(int x); }\n}\nstatic int local_count(void);\nint shape_count(void);\n";
+        let source = "#include \"shape.h\"\ndouble geo::Shape::area() const { return 0; }\n";
+        std::fs::write(dir.path().join("shape.h"), header).unwrap();
+        std::fs::write(dir.path().join("shape.cpp"), source).unwrap();
+
+        let added = |path: &str, content: &str| {
+            let lines: Vec<String> = content.lines().map(|l| format!("+{}", l)).collect();
+            format!(
+                "diff --git a/{0} b/{0}\nnew file mode 100644\n--- /dev/null\n+++ b/{0}\n@@ -0,0 +1,{1} @@\n{2}\n",
+                path, lines.len(), lines.join("\n")
+            )
+        };
+        let diff_text = format!("{}{}", added("shape.h", header), added("shape.cpp", source));
+
+        let mut analyzer = TreeSitterAnalyzer::new(TreeSitterConfig::default()).unwrap();
+        analyzer.set_project_root(dir.path().to_path_buf());
+        let analysis = analyzer.analyze_diff(&diff_text).unwrap();
+
+        let visibility = |file: &str, node_type: &str, name: &str| -> bool {
+            analysis.file_analyses
+                .iter()
+                .find(|a| a.path.ends_with(file))
+                .unwrap()
+                .affected_nodes
+                .iter()
+                .find(|n| n.node_type == node_type && n.name == name)
+                .unwrap_or_else(|| panic!("{} {} not found in {}", node_type, name, file))
+                .is_public
+        };
+        assert!(visibility("shape.h", "class", "Shape"));
+        assert!(visibility("shape.h", "method", "area"));
+        assert!(!visibility("shape.h", "method", "recalc"));
+        assert!(!visibility("shape.h", "function", "helper"));
+        assert!(!visibility("shape.h", "function", "local_count"));
+        assert!(visibility("shape.h", "function", "shape_count"));
+        assert!(!visibility("shape.cpp", "method", "geo::Shape::area"));
+
+        let header_summary = analysis.file_analyses[0].summary.clone().unwrap();
+        assert!(header_summary.contains("公共 API 变更"), "{}", header_summary);
+    }
+
+    #[test]
+    fn test_python_and_go_structural_analysis() {
+        let dir = tempfile::tempdir().unwrap();
+        let python = "__all__ = [\"Client\", \"fetch\"]\n\n\nclass Client:\n    def send(self):\n        pass\n\n    def _retry(self):\n        pass\n\n    @property\n    def url(self):\n        return self._url\n\n\nasync def fetch(url):\n    return url\n\n\ndef helper():\n    pass\n\n\n@app.route(\"/items\")\ndef list_items():\n    return []\n";
+        let go = "package store\n\ntype Store interface {\n\tGet(key string) string\n}\n\ntype memStore struct {\n\tdata map[string]string\n}\n\nfunc (m *memStore) Get(key string) string {\n\treturn m.data[key]\n}\n\nfunc NewStore() Store {\n\treturn &memStore{}\n}\n\nconst MaxKeys = 10\n";
+        std::fs::write(dir.path().join("client.py"), python).unwrap();
+        std::fs::write(dir.path().join("store.go"), go).unwrap();
+
+        let added = |path: &str, content: &str| {
+            let lines: Vec<String> = content.lines().map(|l| format!("+{}", l)).collect();
+            format!(
+                "diff --git a/{0} b/{0}\nnew file mode 100644\n--- /dev/null\n+++ b/{0}\n@@ -0,0 +1,{1} @@\n{2}\n",
+                path, lines.len(), lines.join("\n")
+            )
+        };
+        let diff_text = format!("{}{}", added("client.py", python), added("store.go", go));
+
+        let config = TreeSitterConfig {
+            languages: vec!["python".to_string(), "go".to_string()],
+            ..Default::default()
+        };
+        let mut analyzer = TreeSitterAnalyzer::new(config).unwrap();
+        analyzer.set_project_root(dir.path().to_path_buf());
+        let analysis = analyzer.analyze_diff(&diff_text).unwrap();
+
+        let visibility = |file: &str, node_type: &str, name: &str| -> bool {
+            analysis.file_analyses
+                .iter()
+                .find(|a| a.path.ends_with(file))
+                .unwrap()
+                .affected_nodes
+                .iter()
+                .find(|n| n.node_type == node_type && n.name == name)
+                .unwrap_or_else(|| panic!("{} {} not found in {}", node_type, name, file))
+                .is_public
+        };
+        assert!(visibility("client.py", "class", "Client"));
+        assert!(visibility("client.py", "method", "send"));
+        assert!(!visibility("client.py", "method", "_retry"));
+        assert!(visibility("client.py", "property", "url"));
+        assert!(visibility("client.py", "async_function", "fetch"));
+        assert!(!visibility("client.py", "function", "helper"));
+        assert!(!visibility("client.py", "api_endpoint", "list_items"));
+        assert!(visibility("client.py", "public_api", "__all__"));
+
+        assert!(visibility("store.go", "interface", "Store"));
+        assert!(!visibility("store.go", "struct", "memStore"));
+        assert!(!visibility("store.go", "method", "memStore.Get"));
+        assert!(visibility("store.go", "function", "NewStore"));
+        assert!(visibility("store.go", "const", "MaxKeys"));
+
+        let go_summary = analysis.file_analyses[1].summary.clone().unwrap();
+        assert!(go_summary.contains("导出 API 变更: Store"), "{}", go_summary);
+    }
+
+    #[test]
+    fn test_project_query_files_extend_builtin_queries() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = "pub fn handler() {}\n\nregister_route!(handler);\n";
+        std::fs::write(dir.path().join("routes.rs"), source).unwrap();
+        let query_dir = dir.path().join(".gitie/queries/rust");
+        std::fs::create_dir_all(&query_dir).unwrap();
+        std::fs::write(
+            query_dir.join("routes.scm"),
+            "(macro_invocation macro: (identifier) @route.name (#eq? @route.name \"register_route\")) @route.declaration\n",
+        )
+        .unwrap();
+
+        let lines: Vec<String> = source.lines().map(|l| format!("+{}", l)).collect();
+        let diff_text = format!(
+            "diff --git a/routes.rs b/routes.rs\nnew file mode 100644\n--- /dev/null\n+++ b/routes.rs\n@@ -0,0 +1,{} @@\n{}\n",
+            lines.len(), lines.join("\n")
+        );
+
+        let mut analyzer = TreeSitterAnalyzer::new(TreeSitterConfig::default()).unwrap();
+        analyzer.set_project_root(dir.path().to_path_buf());
+        let analysis = analyzer.analyze_diff(&diff_text).unwrap();
+        let nodes = &analysis.file_analyses[0].affected_nodes;
+        assert!(nodes.iter().any(|n| n.node_type == "function" && n.name == "handler"));
+        assert!(nodes.iter().any(|n| n.node_type == "route" && n.name == "register_route"));
+
+        // 无效的自定义查询回退到内置查询
+        std::fs::write(query_dir.join("routes.scm"), "(no_such_node) @broken\n").unwrap();
+        analyzer.set_project_root(dir.path().to_path_buf());
+        let analysis = analyzer.analyze_diff(&diff_text).unwrap();
+        let nodes = &analysis.file_analyses[0].affected_nodes;
+        assert!(nodes.iter().any(|n| n.node_type == "function" && n.name == "handler"));
+        assert!(!nodes.iter().any(|n| n.node_type == "route"));
+    }
+
+    #[test]
+    fn test_runtime_grammar_config_and_missing_library() {
+        let config: TreeSitterConfig = toml::from_str(
+            "languages = [\"rust\", \"kotlin\"]\n\n[grammars.kotlin]\npath = \"/nonexistent/libtree-sitter-kotlin.so\"\nextensions = [\"kt\", \".kts\"]\n",
+        )
+        .unwrap();
+        let kotlin = &config.grammars["kotlin"];
+        assert_eq!(kotlin.path, PathBuf::from("/nonexistent/libtree-sitter-kotlin.so"));
+        assert_eq!(kotlin.symbol, None);
+        assert_eq!(kotlin.extensions, vec!["kt".to_string(), ".kts".to_string()]);
+
+        // 动态库加载失败时只跳过该语法，不影响其他语言
+        let analyzer = TreeSitterAnalyzer::new(config).unwrap();
+        assert_eq!(analyzer.detect_language(&PathBuf::from("Main.kt")).unwrap(), None);
+        assert_eq!(analyzer.detect_language(&PathBuf::from("main.rs")).unwrap(), Some("rust".to_string()));
+    }
+
+    #[test]
+    fn test_diff_file_versions_matches_definitions() {
+        use gitie::tree_sitter_analyzer::{FileVersions, ast_diff::DefinitionChangeKind};
+
+        let old_lib = "pub struct Parser {\n    pos: usize,\n}\n\nimpl Parser {\n    pub fn parse(&self) -> usize {\n        self.pos\n    }\n\n    fn helper(&self) -> usize {\n        1\n    }\n}\n\nfn compute(x: i32) -> i32 {\n    let y = x * 2;\n    y + 1\n}\n\nfn obsolete() {\n    println!(\"old\");\n}\n\nfn shared_util() -> u8 {\n    7\n}\n";
+        let new_lib = "pub struct Parser {\n    pos: usize,\n}\n\nimpl Parser {\n    fn helper(&self) -> usize {\n        1\n    }\n\n    pub fn parse(&self) -> usize {\n        self.pos + 1\n    }\n}\n\nfn calculate(x: i32) -> i32 {\n    let y = x * 2;\n    y + 1\n}\n\nfn fresh() -> bool {\n    true\n}\n";
+        let new_util = "fn shared_util() -> u8 {\n    7\n}\n";
+
+        let files = vec![
+            FileVersions {
+                old_path: Some(PathBuf::from("src/lib.rs")),
+                new_path: Some(PathBuf::from("src/lib.rs")),
+                old_source: Some(old_lib.to_string()),
+                new_source: Some(new_lib.to_string()),
+            },
+            FileVersions {
+                old_path: None,
+                new_path: Some(PathBuf::from("src/util.rs")),
+                old_source: None,
+                new_source: Some(new_util.to_string()),
+            },
+            FileVersions {
+                old_path: Some(PathBuf::from("README.md")),
+                new_path: Some(PathBuf::from("README.md")),
+                old_source: Some("old".to_string()),
+                new_source: Some("new".to_string()),
+            },
+        ];
+
+        let mut analyzer = TreeSitterAnalyzer::new(TreeSitterConfig::default()).unwrap();
+        let diff = analyzer.diff_file_versions(&files);
+        let changes: Vec<(DefinitionChangeKind, &str)> = diff.changes.iter().map(|c| (c.kind, c.name())).collect();
+
+        assert!(changes.contains(&(DefinitionChangeKind::Moved, "Parser::parse")), "{:?}", changes);
+        assert!(changes.contains(&(DefinitionChangeKind::Renamed, "calculate")), "{:?}", changes);
+        assert!(changes.contains(&(DefinitionChangeKind::Removed, "obsolete")), "{:?}", changes);
+        assert!(changes.contains(&(DefinitionChangeKind::Added, "fresh")), "{:?}", changes);
+        assert!(changes.contains(&(DefinitionChangeKind::Moved, "shared_util")), "{:?}", changes);
+        assert_eq!(changes.len(), 5, "{:?}", changes);
+        assert_eq!(diff.skipped, vec![PathBuf::from("README.md")]);
+
+        let parse = diff.changes.iter().find(|c| c.name() == "Parser::parse").unwrap();
+        assert!(parse.content_changed);
+        let moved = diff.changes.iter().find(|c| c.name() == "shared_util").unwrap();
+        assert_eq!(moved.old.as_ref().unwrap().path, PathBuf::from("src/lib.rs"));
+        assert_eq!(moved.new.as_ref().unwrap().path, PathBuf::from("src/util.rs"));
+        assert!(diff.to_markdown().contains("重命名: function `compute` → `calculate`"));
+    }
+
+    #[test]
+    fn test_parse_git_diff_text_tracks_old_paths() {
+        let diff_text = "diff --git a/src/old.rs b/src/new.rs\nsimilarity index 100%\nrename from src/old.rs\nrename to src/new.rs\ndiff --git a/src/gone.rs b/src/gone.rs\ndeleted file mode 100644\n--- a/src/gone.rs\n+++ /dev/null\n@@ -1 +0,0 @@\n-fn gone() {}\n";
+        let analyzer = TreeSitterAnalyzer::new(TreeSitterConfig::default()).unwrap();
+        let git_diff = analyzer.parse_git_diff_text(diff_text).unwrap();
+
+        assert_eq!(git_diff.changed_files.len(), 2);
+        let renamed = &git_diff.changed_files[0];
+        assert_eq!(renamed.change_type, ChangeType::Renamed);
+        assert_eq!(renamed.path, PathBuf::from("src/new.rs"));
+        assert_eq!(renamed.old_path, Some(PathBuf::from("src/old.rs")));
+        let deleted = &git_diff.changed_files[1];
+        assert_eq!(deleted.change_type, ChangeType::Deleted);
+        assert_eq!(deleted.path, PathBuf::from("src/gone.rs"));
+        assert_eq!(deleted.hunks.len(), 1);
+    }
+
+    #[test]
+    fn test_analyze_diff_reports_moved_code() {
+        let dir = tempfile::tempdir().unwrap();
+        let checksum_old = [
+            "fn checksum(data: &[u8]) -> u32 {",
+            "    let mut sum = 0u32;",
+            "    for byte in data {",
+            "        sum = sum.wrapping_add(*byte as u32);",
+            "    }",
+            "    if sum == 0 {",
+            "        return 1;",
+            "    }",
+            "    sum",
+            "}",
+        ];
+        let checksum_new = [
+            "pub fn checksum(data: &[u8]) -> u32 {",
+            "    let mut sum = 0u32;",
+            "    for byte in data {",
+            "        sum = sum.wrapping_add(*byte as u32);",
+            "    }",
+            "    if sum == 0 {",
+            "        return 7;",
+            "    }",
+            "    sum",
+            "}",
+        ];
+        std::fs::write(dir.path().join("lib.rs"), "pub fn keep() -> i32 {\n    1\n}\n").unwrap();
+        std::fs::write(dir.path().join("hash.rs"), format!("{}\n", checksum_new.join("\n"))).unwrap();
+
+        let removed: Vec<String> = checksum_old.iter().map(|l| format!("-{}", l)).collect();
+        let added: Vec<String> = checksum_new.iter().map(|l| format!("+{}", l)).collect();
+        let diff_text = format!(
+            "diff --git a/lib.rs b/lib.rs\n--- a/lib.rs\n+++ b/lib.rs\n@@ -1,14 +1,3 @@\n pub fn keep() -> i32 {{\n     1\n }}\n-\n{}\n\
+             diff --git a/hash.rs b/hash.rs\nnew file mode 100644\n--- /dev/null\n+++ b/hash.rs\n@@ -0,0 +1,10 @@\n{}\n",
+            removed.join("\n"),
+            added.join("\n")
+        );
+
+        let mut analyzer = TreeSitterAnalyzer::new(TreeSitterConfig::default()).unwrap();
+        analyzer.set_project_root(dir.path().to_path_buf());
+        let analysis = analyzer.analyze_diff(&diff_text).unwrap();
+
+        assert_eq!(analysis.moved_code.len(), 1, "{:?}", analysis.moved_code);
+        let moved = &analysis.moved_code[0];
+        assert_eq!(moved.name(), "checksum");
+        assert!(moved.content_changed);
+        assert_eq!(moved.old.as_ref().unwrap().path, PathBuf::from("lib.rs"));
+        assert_eq!(moved.old.as_ref().unwrap().start_line, 5);
+        assert_eq!(moved.new.as_ref().unwrap().path, PathBuf::from("hash.rs"));
+        assert!(moved.describe().contains("lib.rs:5 → hash.rs:1"));
+        assert!(analysis.overall_summary.contains("1个代码块被移动"));
+
+        let hash_file = analysis.file_analyses.iter().find(|f| f.path.ends_with("hash.rs")).unwrap();
+        assert!(hash_file.affected_nodes.iter().any(|n| n.change_type.as_deref() == Some("moved")));
+    }
+
+    fn api_files(path: &str, old: Option<&str>, new: Option<&str>) -> Vec<gitie::tree_sitter_analyzer::FileVersions> {
+        vec![gitie::tree_sitter_analyzer::FileVersions {
+            old_path: old.map(|_| PathBuf::from(path)),
+            new_path: new.map(|_| PathBuf::from(path)),
+            old_source: old.map(str::to_string),
+            new_source: new.map(str::to_string),
+        }]
+    }
+
+    #[test]
+    fn test_public_api_visibility_rules() {
+        use gitie::tree_sitter_analyzer::api_surface::ApiImpact;
+
+        let rust = "pub fn open() {}\npub(crate) fn internal() {}\nfn private() {}\n\nmod hidden {\n    pub fn not_exported() {}\n}\n\npub mod api {\n    pub fn exported() {\n        fn local() {}\n    }\n}\n\npub trait Store {\n    fn get(&self) -> u32;\n    fn size(&self) -> usize { 0 }\n}\n\npub struct Config {\n    pub name: String,\n    secret: u32,\n}\n\nimpl Config {\n    pub fn new() -> Self { todo!() }\n    fn check(&self) {}\n}\n\nimpl Store for Config {\n    fn get(&self) -> u32 { 1 }\n}\n";
+        let mut analyzer = TreeSitterAnalyzer::new(TreeSitterConfig::default()).unwrap();
+        let report = analyzer.diff_public_api(&api_files("src/lib.rs", None, Some(rust)));
+        let mut names: Vec<&str> = report.changes_with(ApiImpact::Additive).map(|c| c.name.as_str()).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["Config", "Config::new", "Store", "Store for Config", "Store::get", "Store::size", "api", "api::exported", "open"],
+            "{:?}",
+            report.changes
+        );
+        let config = report.changes.iter().find(|c| c.name == "Config").unwrap();
+        assert_eq!(config.new_signature.as_deref(), Some("pub struct Config { pub name: String }"));
+
+        let java = "package com.example;\n\npublic interface Repository {\n    User find(long id);\n    default int count() { return 0; }\n    private void helper() {}\n}\n\nclass Hidden {\n    public void visible() {}\n}\n\npublic class Service {\n    public Service() {}\n    protected void hook() {}\n    void packagePrivate() {}\n    public String name;\n    public enum Mode { FAST, SLOW }\n    private static class Inner {\n        public void run() {}\n    }\n}\n";
+        let report = analyzer.diff_public_api(&api_files("src/main/java/com/example/Service.java", None, Some(java)));
+        let mut names: Vec<&str> = report.changes_with(ApiImpact::Additive).map(|c| c.name.as_str()).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["Repository", "Repository::count", "Repository::find", "Service", "Service::Mode", "Service::Service", "Service::name"],
+            "{:?}",
+            report.changes
+        );
+        let mode = report.changes.iter().find(|c| c.name == "Service::Mode").unwrap();
+        assert_eq!(mode.new_signature.as_deref(), Some("public enum Mode { FAST, SLOW }"));
+    }
+
+    #[test]
+    fn test_public_api_diff_classifies_changes() {
+        use gitie::tree_sitter_analyzer::{SemverBump, api_surface::ApiImpact};
+
+        let old = "pub fn parse(input: &str) -> u32 {\n    input.len() as u32\n}\n\npub fn legacy() {}\n\nfn helper() -> u8 {\n    1\n}\n\npub trait Store {\n    fn get(&self) -> u32;\n}\n";
+        let body_only = "pub fn parse(input: &str) -> u32 {\n    // 只修改实现\n    input.trim().len() as u32\n}\n\npub fn legacy() {}\n\nfn helper() -> u8 {\n    2\n}\n\npub trait Store {\n    fn get(&self) -> u32;\n}\n";
+        let mut analyzer = TreeSitterAnalyzer::new(TreeSitterConfig::default()).unwrap();
+        let report = analyzer.diff_public_api(&api_files("src/lib.rs", Some(old), Some(body_only)));
+        assert_eq!(report.bump, SemverBump::Patch, "{:?}", report.changes);
+        assert!(report.changes.iter().all(|c| c.impact == ApiImpact::Internal));
+        assert_eq!(report.changes.len(), 2);
+
+        let additive = format!("{}\npub fn extra() {{}}\n", old);
+        let report = analyzer.diff_public_api(&api_files("src/lib.rs", Some(old), Some(&additive)));
+        assert_eq!(report.bump, SemverBump::Minor, "{:?}", report.changes);
+        assert!(!report.has_breaking_changes());
+
+        let breaking = "pub fn parse(input: &str, strict: bool) -> u32 {\n    input.len() as u32\n}\n\npub(crate) fn legacy() {}\n\nfn helper() -> u8 {\n    1\n}\n\npub trait Store {\n    fn get(&self) -> u32;\n    fn put(&mut self, value: u32);\n}\n";
+        let report = analyzer.diff_public_api(&api_files("src/lib.rs", Some(old), Some(breaking)));
+        assert_eq!(report.bump, SemverBump::Major);
+        let breaking: Vec<(&str, &str)> = report
+            .changes_with(ApiImpact::Breaking)
+            .map(|c| (c.name.as_str(), c.description.as_str()))
+            .collect();
+        assert_eq!(
+            breaking,
+            vec![("parse", "签名变更"), ("legacy", "删除或不再公开"), ("Store::put", "新增必须实现的成员")],
+            "{:?}",
+            report.changes
+        );
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("建议版本升级：major"));
+        assert!(markdown.contains("`pub fn parse(input: &str) -> u32` → `pub fn parse(input: &str, strict: bool) -> u32`"), "{}", markdown);
+    }
+
+    #[test]
+    fn test_analysis_cache_is_persisted_in_git_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        let source = "pub struct Config {\n    pub name: String,\n}\n\npub fn load() -> Config {\n    Config { name: String::new() }\n}\n\nfn helper() -> u8 {\n    2\n}\n";
+        std::fs::write(dir.path().join("lib.rs"), source).unwrap();
+        let diff_text = "diff --git a/lib.rs b/lib.rs\n--- a/lib.rs\n+++ b/lib.rs\n@@ -9,3 +9,3 @@\n fn helper() -> u8 {\n-    1\n+    2\n }\n";
+        let old = source.replace("pub fn load", "pub fn load_config");
+        let files = api_files("lib.rs", Some(&old), Some(source));
+        let cache_dir = dir.path().join(".git/gitie/analysis-cache");
+
+        // 每次运行都使用新的分析器，第二次的结果来自持久化缓存
+        let run = |config: TreeSitterConfig| {
+            let mut analyzer = TreeSitterAnalyzer::new(config).unwrap();
+            analyzer.set_project_root(dir.path().to_path_buf());
+            let analysis = analyzer.analyze_diff(diff_text).unwrap();
+            let report = analyzer.diff_public_api(&files);
+            (
+                format!("{:?}", analysis.file_analyses),
+                analysis.overall_summary,
+                serde_json::to_string(&report).unwrap(),
+            )
+        };
+
+        let uncached = run(TreeSitterConfig {
+            cache_enabled: false,
+            ..Default::default()
+        });
+        assert!(!cache_dir.exists());
+
+        let first = run(TreeSitterConfig::default());
+        let entries = std::fs::read_dir(&cache_dir).unwrap().count();
+        assert!(entries >= 2, "{}", entries);
+        let second = run(TreeSitterConfig::default());
+        assert_eq!(first, uncached);
+        assert_eq!(second, first);
+        assert!(first.0.contains("helper"), "{}", first.0);
+        assert!(first.2.contains("load_config"), "{}", first.2);
+    }
+
+    #[test]
+    fn test_parallel_analysis_is_deterministic_and_enforces_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut diff_text = String::new();
+        for i in 0..12 {
+            let (name, source) = if i % 2 == 0 {
+                (format!("mod_{}.rs", i), format!("pub fn run_{i}() -> u32 {{\n    {i}\n}}\n\nfn helper_{i}() {{}}\n"))
+            } else {
+                (format!("mod_{}.py", i), format!("def run_{i}():\n    return {i}\n\nclass Model{i}:\n    pass\n"))
+            };
+            std::fs::write(dir.path().join(&name), &source).unwrap();
+            let added: Vec<String> = source.lines().map(|l| format!("+{}", l)).collect();
+            diff_text.push_str(&format!(
+                "diff --git a/{0} b/{0}\nnew file mode 100644\n--- /dev/null\n+++ b/{0}\n@@ -0,0 +1,{1} @@\n{2}\n",
+                name,
+                added.len(),
+                added.join("\n")
+            ));
+        }
+
+        let run = |threads: usize, timeout_ms: u64, diff_text: &str| {
+            let config = TreeSitterConfig {
+                analysis_threads: threads,
+                file_timeout_ms: timeout_ms,
+                ..Default::default()
+            };
+            let mut analyzer = TreeSitterAnalyzer::new(config).unwrap();
+            analyzer.set_project_root(dir.path().to_path_buf());
+            analyzer.analyze_diff(diff_text).unwrap()
+        };
+
+        let sequential = run(1, 0, &diff_text);
+        let parallel = run(4, 0, &diff_text);
+        assert_eq!(format!("{:?}", parallel.file_analyses), format!("{:?}", sequential.file_analyses));
+        assert_eq!(parallel.overall_summary, sequential.overall_summary);
+        let paths: Vec<_> = parallel.file_analyses.iter().map(|f| f.path.file_name().unwrap().to_owned()).collect();
+        assert_eq!(paths[0], "mod_0.rs");
+        assert_eq!(paths[11], "mod_11.py");
+        assert!(parallel.file_analyses.iter().all(|f| !f.affected_nodes.is_empty()));
+
+        // 超过单个文件时间预算的文件被跳过，分析本身不失败；深度嵌套的表达式只有几 KB，
+        // 但解析远超 1 毫秒
+        let nested = format!("fn f0() -> u32 {{ {}0{} }}\n", "(".repeat(3000), ")".repeat(3000));
+        std::fs::write(dir.path().join("nested.rs"), &nested).unwrap();
+        let nested_diff = "diff --git a/nested.rs b/nested.rs\n--- a/nested.rs\n+++ b/nested.rs\n@@ -1,1 +1,1 @@\n-fn f0() -> u32 { 1 }\n+fn f0() -> u32 { 0 }\n";
+        let analysis = run(2, 1, nested_diff);
+        let nested_file = &analysis.file_analyses[0];
+        assert!(nested_file.affected_nodes.is_empty());
+        assert!(nested_file.summary.as_deref().unwrap_or_default().contains("分析超时"), "{:?}", nested_file.summary);
+    }
+}